@@ -1,6 +1,6 @@
 'use client';
 
-import React, { useState, useEffect, useCallback } from 'react';
+import React, { useState, useEffect, useCallback, useRef } from 'react';
 import { getCryptoProcessor, CryptoResult, EncryptionResult, HashVisualization } from '../lib/wasm-crypto-processor';
 
 interface CryptoDemoState {
@@ -61,6 +61,7 @@ export default function CryptographicDemo() {
   });
 
   const cryptoProcessor = getCryptoProcessor();
+  const identiconCanvasRef = useRef<HTMLCanvasElement>(null);
 
   useEffect(() => {
     const initializeProcessor = async () => {
@@ -80,6 +81,16 @@ export default function CryptographicDemo() {
     initializeProcessor();
   }, [cryptoProcessor]);
 
+  // Draw the identicon's RGBA buffer whenever a new visualization arrives
+  useEffect(() => {
+    const visualization = state.hashVisualization;
+    const ctx = identiconCanvasRef.current?.getContext('2d');
+    if (!visualization || !ctx) return;
+
+    const { identicon, size } = visualization;
+    ctx.putImageData(new ImageData(identicon, size, size), 0, 0);
+  }, [state.hashVisualization]);
+
   const updateState = useCallback((updates: Partial<CryptoDemoState>) => {
     setState(prev => ({ ...prev, ...updates }));
   }, []);
@@ -220,37 +231,25 @@ export default function CryptographicDemo() {
   const renderHashVisualization = () => {
     if (!state.hashVisualization) return null;
 
-    const { pattern, size, color } = state.hashVisualization;
-    const cellSize = 20;
+    const { size, color, digest, randart } = state.hashVisualization;
 
     return (
       <div className="mt-4">
         <h4 className="text-sm font-medium text-gray-700 mb-2">Hash Visualization</h4>
-        <div className="flex items-center gap-4">
-          <div
-            className="grid gap-1 p-2 bg-gray-50 rounded"
-            style={{
-              gridTemplateColumns: `repeat(${size}, ${cellSize}px)`,
-              gridTemplateRows: `repeat(${size}, ${cellSize}px)`
-            }}
-          >
-            {pattern.map((colorValue, index) => (
-              <div
-                key={index}
-                className="rounded-sm"
-                style={{
-                  backgroundColor: cryptoProcessor.colorToHex(colorValue),
-                  width: cellSize,
-                  height: cellSize
-                }}
-              />
-            ))}
-          </div>
+        <div className="flex items-start gap-4">
+          <canvas
+            ref={identiconCanvasRef}
+            width={size}
+            height={size}
+            className="rounded border border-gray-200"
+          />
+          <pre className="text-xs font-mono leading-tight p-2 bg-gray-50 rounded">{randart}</pre>
           <div className="text-sm text-gray-600">
             <div>Hash Color: <span style={{ color: cryptoProcessor.colorToHex(color) }}>
               {cryptoProcessor.colorToHex(color)}
             </span></div>
-            <div>Pattern: {size}×{size} grid</div>
+            <div>Identicon: 5×5 mirrored, from SHA-256</div>
+            <div className="font-mono text-xs break-all">{digest}</div>
           </div>
         </div>
       </div>
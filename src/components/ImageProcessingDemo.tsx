@@ -5,10 +5,11 @@ import {
   loadWASMImageProcessor,
   type WASMImageProcessor
 } from '@/lib/wasm-image-processor'
+import { applyFilterJSInPlace, type FilterType } from '@/lib/js-image-filters'
 
 interface FilterConfig {
   name: string
-  type: 'blur' | 'edge' | 'color' | 'brightness' | 'contrast' | 'sharpen'
+  type: FilterType
   label: string
   hasParameter: boolean
   parameterLabel?: string
@@ -113,6 +114,8 @@ interface PerformanceMetrics {
   jsTime: number
   speedup: number
   memoryUsage: number
+  // SSIM between the WASM and JS results (1 = identical), when the module exports it
+  parity?: number
 }
 
 interface PerformanceHistoryEntry {
@@ -140,6 +143,8 @@ interface PerformanceSummary {
 export default function ImageProcessingDemo() {
   const wasmCanvasRef = useRef<HTMLCanvasElement>(null)
   const jsCanvasRef = useRef<HTMLCanvasElement>(null)
+  const srgbCanvasRef = useRef<HTMLCanvasElement>(null)
+  const linearCanvasRef = useRef<HTMLCanvasElement>(null)
   const fileInputRef = useRef<HTMLInputElement>(null)
   const [wasmModule, setWasmModule] = useState<WASMImageProcessor | null>(null)
   const [isLoading, setIsLoading] = useState(false)
@@ -155,6 +160,8 @@ export default function ImageProcessingDemo() {
     FILTERS[0].parameterDefault || 1
   )
   const [isProcessing, setIsProcessing] = useState(false)
+  // Show the selected filter run by WASM in sRGB next to the same filter in linear light
+  const [showGammaComparison, setShowGammaComparison] = useState(false)
   const [performanceMetrics, setPerformanceMetrics] =
     useState<PerformanceMetrics | null>(null)
   const [performanceHistory, setPerformanceHistory] = useState<PerformanceHistoryEntry[]>([])
@@ -390,121 +397,14 @@ export default function ImageProcessingDemo() {
       const startTime = performance.now()
       const data = new Uint8ClampedArray(imageData.data)
 
-      // Simple JavaScript implementations for comparison
-      switch (filter.type) {
-        case 'blur':
-          // Simple box blur with parameter support
-          const radius = Math.max(1, Math.floor(parameter))
-          for (let y = radius; y < imageData.height - radius; y++) {
-            for (let x = radius; x < imageData.width - radius; x++) {
-              for (let c = 0; c < 3; c++) {
-                let sum = 0
-                let count = 0
-                for (let dy = -radius; dy <= radius; dy++) {
-                  for (let dx = -radius; dx <= radius; dx++) {
-                    const idx = ((y + dy) * imageData.width + (x + dx)) * 4 + c
-                    sum += data[idx]
-                    count++
-                  }
-                }
-                const idx = (y * imageData.width + x) * 4 + c
-                data[idx] = sum / count
-              }
-            }
-          }
-          break
-        case 'edge':
-          // Simple edge detection
-          for (let y = 1; y < imageData.height - 1; y++) {
-            for (let x = 1; x < imageData.width - 1; x++) {
-              const idx = (y * imageData.width + x) * 4
-              const gray =
-                0.299 * data[idx] +
-                0.587 * data[idx + 1] +
-                0.114 * data[idx + 2]
-              data[idx] = data[idx + 1] = data[idx + 2] = gray > 128 ? 255 : 0
-            }
-          }
-          break
-        case 'color':
-          for (let i = 0; i < data.length; i += 4) {
-            if (filter.name === 'grayscale') {
-              const gray =
-                0.299 * data[i] + 0.587 * data[i + 1] + 0.114 * data[i + 2]
-              data[i] = data[i + 1] = data[i + 2] = gray
-            } else if (filter.name === 'invert') {
-              data[i] = 255 - data[i]
-              data[i + 1] = 255 - data[i + 1]
-              data[i + 2] = 255 - data[i + 2]
-            } else if (filter.name === 'sepia') {
-              const r = data[i]
-              const g = data[i + 1]
-              const b = data[i + 2]
-              data[i] = Math.min(255, r * 0.393 + g * 0.769 + b * 0.189)
-              data[i + 1] = Math.min(255, r * 0.349 + g * 0.686 + b * 0.168)
-              data[i + 2] = Math.min(255, r * 0.272 + g * 0.534 + b * 0.131)
-            } else if (filter.name === 'red') {
-              data[i + 1] = 0 // Remove green
-              data[i + 2] = 0 // Remove blue
-            } else if (filter.name === 'green') {
-              data[i] = 0 // Remove red
-              data[i + 2] = 0 // Remove blue
-            } else if (filter.name === 'blue') {
-              data[i] = 0 // Remove red
-              data[i + 1] = 0 // Remove green
-            }
-          }
-          break
-        case 'brightness':
-          for (let i = 0; i < data.length; i += 4) {
-            data[i] = Math.min(255, Math.max(0, data[i] * parameter))
-            data[i + 1] = Math.min(255, Math.max(0, data[i + 1] * parameter))
-            data[i + 2] = Math.min(255, Math.max(0, data[i + 2] * parameter))
-          }
-          break
-        case 'contrast':
-          // Contrast adjustment: factor = (259 * (contrast + 255)) / (255 * (259 - contrast))
-          const factor = (259 * (parameter + 255)) / (255 * (259 - parameter))
-          for (let i = 0; i < data.length; i += 4) {
-            data[i] = Math.min(255, Math.max(0, factor * (data[i] - 128) + 128))
-            data[i + 1] = Math.min(
-              255,
-              Math.max(0, factor * (data[i + 1] - 128) + 128)
-            )
-            data[i + 2] = Math.min(
-              255,
-              Math.max(0, factor * (data[i + 2] - 128) + 128)
-            )
-          }
-          break
-        case 'sharpen':
-          // Simple unsharp mask approximation
-          const strength = parameter
-          const tempData = new Uint8ClampedArray(data)
-          for (let y = 1; y < imageData.height - 1; y++) {
-            for (let x = 1; x < imageData.width - 1; x++) {
-              for (let c = 0; c < 3; c++) {
-                const idx = (y * imageData.width + x) * 4 + c
-                const original = tempData[idx]
-
-                // Calculate blur value (simple 3x3 average)
-                let blur = 0
-                for (let dy = -1; dy <= 1; dy++) {
-                  for (let dx = -1; dx <= 1; dx++) {
-                    blur +=
-                      tempData[((y + dy) * imageData.width + (x + dx)) * 4 + c]
-                  }
-                }
-                blur /= 9
-
-                // Apply unsharp mask: original + strength * (original - blur)
-                const sharpened = original + strength * (original - blur)
-                data[idx] = Math.min(255, Math.max(0, sharpened))
-              }
-            }
-          }
-          break
-      }
+      applyFilterJSInPlace(
+        data,
+        imageData.width,
+        imageData.height,
+        filter.type,
+        filter.name,
+        parameter
+      )
 
       const endTime = performance.now()
       const processingTime = endTime - startTime
@@ -557,11 +457,20 @@ export default function ImageProcessingDemo() {
       const speedup = jsResult.processingTime / wasmResult.processingTime
       const memoryUsage = wasmModule ? wasmModule.get_memory_usage() : 0
 
+      const { width, height } = wasmResult.processedData
+      const parity = wasmModule?.ssim?.(
+        wasmResult.processedData.data,
+        jsResult.processedData.data,
+        width,
+        height
+      )
+
       const metrics = {
         wasmTime: wasmResult.processingTime,
         jsTime: jsResult.processingTime,
         speedup,
-        memoryUsage
+        memoryUsage,
+        parity
       }
 
       setPerformanceMetrics(metrics)
@@ -632,6 +541,29 @@ export default function ImageProcessingDemo() {
     isProcessing
   ])
 
+  // Same filter, same input, same WASM code: only the working space differs, so the panels show
+  // what gamma-correct processing changes
+  useEffect(() => {
+    if (!showGammaComparison || !originalImageData || !wasmModule?.apply_filter) return
+
+    const { width, height } = originalImageData
+    const op = selectedFilter.type === 'color' ? selectedFilter.name : selectedFilter.type
+    const panels: Array<[HTMLCanvasElement | null, string]> = [
+      [srgbCanvasRef.current, 'srgb'],
+      [linearCanvasRef.current, 'linear']
+    ]
+    for (const [canvas, workingSpace] of panels) {
+      const ctx = canvas?.getContext('2d')
+      if (!canvas || !ctx) continue
+
+      const data = new Uint8ClampedArray(originalImageData.data)
+      wasmModule.apply_filter(data, width, height, op, filterParameter, workingSpace)
+      canvas.width = width
+      canvas.height = height
+      ctx.putImageData(new ImageData(data, width, height), 0, 0)
+    }
+  }, [showGammaComparison, originalImageData, wasmModule, selectedFilter, filterParameter])
+
   const resetImage = useCallback(() => {
     if (!originalImageData) return
 
@@ -716,11 +648,20 @@ export default function ImageProcessingDemo() {
           const speedup = jsResult.processingTime / wasmResult.processingTime
           const memoryUsage = wasmModule ? wasmModule.get_memory_usage() : 0
 
+          const { width, height } = wasmResult.processedData
+          const parity = wasmModule?.ssim?.(
+            wasmResult.processedData.data,
+            jsResult.processedData.data,
+            width,
+            height
+          )
+
           const metrics = {
             wasmTime: wasmResult.processingTime,
             jsTime: jsResult.processingTime,
             speedup,
-            memoryUsage
+            memoryUsage,
+            parity
           }
 
           setPerformanceMetrics(metrics)
@@ -865,6 +806,40 @@ export default function ImageProcessingDemo() {
           </div>
         </div>
 
+        {/* sRGB vs linear-light comparison (both WASM) */}
+        {showGammaComparison && wasmModule?.apply_filter && (
+          <div className="mb-6">
+            <div className="grid grid-cols-1 lg:grid-cols-2 gap-6">
+              <div className="text-center">
+                <h3 className="text-lg font-semibold text-white mb-3">
+                  sRGB (before)
+                </h3>
+                <canvas
+                  ref={srgbCanvasRef}
+                  className="border border-gray-300 rounded-lg shadow-sm max-w-full mx-auto"
+                  style={{ maxHeight: '300px' }}
+                />
+                <p className="text-sm text-gray-400 mt-2">
+                  Filter applied directly to gamma-encoded bytes
+                </p>
+              </div>
+              <div className="text-center">
+                <h3 className="text-lg font-semibold text-white mb-3">
+                  Linear light (after)
+                </h3>
+                <canvas
+                  ref={linearCanvasRef}
+                  className="border border-gray-300 rounded-lg shadow-sm max-w-full mx-auto"
+                  style={{ maxHeight: '300px' }}
+                />
+                <p className="text-sm text-gray-400 mt-2">
+                  Same WASM filter, decoded to linear light first and re-encoded after
+                </p>
+              </div>
+            </div>
+          </div>
+        )}
+
         {/* Filter Controls */}
         <div className="grid grid-cols-1 md:grid-cols-2 gap-6 mb-6">
           <div>
@@ -905,6 +880,17 @@ export default function ImageProcessingDemo() {
               />
             </div>
           )}
+
+          {wasmModule?.apply_filter && (
+            <label className="flex items-center gap-2 text-sm text-gray-200">
+              <input
+                type="checkbox"
+                checked={showGammaComparison}
+                onChange={(e) => setShowGammaComparison(e.target.checked)}
+              />
+              Compare sRGB and linear-light processing (both WASM)
+            </label>
+          )}
         </div>
 
         {/* Action Buttons */}
@@ -966,6 +952,14 @@ export default function ImageProcessingDemo() {
                   {(performanceMetrics.memoryUsage / 1024).toFixed(1)}KB
                 </div>
               </div>
+              {performanceMetrics.parity !== undefined && (
+                <div>
+                  <div className="text-gray-300">WASM/JS SSIM</div>
+                  <div className={`font-mono ${performanceMetrics.parity > 0.99 ? 'text-green-400' : 'text-yellow-400'}`}>
+                    {performanceMetrics.parity.toFixed(4)}
+                  </div>
+                </div>
+              )}
             </div>
           </div>
         )}
@@ -15,7 +15,9 @@ export interface CryptoResult {
 export interface HashVisualization {
   hash: number;
   color: number;
-  pattern: number[];
+  digest: string;
+  identicon: Uint8ClampedArray;
+  randart: string;
   size: number;
 }
 
@@ -35,6 +37,13 @@ export interface WASMCryptoModule {
   demo_sha_hash(input: string): string;
   crc32(data: Uint8Array): number;
 
+  // Perceptual image hashes: similar images give similar 16-digit hex hashes
+  ahash?(data: Uint8ClampedArray, width: number, height: number): string;
+  dhash?(data: Uint8ClampedArray, width: number, height: number): string;
+  phash?(data: Uint8ClampedArray, width: number, height: number): string;
+  phash_distance?(a: string, b: string): number;
+  hashes_similar?(a: string, b: string, maxDistance: number): boolean;
+
   // Encryption functions
   caesar_encrypt(text: string, shift: number): string;
   caesar_decrypt(text: string, shift: number): string;
@@ -46,7 +55,9 @@ export interface WASMCryptoModule {
 
   // Hash visualization
   hash_to_color(hash: number): number;
-  hash_to_pattern(hash: number, size: number): Uint32Array;
+  sha256_hex(input: string): string;
+  identicon_rgba(input: string, size: number): Uint8Array;
+  drunken_bishop_art(input: string): string;
 
   // Analysis functions
   demonstrate_avalanche_effect(input1: string, input2: string): Uint32Array;
@@ -109,6 +120,11 @@ class WASMCryptoProcessorLoader {
               demo_md5_hash: wasmModule.demo_md5_hash,
               demo_sha_hash: wasmModule.demo_sha_hash,
               crc32: wasmModule.crc32,
+              ahash: wasmModule.ahash,
+              dhash: wasmModule.dhash,
+              phash: wasmModule.phash,
+              phash_distance: wasmModule.phash_distance,
+              hashes_similar: wasmModule.hashes_similar,
 
               // Encryption functions
               caesar_encrypt: wasmModule.caesar_encrypt,
@@ -121,7 +137,9 @@ class WASMCryptoProcessorLoader {
 
               // Hash visualization
               hash_to_color: wasmModule.hash_to_color,
-              hash_to_pattern: wasmModule.hash_to_pattern,
+              sha256_hex: wasmModule.sha256_hex,
+              identicon_rgba: wasmModule.identicon_rgba,
+              drunken_bishop_art: wasmModule.drunken_bishop_art,
 
               // Analysis functions
               demonstrate_avalanche_effect: wasmModule.demonstrate_avalanche_effect,
@@ -463,20 +481,22 @@ export class WASMCryptoProcessor {
     }
   }
 
-  // Hash Visualization
-  async generateHashVisualization(input: string, size: number = 8): Promise<HashVisualization> {
+  // Hash Visualization: SHA-256 identicon (size x size RGBA) and OpenSSH-style randart
+  async generateHashVisualization(input: string, size: number = 120): Promise<HashVisualization> {
     this.ensureInitialized();
 
     try {
       const module = this.getModule();
       const hash = module.simple_hash(input);
       const color = module.hash_to_color(hash);
-      const pattern = module.hash_to_pattern(hash, size);
+      const identicon = module.identicon_rgba(input, size);
 
       return {
         hash,
         color,
-        pattern: Array.from(pattern),
+        digest: module.sha256_hex(input),
+        identicon: new Uint8ClampedArray(identicon),
+        randart: module.drunken_bishop_art(input),
         size
       };
     } catch (error) {
@@ -10,6 +10,13 @@ export interface WASMImageProcessor {
   adjust_brightness(data: Uint8ClampedArray, width: number, height: number, factor: number): void
   adjust_contrast(data: Uint8ClampedArray, width: number, height: number, factor: number): void
   apply_sharpen(data: Uint8ClampedArray, width: number, height: number, strength: number): void
+  // Any of the filters above by name ('blur', 'sepia', ...) in 'srgb' or 'linear' working space
+  apply_filter?(data: Uint8ClampedArray, width: number, height: number, op: string, value: number, workingSpace: string): void
+  // Quality metrics for checking WASM output against the JS fallbacks
+  psnr?(a: Uint8ClampedArray, b: Uint8ClampedArray): number
+  ssim?(a: Uint8ClampedArray, b: Uint8ClampedArray, width: number, height: number): number
+  ms_ssim?(a: Uint8ClampedArray, b: Uint8ClampedArray, width: number, height: number): number
+  diff_heatmap?(a: Uint8ClampedArray, b: Uint8ClampedArray, width: number, height: number, gain: number): Uint8Array
   get_memory_usage(): number
 }
 
@@ -67,6 +74,11 @@ class WASMImageProcessorLoader {
               adjust_brightness: wasmModule.adjust_brightness,
               adjust_contrast: wasmModule.adjust_contrast,
               apply_sharpen: wasmModule.apply_sharpen,
+              apply_filter: wasmModule.apply_filter,
+              psnr: wasmModule.psnr,
+              ssim: wasmModule.ssim,
+              ms_ssim: wasmModule.ms_ssim,
+              diff_heatmap: wasmModule.diff_heatmap,
               get_memory_usage: wasmModule.get_memory_usage || (() => 0)
             };
 
@@ -20,7 +20,9 @@ const mockWASMModule = {
   substitution_encrypt: vi.fn((text: string, key: string) => 'Svool, Dliow!'),
   simple_base64_encode: vi.fn((text: string) => 'SGVsbG8gV29ybGQ='),
   hash_to_color: vi.fn((hash: number) => 0xFF5733),
-  hash_to_pattern: vi.fn((hash: number, size: number) => new Uint32Array(size * size).fill(0xFF5733)),
+  sha256_hex: vi.fn((input: string) => '9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08'),
+  identicon_rgba: vi.fn((input: string, size: number) => new Uint8Array(size * size * 4).fill(240)),
+  drunken_bishop_art: vi.fn((input: string) => '+-----------------+\n+----[SHA256]-----+'),
   demonstrate_avalanche_effect: vi.fn((input1: string, input2: string) => new Uint32Array([1, 3, 5, 7])),
   find_simple_collision: vi.fn((hash: number, attempts: number) => 'test123'),
   calculate_entropy: vi.fn((text: string) => 3.14159),
@@ -213,13 +215,16 @@ describe('WASMCryptoProcessor', () => {
       expect(result).toEqual({
         hash: 12345,
         color: 0xFF5733,
-        pattern: expect.any(Array),
+        digest: '9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08',
+        identicon: expect.any(Uint8ClampedArray),
+        randart: '+-----------------+\n+----[SHA256]-----+',
         size: 4
       });
-      expect(result.pattern).toHaveLength(16); // 4x4 grid
+      expect(result.identicon).toHaveLength(64); // 4x4 RGBA
       expect(mockWASMModule.simple_hash).toHaveBeenCalledWith('test');
       expect(mockWASMModule.hash_to_color).toHaveBeenCalledWith(12345);
-      expect(mockWASMModule.hash_to_pattern).toHaveBeenCalledWith(12345, 4);
+      expect(mockWASMModule.identicon_rgba).toHaveBeenCalledWith('test', 4);
+      expect(mockWASMModule.drunken_bishop_art).toHaveBeenCalledWith('test');
     });
   });
 
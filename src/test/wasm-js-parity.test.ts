@@ -0,0 +1,88 @@
+/**
+ * WASM/JS parity: the demo's JavaScript filters and the WASM exports should agree wherever they
+ * implement the same operation
+ */
+
+import { describe, it, expect, beforeAll } from 'vitest'
+import { existsSync, readFileSync } from 'node:fs'
+import path from 'node:path'
+import { applyFilterJSInPlace, type FilterType } from '@/lib/js-image-filters'
+import type { WASMImageProcessor } from '@/lib/wasm-image-processor'
+
+const WASM_DIR = path.resolve(__dirname, '../../public/wasm')
+const WASM_GLUE = path.join(WASM_DIR, 'portfolio_wasm.js')
+const WASM_BINARY = path.join(WASM_DIR, 'portfolio_wasm_bg.wasm')
+// `npm run test:run` builds these first; a bare `vitest` run without them reports the suite as skipped
+const WASM_BUILT = existsSync(WASM_GLUE) && existsSync(WASM_BINARY)
+const WIDTH = 64
+const HEIGHT = 48
+
+// Gradients plus hard stripes; deterministic so a failure reproduces
+function testImage(): Uint8ClampedArray {
+  const data = new Uint8ClampedArray(WIDTH * HEIGHT * 4)
+  for (let y = 0; y < HEIGHT; y++) {
+    for (let x = 0; x < WIDTH; x++) {
+      const i = (y * WIDTH + x) * 4
+      data[i] = (x * 255) / (WIDTH - 1)
+      data[i + 1] = (y * 255) / (HEIGHT - 1)
+      data[i + 2] = (x + y) % 32 < 16 ? 40 : 210
+      data[i + 3] = 255
+    }
+  }
+  return data
+}
+
+function applyFilterWASM(
+  wasm: WASMImageProcessor,
+  data: Uint8ClampedArray,
+  type: FilterType,
+  name: string,
+  parameter: number
+): void {
+  switch (type) {
+    case 'color':
+      wasm.apply_color_filter(data, WIDTH, HEIGHT, name)
+      break
+    case 'brightness':
+      wasm.adjust_brightness(data, WIDTH, HEIGHT, parameter)
+      break
+    case 'contrast':
+      wasm.adjust_contrast(data, WIDTH, HEIGHT, parameter)
+      break
+    default:
+      throw new Error(`No parity case for ${type}`)
+  }
+}
+
+describe.skipIf(!WASM_BUILT)('WASM/JS filter parity', () => {
+  let wasm: WASMImageProcessor
+
+  beforeAll(async () => {
+    const module = await import(/* @vite-ignore */ WASM_GLUE)
+    module.initSync({ module: readFileSync(WASM_BINARY) })
+    wasm = module as WASMImageProcessor
+  })
+
+  // Blur, edge and sharpen use different algorithms on each side, so only the per-pixel filters
+  // are expected to match (within the ±1 of truncating vs rounding stores)
+  const cases: Array<{ type: FilterType; name: string; parameter: number }> = [
+    { type: 'color', name: 'sepia', parameter: 0 },
+    { type: 'color', name: 'grayscale', parameter: 0 },
+    { type: 'color', name: 'invert', parameter: 0 },
+    { type: 'brightness', name: 'brightness', parameter: 1.3 },
+    { type: 'contrast', name: 'contrast', parameter: 40 }
+  ]
+
+  it.each(cases)('$name gives the same image in WASM and JS', ({ type, name, parameter }) => {
+    expect(wasm.ssim).toBeTypeOf('function')
+    expect(wasm.psnr).toBeTypeOf('function')
+
+    const wasmData = testImage()
+    const jsData = testImage()
+    applyFilterWASM(wasm, wasmData, type, name, parameter)
+    applyFilterJSInPlace(jsData, WIDTH, HEIGHT, type, name, parameter)
+
+    expect(wasm.ssim!(wasmData, jsData, WIDTH, HEIGHT)).toBeGreaterThanOrEqual(0.99)
+    expect(wasm.psnr!(wasmData, jsData)).toBeGreaterThanOrEqual(40)
+  })
+})
@@ -60,10 +60,118 @@ export interface WASMModuleInstance {
   is_initialized?(): boolean
 }
 
+// Handle to a WASM-side object (Pipeline, Lut, Kernel, ...); free() releases its memory
+export interface WASMObjectHandle {
+  free(): void
+}
+
+/**
+ * Stateful editor around one image with undo/redo. Methods whose Rust side returns a Result
+ * throw the error message as a string; the rest cannot fail.
+ */
 export interface ImageProcessorInstance {
+  free(): void
   get_width(): number
   get_height(): number
+
+  /** Bicubic resample of the owned image; throws if the new size is too large */
   set_dimensions(width: number, height: number): void
+
+  // Geometry (undoable; clears the selection). These throw on bad arguments or oversized output.
+  resize(width: number, height: number, filter: 'bilinear' | 'bicubic' | 'lanczos3' | 'area'): void
+  crop(x: number, y: number, width: number, height: number): void
+  rotate_90(): void
+  rotate_180(): void
+  rotate_270(): void
+  flip_horizontal(): void
+  flip_vertical(): void
+  rotate(degrees: number, expand: boolean, sampling: string, border: string): void
+  warp_affine(matrix: Float64Array, sampling: string, border: string): void
+  warp_perspective(matrix: Float64Array, sampling: string, border: string): void
+  perspective_correct(corners: Float64Array, width: number, height: number, sampling: string): void
+
+  // Pixel access; load() throws if the data length doesn't match width * height * 4
+  load(data: Uint8Array | Uint8ClampedArray): void
+  get_pixels(): Uint8Array
+  pixels_ptr(): number
+  pixels_len(): number
+
+  // Filters (undoable, confined to the selection)
+  apply(op: string, value: number): void
+  apply_blur(radius: number): void
+  apply_blur_auto(radius: number): void
+  apply_box_blur(radius: number): void
+  apply_stack_blur(radius: number): void
+  apply_sharpen(strength: number): void
+  apply_edge_detection(): void
+  median_filter(radius: number): void
+  bilateral_filter(sigma_spatial: number, sigma_range: number): void
+  guided_filter(radius: number, epsilon: number): void
+  non_local_means(strength: number, patch_radius: number, search_radius: number): void
+  add_gaussian_noise(sigma: number, seed: number): void
+  add_salt_and_pepper_noise(amount: number, seed: number): void
+  psnr_to_original(): number
+  ssim_to_original(): number
+  apply_canny(sigma: number, low: number, high: number): void
+  apply_color_filter(filter_type: string): void
+  adjust_brightness(factor: number): void
+  adjust_contrast(factor: number): void
+  hue_rotate(degrees: number): void
+  adjust_saturation(amount: number): void
+  adjust_vibrance(amount: number): void
+  white_balance(temperature: number, tint: number): void
+  selective_color(range: string, hue_shift: number, saturation: number, lightness: number): void
+  quantize(colors: number, method: string, dither: string, seed: number): void
+  apply_lut(lut: WASMObjectHandle, interpolation: string): void
+  threshold(level: number): void
+  threshold_otsu(): number
+  threshold_adaptive(method: string, block_size: number, offset: number): void
+  morphology(op: string, element: WASMObjectHandle): void
+  skeletonize(): number
+  overlay(
+    data: Uint8Array | Uint8ClampedArray,
+    width: number,
+    height: number,
+    x: number,
+    y: number,
+    opacity: number,
+    blend_mode: string
+  ): void
+  dither(palette: Uint8Array, mode: string): void
+  convolve(
+    kernel: Float32Array,
+    kernel_width: number,
+    kernel_height: number,
+    divisor: number,
+    bias: number,
+    border_mode: string,
+    channels: string
+  ): void
+  apply_kernel(kernel: WASMObjectHandle, border_mode: string, channels: string): void
+  histogram(): WASMObjectHandle
+  equalize_histogram(): void
+  apply_clahe(tile_size: number, clip_limit: number): void
+  auto_levels(clip_percent: number): void
+  apply_pipeline(pipeline: WASMObjectHandle): void
+
+  // Selection: one byte per pixel, 255 = fully affected
+  set_selection(mask: Uint8Array): void
+  clear_selection(): void
+  get_selection(): Uint8Array | undefined
+  invert_selection(): void
+  select_magic_wand(x: number, y: number, tolerance: number, contiguous: boolean): number
+  flood_fill(x: number, y: number, color: Uint8Array, tolerance: number): number
+
+  // History
+  undo(): boolean
+  redo(): boolean
+  can_undo(): boolean
+  can_redo(): boolean
+  reset(): void
+  get_history_limit(): number
+  set_history_limit(limit: number): void
+  set_working_space(working_space: 'srgb' | 'linear'): void
+  get_working_space(): string
 }
 
 export interface WASMImageProcessingModule {
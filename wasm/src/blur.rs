@@ -0,0 +1,337 @@
+use crate::{apply_blur, now_ms};
+use wasm_bindgen::prelude::*;
+
+// Radius at which apply_blur_auto switches from the exact kernel to iterated boxes. Below it the
+// exact blur is cheap and the box approximation's small ripples are most visible.
+const AUTO_EXACT_MAX_RADIUS: f32 = 6.0;
+
+// Summed-area table over interleaved channels, with a zero first row and column so any
+// rectangle sums in four lookups. f64 keeps sums of 8-bit images exact well past 4K x 4K.
+#[wasm_bindgen]
+pub struct SummedAreaTable {
+    width: usize,
+    height: usize,
+    channels: usize,
+    table: Vec<f64>,
+}
+
+impl SummedAreaTable {
+    pub(crate) fn build(values: &[f64], width: usize, height: usize, channels: usize) -> SummedAreaTable {
+        let stride = (width + 1) * channels;
+        let mut table = vec![0f64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row = vec![0f64; channels];
+            for x in 0..width {
+                for c in 0..channels {
+                    row[c] += values[(y * width + x) * channels + c];
+                    table[(y + 1) * stride + (x + 1) * channels + c] = table[y * stride + (x + 1) * channels + c] + row[c];
+                }
+            }
+        }
+        SummedAreaTable { width, height, channels, table }
+    }
+
+    // Sum over the half-open rectangle [x0, x1) x [y0, y1)
+    pub(crate) fn window_sum(&self, x0: usize, y0: usize, x1: usize, y1: usize, channel: usize) -> f64 {
+        let stride = (self.width + 1) * self.channels;
+        let at = |x: usize, y: usize| self.table[y * stride + x * self.channels + channel];
+        at(x1, y1) - at(x1, y0) - at(x0, y1) + at(x0, y0)
+    }
+
+    // Mean of the (2r+1)^2 window around every pixel, shrinking the window at the borders
+    pub(crate) fn box_means(&self, radius: usize) -> Vec<f64> {
+        let (w, h, r) = (self.width, self.height, radius);
+        let mut out = vec![0f64; w * h * self.channels];
+        for y in 0..h {
+            let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(h));
+            for x in 0..w {
+                let (x0, x1) = (x.saturating_sub(r), (x + r + 1).min(w));
+                let area = ((x1 - x0) * (y1 - y0)) as f64;
+                for c in 0..self.channels {
+                    out[(y * w + x) * self.channels + c] = self.window_sum(x0, y0, x1, y1, c) / area;
+                }
+            }
+        }
+        out
+    }
+
+    // Clip a rectangle to the image as half-open bounds
+    fn clip(&self, x: u32, y: u32, width: u32, height: u32) -> (usize, usize, usize, usize) {
+        let x0 = (x as usize).min(self.width);
+        let y0 = (y as usize).min(self.height);
+        let x1 = (x as usize).saturating_add(width as usize).min(self.width);
+        let y1 = (y as usize).saturating_add(height as usize).min(self.height);
+        (x0, y0, x1, y1)
+    }
+}
+
+#[wasm_bindgen]
+impl SummedAreaTable {
+    // Table over an RGBA image
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8], width: u32, height: u32) -> Result<SummedAreaTable, JsValue> {
+        if data.len() != (width * height * 4) as usize {
+            return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+        }
+        let values: Vec<f64> = data.iter().map(|&v| v as f64).collect();
+        Ok(SummedAreaTable::build(&values, width as usize, height as usize, 4))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height as u32
+    }
+
+    // Per-channel sums over a rectangle, clipped to the image
+    #[wasm_bindgen]
+    pub fn sum(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<f64> {
+        let (x0, y0, x1, y1) = self.clip(x, y, width, height);
+        (0..self.channels).map(|c| self.window_sum(x0, y0, x1, y1, c)).collect()
+    }
+
+    // Per-channel means over a rectangle, clipped to the image; zeros if nothing is left
+    #[wasm_bindgen]
+    pub fn mean(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<f64> {
+        let (x0, y0, x1, y1) = self.clip(x, y, width, height);
+        let area = ((x1 - x0) * (y1 - y0)) as f64;
+        self.sum(x, y, width, height)
+            .into_iter()
+            .map(|s| if area > 0.0 { s / area } else { 0.0 })
+            .collect()
+    }
+}
+
+// One filter pass along a line: (values, scratch, start, len, step, radius)
+type LineFilter = fn(&mut [f32], &mut Vec<f32>, usize, usize, usize, usize);
+
+// Run `line` over every channel of every row, then every column, of an interleaved RGBA buffer
+fn separable_passes(values: &mut [f32], width: usize, height: usize, line: LineFilter, radius: usize) {
+    let mut scratch = Vec::with_capacity(width.max(height));
+    for y in 0..height {
+        for c in 0..4 {
+            line(values, &mut scratch, y * width * 4 + c, width, 4, radius);
+        }
+    }
+    for x in 0..width {
+        for c in 0..4 {
+            line(values, &mut scratch, x * 4 + c, height, width * 4, radius);
+        }
+    }
+}
+
+// Box average of radius r along one line with replicated ends, using a running sum
+fn box_line(values: &mut [f32], scratch: &mut Vec<f32>, start: usize, len: usize, step: usize, r: usize) {
+    scratch.clear();
+    scratch.extend((0..len).map(|i| values[start + i * step]));
+    let at = |i: i64| scratch[i.clamp(0, len as i64 - 1) as usize] as f64;
+    let r = r as i64;
+    let n = (2 * r + 1) as f64;
+
+    let mut acc: f64 = (-r..=r).map(at).sum();
+    for i in 0..len as i64 {
+        values[start + i as usize * step] = (acc / n) as f32;
+        acc += at(i + r + 1) - at(i - r);
+    }
+}
+
+// Triangle (stack) filter of radius r along one line: weights 1, 2, .., r+1, .., 2, 1. The
+// weighted sum moves by (incoming - outgoing) each step, and both of those are running sums.
+fn stack_line(values: &mut [f32], scratch: &mut Vec<f32>, start: usize, len: usize, step: usize, r: usize) {
+    scratch.clear();
+    scratch.extend((0..len).map(|i| values[start + i * step]));
+    let at = |i: i64| scratch[i.clamp(0, len as i64 - 1) as usize] as f64;
+    let r = r as i64;
+    let divisor = ((r + 1) * (r + 1)) as f64;
+
+    let mut sum: f64 = (-r..=r).map(|k| (r + 1 - k.abs()) as f64 * at(k)).sum();
+    let mut incoming: f64 = (1..=r + 1).map(at).sum();
+    let mut outgoing: f64 = (-r..=0).map(at).sum();
+    for i in 0..len as i64 {
+        values[start + i as usize * step] = (sum / divisor) as f32;
+        sum += incoming - outgoing;
+        incoming += at(i + r + 2) - at(i + 1);
+        outgoing += at(i + 1) - at(i - r);
+    }
+}
+
+fn to_floats(data: &[u8]) -> Vec<f32> {
+    data.iter().map(|&v| v as f32).collect()
+}
+
+fn store(data: &mut [u8], values: &[f32]) {
+    for (d, v) in data.iter_mut().zip(values) {
+        *d = v.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+// Box widths whose three passes best match a Gaussian of the given sigma (Kovesi's scheme),
+// returned as radii
+fn boxes_for_gaussian(sigma: f32, passes: usize) -> Vec<usize> {
+    let n = passes as f32;
+    let ideal = (12.0 * sigma * sigma / n + 1.0).sqrt();
+    let mut lower = ideal.floor() as i64;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let lower = lower.max(1);
+    let upper = lower + 2;
+    let l = lower as f32;
+    let lower_count = ((12.0 * sigma * sigma - n * l * l - 4.0 * n * l - 3.0 * n) / (-4.0 * l - 4.0)).round() as usize;
+    (0..passes)
+        .map(|i| (if i < lower_count { lower } else { upper } as usize - 1) / 2)
+        .collect()
+}
+
+// Single box blur of the given radius; O(1) per pixel
+#[wasm_bindgen]
+pub fn apply_box_blur(data: &mut [u8], width: u32, height: u32, radius: u32) {
+    if data.len() != (width * height * 4) as usize || radius == 0 {
+        return;
+    }
+    let mut values = to_floats(data);
+    separable_passes(&mut values, width as usize, height as usize, box_line, radius as usize);
+    store(data, &values);
+}
+
+// Gaussian approximated by three box blurs, O(1) per pixel. radius means the same as for
+// apply_blur (sigma = radius / 3).
+#[wasm_bindgen]
+pub fn apply_fast_blur(data: &mut [u8], width: u32, height: u32, radius: f32) {
+    if data.len() != (width * height * 4) as usize || radius <= 0.0 {
+        return;
+    }
+    let mut values = to_floats(data);
+    for r in boxes_for_gaussian(radius / 3.0, 3) {
+        if r > 0 {
+            separable_passes(&mut values, width as usize, height as usize, box_line, r);
+        }
+    }
+    store(data, &values);
+}
+
+// Stack blur (Mario Klingemann's triangle-kernel blur) with an integer radius, O(1) per pixel
+#[wasm_bindgen]
+pub fn apply_stack_blur(data: &mut [u8], width: u32, height: u32, radius: u32) {
+    if data.len() != (width * height * 4) as usize || radius == 0 {
+        return;
+    }
+    let mut values = to_floats(data);
+    separable_passes(&mut values, width as usize, height as usize, stack_line, radius as usize);
+    store(data, &values);
+}
+
+// Stack blur radius whose triangle has the same variance, r(r + 2) / 6, as apply_blur's Gaussian
+fn stack_radius_for(radius: f32) -> u32 {
+    let sigma = radius / 3.0;
+    ((6.0 * sigma * sigma + 1.0).sqrt() - 1.0).round().max(1.0) as u32
+}
+
+// Same visual result as apply_blur, but large radii use the constant-time box approximation
+#[wasm_bindgen]
+pub fn apply_blur_auto(data: &mut [u8], width: u32, height: u32, radius: f32) {
+    if radius <= AUTO_EXACT_MAX_RADIUS {
+        apply_blur(data, width, height, radius);
+    } else {
+        apply_fast_blur(data, width, height, radius);
+    }
+}
+
+// Timings (ms per run) and mean absolute error against apply_blur for each blur implementation
+#[wasm_bindgen]
+pub struct BlurBenchmark {
+    radius: f32,
+    exact_ms: f64,
+    fast_ms: f64,
+    stack_ms: f64,
+    auto_ms: f64,
+    fast_error: f64,
+    stack_error: f64,
+}
+
+#[wasm_bindgen]
+impl BlurBenchmark {
+    #[wasm_bindgen]
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    #[wasm_bindgen]
+    pub fn get_exact_ms(&self) -> f64 {
+        self.exact_ms
+    }
+
+    #[wasm_bindgen]
+    pub fn get_fast_ms(&self) -> f64 {
+        self.fast_ms
+    }
+
+    #[wasm_bindgen]
+    pub fn get_stack_ms(&self) -> f64 {
+        self.stack_ms
+    }
+
+    #[wasm_bindgen]
+    pub fn get_auto_ms(&self) -> f64 {
+        self.auto_ms
+    }
+
+    #[wasm_bindgen]
+    pub fn get_fast_error(&self) -> f64 {
+        self.fast_error
+    }
+
+    #[wasm_bindgen]
+    pub fn get_stack_error(&self) -> f64 {
+        self.stack_error
+    }
+}
+
+// Blur a synthetic width x height image with every implementation, `iterations` times each.
+// The stack blur uses the radius with the same variance as the Gaussian.
+#[wasm_bindgen]
+pub fn benchmark_blurs(width: u32, height: u32, radius: f32, iterations: u32) -> BlurBenchmark {
+    // Checkerboard with a gradient, so both flat areas and hard edges are represented
+    let image: Vec<u8> = (0..width * height)
+        .flat_map(|i| {
+            let (x, y) = (i % width, i / width);
+            let v = if ((x / 16) + (y / 16)).is_multiple_of(2) { 230 } else { 25 };
+            [v, (x * 255 / width.max(1)) as u8, (y * 255 / height.max(1)) as u8, 255]
+        })
+        .collect();
+    let iterations = iterations.max(1);
+
+    let time = |blur: &dyn Fn(&mut [u8])| -> (f64, Vec<u8>) {
+        let mut out = image.clone();
+        let start = now_ms();
+        for _ in 0..iterations {
+            out.copy_from_slice(&image);
+            blur(&mut out);
+        }
+        ((now_ms() - start) / iterations as f64, out)
+    };
+    let error = |a: &[u8], b: &[u8]| -> f64 {
+        let total: u64 = a.iter().zip(b).map(|(&x, &y)| (x as i64 - y as i64).unsigned_abs()).sum();
+        total as f64 / a.len().max(1) as f64
+    };
+
+    let (exact_ms, exact) = time(&|d| apply_blur(d, width, height, radius));
+    let (fast_ms, fast) = time(&|d| apply_fast_blur(d, width, height, radius));
+    let stack_radius = stack_radius_for(radius);
+    let (stack_ms, stack) = time(&|d| apply_stack_blur(d, width, height, stack_radius));
+    let (auto_ms, _) = time(&|d| apply_blur_auto(d, width, height, radius));
+
+    BlurBenchmark {
+        radius,
+        exact_ms,
+        fast_ms,
+        stack_ms,
+        auto_ms,
+        fast_error: error(&exact, &fast),
+        stack_error: error(&exact, &stack),
+    }
+}
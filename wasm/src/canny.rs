@@ -0,0 +1,224 @@
+use crate::identicon::hsl_to_rgb;
+use wasm_bindgen::prelude::*;
+
+// Separable Gaussian over a single-channel plane with clamped borders
+pub(crate) fn gaussian_smooth(plane: &[f32], width: usize, height: usize, sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 {
+        return plane.to_vec();
+    }
+
+    let half = (sigma * 3.0).ceil() as i64;
+    let weights: Vec<f32> = (-half..=half).map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    let weights: Vec<f32> = weights.iter().map(|w| w / total).collect();
+
+    let mut horizontal = vec![0f32; plane.len()];
+    for y in 0..height {
+        let row = &plane[y * width..(y + 1) * width];
+        for x in 0..width {
+            horizontal[y * width + x] = weights
+                .iter()
+                .enumerate()
+                .map(|(k, w)| row[(x as i64 + k as i64 - half).clamp(0, width as i64 - 1) as usize] * w)
+                .sum();
+        }
+    }
+
+    let mut out = vec![0f32; plane.len()];
+    for y in 0..height {
+        for (k, w) in weights.iter().enumerate() {
+            let sy = (y as i64 + k as i64 - half).clamp(0, height as i64 - 1) as usize;
+            for (acc, v) in out[y * width..(y + 1) * width].iter_mut().zip(&horizontal[sy * width..(sy + 1) * width]) {
+                *acc += v * w;
+            }
+        }
+    }
+    out
+}
+
+// Every stage of a Canny run, kept for step-through visualization. Magnitudes are in 0-255
+// intensity levels (Sobel response / 4), so a clean step edge of height d measures d.
+#[wasm_bindgen]
+pub struct CannyStages {
+    width: u32,
+    height: u32,
+    magnitude: Vec<f32>,
+    direction: Vec<f32>,
+    suppressed: Vec<f32>,
+    edges: Vec<bool>,
+}
+
+impl CannyStages {
+    pub(crate) fn compute(data: &[u8], width: u32, height: u32, sigma: f32, low: f32, high: f32) -> Result<CannyStages, String> {
+        if data.len() != (width * height * 4) as usize {
+            return Err("Image data length does not match width * height * 4".to_string());
+        }
+        let (low, high) = if low <= high { (low, high) } else { (high, low) };
+        let (w, h) = (width as usize, height as usize);
+
+        // 1. Luma, smoothed
+        let luma: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+            .collect();
+        let smooth = gaussian_smooth(&luma, w, h, sigma);
+
+        // 2. Sobel gradient with clamped borders
+        let at = |x: i64, y: i64| smooth[y.clamp(0, h as i64 - 1) as usize * w + x.clamp(0, w as i64 - 1) as usize];
+        let mut magnitude = vec![0f32; w * h];
+        let mut direction = vec![0f32; w * h];
+        for y in 0..h as i64 {
+            for x in 0..w as i64 {
+                let gx = (at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1))
+                    - (at(x - 1, y - 1) + 2.0 * at(x - 1, y) + at(x - 1, y + 1));
+                let gy = (at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1))
+                    - (at(x - 1, y - 1) + 2.0 * at(x, y - 1) + at(x + 1, y - 1));
+                let i = y as usize * w + x as usize;
+                magnitude[i] = (gx * gx + gy * gy).sqrt() / 4.0;
+                direction[i] = gy.atan2(gx);
+            }
+        }
+
+        // 3. Non-maximum suppression: keep pixels that peak across the edge, comparing the two
+        // neighbours along the gradient quantized to 0/45/90/135 degrees
+        let mag = |x: i64, y: i64| {
+            if x < 0 || y < 0 || x >= w as i64 || y >= h as i64 { 0.0 } else { magnitude[y as usize * w + x as usize] }
+        };
+        let mut suppressed = vec![0f32; w * h];
+        for y in 0..h as i64 {
+            for x in 0..w as i64 {
+                let i = y as usize * w + x as usize;
+                let m = magnitude[i];
+                if m <= 0.0 {
+                    continue;
+                }
+                let angle = direction[i].to_degrees().rem_euclid(180.0);
+                let (dx, dy) = if !(22.5..157.5).contains(&angle) {
+                    (1, 0)
+                } else if angle < 67.5 {
+                    (1, 1)
+                } else if angle < 112.5 {
+                    (0, 1)
+                } else {
+                    (-1, 1)
+                };
+                // Strict on one side so plateaus stay one pixel wide
+                if m > mag(x - dx, y - dy) && m >= mag(x + dx, y + dy) {
+                    suppressed[i] = m;
+                }
+            }
+        }
+
+        // 4. Hysteresis: strong pixels seed edges that grow through 8-connected weak pixels
+        let mut edges = vec![false; w * h];
+        let mut stack: Vec<usize> = (0..w * h).filter(|&i| suppressed[i] >= high && suppressed[i] > 0.0).collect();
+        for &i in &stack {
+            edges[i] = true;
+        }
+        while let Some(i) = stack.pop() {
+            let (x, y) = ((i % w) as i64, (i / w) as i64);
+            for ny in y - 1..=y + 1 {
+                for nx in x - 1..=x + 1 {
+                    if nx < 0 || ny < 0 || nx >= w as i64 || ny >= h as i64 {
+                        continue;
+                    }
+                    let n = ny as usize * w + nx as usize;
+                    if !edges[n] && suppressed[n] >= low && suppressed[n] > 0.0 {
+                        edges[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+
+        Ok(CannyStages { width, height, magnitude, direction, suppressed, edges })
+    }
+
+    fn max_magnitude(&self) -> f32 {
+        self.magnitude.iter().cloned().fold(0.0, f32::max)
+    }
+
+    fn gray(values: &[f32], max: f32) -> Vec<u8> {
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+        values
+            .iter()
+            .flat_map(|&v| {
+                let g = (v * scale).round().clamp(0.0, 255.0) as u8;
+                [g, g, g, 255]
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl CannyStages {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // Gradient magnitude as RGBA, scaled so the strongest gradient is white
+    #[wasm_bindgen]
+    pub fn get_magnitude(&self) -> Vec<u8> {
+        CannyStages::gray(&self.magnitude, self.max_magnitude())
+    }
+
+    // Gradient direction as hue, brightness by magnitude so flat areas stay dark
+    #[wasm_bindgen]
+    pub fn get_direction(&self) -> Vec<u8> {
+        let max = self.max_magnitude();
+        self.direction
+            .iter()
+            .zip(&self.magnitude)
+            .flat_map(|(&angle, &m)| {
+                let lightness = if max > 0.0 { 0.5 * m / max } else { 0.0 };
+                let [r, g, b] = hsl_to_rgb(angle.to_degrees(), 1.0, lightness);
+                [r, g, b, 255]
+            })
+            .collect()
+    }
+
+    // Magnitude after non-maximum suppression, on the same scale as get_magnitude
+    #[wasm_bindgen]
+    pub fn get_suppressed(&self) -> Vec<u8> {
+        CannyStages::gray(&self.suppressed, self.max_magnitude())
+    }
+
+    // Final edges, white on black
+    #[wasm_bindgen]
+    pub fn get_edges(&self) -> Vec<u8> {
+        self.edges
+            .iter()
+            .flat_map(|&e| if e { [255, 255, 255, 255] } else { [0, 0, 0, 255] })
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_edge_count(&self) -> u32 {
+        self.edges.iter().filter(|&&e| e).count() as u32
+    }
+}
+
+// Canny edge detection in place: white edges on black, alpha preserved. sigma is the Gaussian
+// pre-smoothing (0 to skip); low and high are hysteresis thresholds in 0-255 intensity levels.
+#[wasm_bindgen]
+pub fn canny(data: &mut [u8], width: u32, height: u32, sigma: f32, low: f32, high: f32) {
+    let Ok(stages) = CannyStages::compute(data, width, height, sigma, low, high) else {
+        return;
+    };
+    for (p, &edge) in data.chunks_exact_mut(4).zip(&stages.edges) {
+        let v = if edge { 255 } else { 0 };
+        p[..3].fill(v);
+    }
+}
+
+// Same as canny, but returns every intermediate stage as its own RGBA buffer
+#[wasm_bindgen]
+pub fn canny_stages(data: &[u8], width: u32, height: u32, sigma: f32, low: f32, high: f32) -> Result<CannyStages, JsValue> {
+    CannyStages::compute(data, width, height, sigma, low, high).map_err(|e| JsValue::from_str(&e))
+}
@@ -0,0 +1,399 @@
+use crate::working_space::{decode_linear, decode_srgb, encode_linear, encode_srgb};
+use wasm_bindgen::prelude::*;
+
+// Component ranges per space:
+//   rgb    sRGB, 0-255
+//   hsl    hue in degrees, saturation and lightness 0-1
+//   hsv    hue in degrees, saturation and value 0-1
+//   ycbcr  JFIF full range (as in JPEG), 0-255 with chroma centred on 128
+//   xyz    CIE 1931 XYZ for a D65 white, Y of white = 1
+//   lab    CIE L*a*b* (D65), L 0-100
+//   oklab  Ottosson's OKLab, L 0-1, a and b roughly -0.4..0.4
+// Conversions back to RGB don't clamp, so out-of-gamut colors stay visible to the caller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ColorSpace {
+    Rgb,
+    Hsl,
+    Hsv,
+    YCbCr,
+    Xyz,
+    Lab,
+    OkLab,
+}
+
+const COLOR_SPACES: [&str; 7] = ["rgb", "hsl", "hsv", "ycbcr", "xyz", "lab", "oklab"];
+
+// D65 reference white in XYZ
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+impl ColorSpace {
+    pub(crate) fn from_name(name: &str) -> Result<ColorSpace, String> {
+        match name {
+            "rgb" => Ok(ColorSpace::Rgb),
+            "hsl" => Ok(ColorSpace::Hsl),
+            "hsv" => Ok(ColorSpace::Hsv),
+            "ycbcr" => Ok(ColorSpace::YCbCr),
+            "xyz" => Ok(ColorSpace::Xyz),
+            "lab" => Ok(ColorSpace::Lab),
+            "oklab" => Ok(ColorSpace::OkLab),
+            _ => Err(format!("Unknown color space: {} (expected one of {})", name, COLOR_SPACES.join(", "))),
+        }
+    }
+
+    // From and to sRGB 0-255
+    pub(crate) fn rgb_to(self, rgb: [f32; 3]) -> [f32; 3] {
+        self.rgb_to_f64(rgb.map(f64::from)).map(|c| c as f32)
+    }
+
+    pub(crate) fn to_rgb(self, value: [f32; 3]) -> [f32; 3] {
+        self.to_rgb_f64(value.map(f64::from)).map(|c| c as f32)
+    }
+
+    fn rgb_to_f64(self, rgb: [f64; 3]) -> [f64; 3] {
+        let unit = rgb.map(|c| c / 255.0);
+        match self {
+            ColorSpace::Rgb => rgb,
+            ColorSpace::Hsl => rgb_to_hsl(unit),
+            ColorSpace::Hsv => rgb_to_hsv(unit),
+            ColorSpace::YCbCr => rgb_to_ycbcr(rgb),
+            ColorSpace::Xyz => linear_to_xyz(to_linear(unit)),
+            ColorSpace::Lab => xyz_to_lab(linear_to_xyz(to_linear(unit))),
+            ColorSpace::OkLab => linear_to_oklab(to_linear(unit)),
+        }
+    }
+
+    // To sRGB 0-255
+    fn to_rgb_f64(self, value: [f64; 3]) -> [f64; 3] {
+        let unit = match self {
+            ColorSpace::Rgb => return value,
+            ColorSpace::YCbCr => return ycbcr_to_rgb(value),
+            ColorSpace::Hsl => hsl_to_unit_rgb(value[0], value[1], value[2]),
+            ColorSpace::Hsv => hsv_to_rgb(value),
+            ColorSpace::Xyz => from_linear(xyz_to_linear(value)),
+            ColorSpace::Lab => from_linear(xyz_to_linear(lab_to_xyz(value))),
+            ColorSpace::OkLab => from_linear(oklab_to_linear(value)),
+        };
+        unit.map(|c| c * 255.0)
+    }
+}
+
+fn to_linear(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(decode_srgb)
+}
+
+fn from_linear(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(encode_srgb)
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn rgb_to_hue(r: f64, g: f64, b: f64, max: f64, chroma: f64) -> f64 {
+    if chroma <= 0.0 {
+        0.0
+    } else if max == r {
+        (60.0 * (g - b) / chroma).rem_euclid(360.0)
+    } else if max == g {
+        60.0 * (b - r) / chroma + 120.0
+    } else {
+        60.0 * (r - g) / chroma + 240.0
+    }
+}
+
+fn rgb_to_hsl([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let l = (max + min) / 2.0;
+    let s = if chroma <= 0.0 { 0.0 } else { chroma / (1.0 - (2.0 * l - 1.0).abs()) };
+    [rgb_to_hue(r, g, b, max, chroma), s, l]
+}
+
+// h in degrees, s and l in 0..1; RGB in 0..1
+fn hsl_to_unit_rgb(h: f64, s: f64, l: f64) -> [f64; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let hp = (h.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (hp % 2.0 - 1.0).abs());
+    let (r, g, b) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    [r + m, g + m, b + m]
+}
+
+fn rgb_to_hsv([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    let s = if max <= 0.0 { 0.0 } else { chroma / max };
+    [rgb_to_hue(r, g, b, max, chroma), s, max]
+}
+
+fn hsv_to_rgb([h, s, v]: [f64; 3]) -> [f64; 3] {
+    // An HSV color is the HSL color with the same hue and chroma, shifted to the same maximum
+    let chroma = v * s;
+    let [r, g, b] = hsl_to_unit_rgb(h, 1.0, 0.5);
+    [r, g, b].map(|c| v - chroma + c * chroma)
+}
+
+fn rgb_to_ycbcr([r, g, b]: [f64; 3]) -> [f64; 3] {
+    [
+        0.299 * r + 0.587 * g + 0.114 * b,
+        128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b,
+        128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b,
+    ]
+}
+
+fn ycbcr_to_rgb([y, cb, cr]: [f64; 3]) -> [f64; 3] {
+    let (cb, cr) = (cb - 128.0, cr - 128.0);
+    [y + 1.402 * cr, y - 0.344136 * cb - 0.714136 * cr, y + 1.772 * cb]
+}
+
+// Linear sRGB <-> XYZ (D65)
+const RGB_TO_XYZ: [[f64; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.1191920, 0.9503041],
+];
+const XYZ_TO_RGB: [[f64; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.9692660, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+fn linear_to_xyz(rgb: [f64; 3]) -> [f64; 3] {
+    mul(&RGB_TO_XYZ, rgb)
+}
+
+fn xyz_to_linear(xyz: [f64; 3]) -> [f64; 3] {
+    mul(&XYZ_TO_RGB, xyz)
+}
+
+const LAB_EPSILON: f64 = 216.0 / 24389.0;
+const LAB_KAPPA: f64 = 24389.0 / 27.0;
+
+fn xyz_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let f = |t: f64| if t > LAB_EPSILON { t.cbrt() } else { (LAB_KAPPA * t + 16.0) / 116.0 };
+    let [fx, fy, fz] = [f(xyz[0] / WHITE[0]), f(xyz[1] / WHITE[1]), f(xyz[2] / WHITE[2])];
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_xyz([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let inverse = |f: f64| if f * f * f > LAB_EPSILON { f * f * f } else { (116.0 * f - 16.0) / LAB_KAPPA };
+    [inverse(fx) * WHITE[0], inverse(fy) * WHITE[1], inverse(fz) * WHITE[2]]
+}
+
+// Linear sRGB <-> OKLab, https://bottosson.github.io/posts/oklab/
+const RGB_TO_LMS: [[f64; 3]; 3] = [
+    [0.4122214708, 0.5363325363, 0.0514459929],
+    [0.2119034982, 0.6806995451, 0.1073969566],
+    [0.0883024619, 0.2817188376, 0.6299787005],
+];
+const LMS_TO_OKLAB: [[f64; 3]; 3] = [
+    [0.2104542553, 0.7936177850, -0.0040720468],
+    [1.9779984951, -2.4285922050, 0.4505937099],
+    [0.0259040371, 0.7827717662, -0.8086757660],
+];
+const OKLAB_TO_LMS: [[f64; 3]; 3] = [
+    [1.0, 0.3963377774, 0.2158037573],
+    [1.0, -0.1055613458, -0.0638541728],
+    [1.0, -0.0894841775, -1.2914855480],
+];
+const LMS_TO_RGB: [[f64; 3]; 3] = [
+    [4.0767416621, -3.3077115913, 0.2309699292],
+    [-1.2684380046, 2.6097574011, -0.3413193965],
+    [-0.0041960863, -0.7034186147, 1.7076147010],
+];
+
+fn linear_to_oklab(rgb: [f64; 3]) -> [f64; 3] {
+    mul(&LMS_TO_OKLAB, mul(&RGB_TO_LMS, rgb).map(f64::cbrt))
+}
+
+fn oklab_to_linear(lab: [f64; 3]) -> [f64; 3] {
+    mul(&LMS_TO_RGB, mul(&OKLAB_TO_LMS, lab).map(|c| c * c * c))
+}
+
+fn check_size(data: &[u8], width: u32, height: u32) -> bool {
+    data.len() == (width * height * 4) as usize
+}
+
+// Rewrite every pixel's RGB through `adjust`, which works on 0-255 floats; alpha is preserved
+fn map_rgb<F: Fn([f32; 3]) -> [f32; 3]>(data: &mut [u8], adjust: F) {
+    for p in data.chunks_exact_mut(4) {
+        let out = adjust([p[0] as f32, p[1] as f32, p[2] as f32]);
+        for c in 0..3 {
+            p[c] = out[c].round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// Scale OKLab chroma by factor(rgb), keeping lightness and hue
+fn scale_chroma<F: Fn([f32; 3]) -> f32>(data: &mut [u8], factor: F) {
+    map_rgb(data, |rgb| {
+        let k = factor(rgb);
+        let [l, a, b] = ColorSpace::OkLab.rgb_to(rgb);
+        ColorSpace::OkLab.to_rgb([l, a * k, b * k])
+    });
+}
+
+// Convert one color between any two spaces named above
+#[wasm_bindgen]
+pub fn convert_color(from: &str, to: &str, a: f32, b: f32, c: f32) -> Result<Vec<f32>, JsValue> {
+    let from = ColorSpace::from_name(from).map_err(|e| JsValue::from_str(&e))?;
+    let to = ColorSpace::from_name(to).map_err(|e| JsValue::from_str(&e))?;
+    Ok(to.rgb_to(from.to_rgb([a, b, c])).to_vec())
+}
+
+// Convert an RGBA image to four floats per pixel in the given space; alpha is copied through (0-255)
+#[wasm_bindgen]
+pub fn rgba_to_color_space(data: &[u8], space: &str) -> Result<Vec<f32>, JsValue> {
+    let space = ColorSpace::from_name(space).map_err(|e| JsValue::from_str(&e))?;
+    Ok(data
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let [x, y, z] = space.rgb_to([p[0] as f32, p[1] as f32, p[2] as f32]);
+            [x, y, z, p[3] as f32]
+        })
+        .collect())
+}
+
+// Inverse of rgba_to_color_space; out-of-gamut colors are clamped
+#[wasm_bindgen]
+pub fn color_space_to_rgba(values: &[f32], space: &str) -> Result<Vec<u8>, JsValue> {
+    let space = ColorSpace::from_name(space).map_err(|e| JsValue::from_str(&e))?;
+    Ok(values
+        .chunks_exact(4)
+        .flat_map(|v| {
+            let [r, g, b] = space.to_rgb([v[0], v[1], v[2]]);
+            [r, g, b, v[3]].map(|c| c.round().clamp(0.0, 255.0) as u8)
+        })
+        .collect())
+}
+
+// Rotate hue by `degrees` in OKLCh, so lightness stays put (unlike an HSL rotation, which turns
+// yellow into a much darker blue)
+#[wasm_bindgen]
+pub fn hue_rotate(data: &mut [u8], width: u32, height: u32, degrees: f32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    map_rgb(data, |rgb| {
+        let [l, a, b] = ColorSpace::OkLab.rgb_to(rgb);
+        ColorSpace::OkLab.to_rgb([l, a * cos - b * sin, a * sin + b * cos])
+    });
+}
+
+// Scale chroma by 1 + amount: -1 is a perceptual grayscale, 1 doubles saturation
+#[wasm_bindgen]
+pub fn adjust_saturation(data: &mut [u8], width: u32, height: u32, amount: f32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    let k = (1.0 + amount).max(0.0);
+    scale_chroma(data, |_| k);
+}
+
+// Like saturation, but weighted towards muted colors so already saturated ones don't clip
+#[wasm_bindgen]
+pub fn adjust_vibrance(data: &mut [u8], width: u32, height: u32, amount: f32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    scale_chroma(data, |rgb| {
+        let saturation = rgb_to_hsv(rgb.map(|c| c as f64 / 255.0))[1] as f32;
+        (1.0 + amount * (1.0 - saturation)).max(0.0)
+    });
+}
+
+// White balance: temperature warms (positive, towards yellow) or cools (negative, towards blue),
+// tint shifts towards magenta (positive) or green (negative); both -100..100. Applied as
+// channel gains on linear light, normalized so neutral grays keep their luminance.
+#[wasm_bindgen]
+pub fn white_balance(data: &mut [u8], width: u32, height: u32, temperature: f32, tint: f32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    let (t, m) = (temperature.clamp(-100.0, 100.0) / 100.0, tint.clamp(-100.0, 100.0) / 100.0);
+    let gains = [1.0 + 0.5 * t, 1.0 - 0.5 * m, 1.0 - 0.5 * t];
+    let y = RGB_TO_XYZ[1].map(|c| c as f32);
+    let luminance = y[0] * gains[0] + y[1] * gains[1] + y[2] * gains[2];
+
+    let mut values = decode_linear(data);
+    for p in values.chunks_exact_mut(4) {
+        for c in 0..3 {
+            p[c] *= gains[c] / luminance;
+        }
+    }
+    encode_linear(&values, data);
+}
+
+// Hue centres of the selective color ranges, as in a photo editor's HSL panel
+const COLOR_RANGES: [(&str, f32); 8] = [
+    ("reds", 0.0),
+    ("oranges", 30.0),
+    ("yellows", 60.0),
+    ("greens", 120.0),
+    ("aquas", 180.0),
+    ("blues", 240.0),
+    ("purples", 270.0),
+    ("magentas", 300.0),
+];
+
+// How far a range reaches either side of its centre, fading out with a cosine
+const RANGE_WIDTH: f32 = 45.0;
+
+// Hue centre of a named range
+pub(crate) fn parse_color_range(range: &str) -> Result<f32, String> {
+    COLOR_RANGES
+        .iter()
+        .find(|(name, _)| *name == range)
+        .map(|&(_, hue)| hue)
+        .ok_or_else(|| {
+            let names: Vec<&str> = COLOR_RANGES.iter().map(|(name, _)| *name).collect();
+            format!("Unknown color range: {} (expected one of {})", range, names.join(", "))
+        })
+}
+
+pub(crate) fn adjust_color_range(data: &mut [u8], centre: f32, hue_shift: f32, saturation: f32, lightness: f32) {
+    map_rgb(data, |rgb| {
+        let [h, s, l] = rgb_to_hsl(rgb.map(|c| c as f64 / 255.0)).map(|c| c as f32);
+        let distance = ((h - centre + 180.0).rem_euclid(360.0) - 180.0).abs();
+        if distance >= RANGE_WIDTH || s <= 0.0 {
+            return rgb;
+        }
+        // Grays have no hue, so the weight also fades with saturation
+        let weight = 0.5 * (1.0 + (std::f32::consts::PI * distance / RANGE_WIDTH).cos()) * s;
+        let s = (s * (1.0 + saturation * weight)).clamp(0.0, 1.0);
+        let l = if lightness >= 0.0 { l + lightness * weight * (1.0 - l) } else { l + lightness * weight * l };
+        hsl_to_unit_rgb((h + hue_shift * weight) as f64, s as f64, l as f64).map(|c| (c * 255.0) as f32)
+    });
+}
+
+// Adjust one hue range ("reds", "oranges", "yellows", "greens", "aquas", "blues", "purples",
+// "magentas"): hue_shift in degrees, saturation and lightness in -1..1
+#[wasm_bindgen]
+pub fn selective_color(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    range: &str,
+    hue_shift: f32,
+    saturation: f32,
+    lightness: f32,
+) -> Result<(), JsValue> {
+    if !check_size(data, width, height) {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    let centre = parse_color_range(range).map_err(|e| JsValue::from_str(&e))?;
+    adjust_color_range(data, centre, hue_shift, saturation, lightness);
+    Ok(())
+}
@@ -0,0 +1,424 @@
+use wasm_bindgen::prelude::*;
+
+// W3C Compositing and Blending Level 1 modes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+const BLEND_MODES: [(&str, BlendMode); 16] = [
+    ("normal", BlendMode::Normal),
+    ("multiply", BlendMode::Multiply),
+    ("screen", BlendMode::Screen),
+    ("overlay", BlendMode::Overlay),
+    ("darken", BlendMode::Darken),
+    ("lighten", BlendMode::Lighten),
+    ("color_dodge", BlendMode::ColorDodge),
+    ("color_burn", BlendMode::ColorBurn),
+    ("hard_light", BlendMode::HardLight),
+    ("soft_light", BlendMode::SoftLight),
+    ("difference", BlendMode::Difference),
+    ("exclusion", BlendMode::Exclusion),
+    ("hue", BlendMode::Hue),
+    ("saturation", BlendMode::Saturation),
+    ("color", BlendMode::Color),
+    ("luminosity", BlendMode::Luminosity),
+];
+
+impl BlendMode {
+    pub(crate) fn from_name(name: &str) -> Result<BlendMode, String> {
+        BLEND_MODES.iter().find(|(n, _)| *n == name).map(|&(_, mode)| mode).ok_or_else(|| {
+            let names: Vec<&str> = BLEND_MODES.iter().map(|(n, _)| *n).collect();
+            format!("Unknown blend mode: {} (expected one of {})", name, names.join(", "))
+        })
+    }
+
+    fn name(self) -> &'static str {
+        BLEND_MODES.iter().find(|(_, m)| *m == self).map(|(n, _)| *n).unwrap_or("normal")
+    }
+}
+
+fn hard_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5 {
+        cb * 2.0 * cs
+    } else {
+        let s = 2.0 * cs - 1.0;
+        cb + s - cb * s
+    }
+}
+
+// Separable modes, per channel on unpremultiplied 0-1 values (backdrop cb, source cs)
+fn blend_channel(mode: BlendMode, cb: f32, cs: f32) -> f32 {
+    match mode {
+        BlendMode::Multiply => cb * cs,
+        BlendMode::Screen => cb + cs - cb * cs,
+        BlendMode::Overlay => hard_light(cs, cb),
+        BlendMode::Darken => cb.min(cs),
+        BlendMode::Lighten => cb.max(cs),
+        BlendMode::ColorDodge => {
+            if cb <= 0.0 {
+                0.0
+            } else if cs >= 1.0 {
+                1.0
+            } else {
+                (cb / (1.0 - cs)).min(1.0)
+            }
+        }
+        BlendMode::ColorBurn => {
+            if cb >= 1.0 {
+                1.0
+            } else if cs <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - cb) / cs).min(1.0)
+            }
+        }
+        BlendMode::HardLight => hard_light(cb, cs),
+        BlendMode::SoftLight => {
+            if cs <= 0.5 {
+                cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+            } else {
+                let d = if cb <= 0.25 { ((16.0 * cb - 12.0) * cb + 4.0) * cb } else { cb.sqrt() };
+                cb + (2.0 * cs - 1.0) * (d - cb)
+            }
+        }
+        BlendMode::Difference => (cb - cs).abs(),
+        BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+        _ => cs,
+    }
+}
+
+// Helpers for the non-separable modes, as defined in the spec
+fn lum(c: [f32; 3]) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn clip_color(c: [f32; 3]) -> [f32; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut out = c;
+    if n < 0.0 {
+        out = out.map(|v| l + (v - l) * l / (l - n));
+    }
+    if x > 1.0 {
+        out = out.map(|v| l + (v - l) * (1.0 - l) / (x - l));
+    }
+    out
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    clip_color(c.map(|v| v + d))
+}
+
+fn sat(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| c[a].total_cmp(&c[b]));
+    let [min, mid, max] = order;
+    let mut out = [0f32; 3];
+    if c[max] > c[min] {
+        out[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        out[max] = s;
+    }
+    out
+}
+
+fn blend(mode: BlendMode, cb: [f32; 3], cs: [f32; 3]) -> [f32; 3] {
+    match mode {
+        BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        BlendMode::Color => set_lum(cs, lum(cb)),
+        BlendMode::Luminosity => set_lum(cb, lum(cs)),
+        _ => [0, 1, 2].map(|c| blend_channel(mode, cb[c], cs[c])),
+    }
+}
+
+fn unpremultiply(p: [f32; 4]) -> [f32; 3] {
+    if p[3] <= 0.0 { [0.0; 3] } else { [p[0] / p[3], p[1] / p[3], p[2] / p[3]] }
+}
+
+// Source-over with the blend mode mixed in where both layers are present; everything is
+// premultiplied
+fn composite(mode: BlendMode, backdrop: [f32; 4], source: [f32; 4]) -> [f32; 4] {
+    let (ab, a_s) = (backdrop[3], source[3]);
+    let alpha = a_s + ab * (1.0 - a_s);
+    let mut out = [0f32, 0.0, 0.0, alpha];
+    if mode == BlendMode::Normal {
+        for c in 0..3 {
+            out[c] = source[c] + backdrop[c] * (1.0 - a_s);
+        }
+        return out;
+    }
+    let mixed = blend(mode, unpremultiply(backdrop), unpremultiply(source));
+    for c in 0..3 {
+        out[c] = source[c] * (1.0 - ab) + backdrop[c] * (1.0 - a_s) + a_s * ab * mixed[c];
+    }
+    out
+}
+
+struct Layer {
+    width: u32,
+    height: u32,
+    x: i32,
+    y: i32,
+    // Premultiplied RGBA, 0-1
+    pixels: Vec<[f32; 4]>,
+    opacity: f32,
+    mask: Option<Vec<f32>>,
+    mode: BlendMode,
+    visible: bool,
+}
+
+// A stack of RGBA layers over a transparent canvas; layer 0 is the bottom. Layers can be smaller
+// than the canvas and placed at an offset (a watermark, say).
+#[wasm_bindgen]
+pub struct Compositor {
+    width: u32,
+    height: u32,
+    layers: Vec<Layer>,
+}
+
+impl Compositor {
+    pub(crate) fn push_layer(&mut self, data: &[u8], width: u32, height: u32) -> Result<usize, String> {
+        if data.len() != (width * height * 4) as usize {
+            return Err("Layer data length does not match width * height * 4".to_string());
+        }
+        let pixels = data
+            .chunks_exact(4)
+            .map(|p| {
+                let a = p[3] as f32 / 255.0;
+                [p[0] as f32 / 255.0 * a, p[1] as f32 / 255.0 * a, p[2] as f32 / 255.0 * a, a]
+            })
+            .collect();
+        self.layers.push(Layer {
+            width,
+            height,
+            x: 0,
+            y: 0,
+            pixels,
+            opacity: 1.0,
+            mask: None,
+            mode: BlendMode::Normal,
+            visible: true,
+        });
+        Ok(self.layers.len() - 1)
+    }
+
+    fn layer_mut(&mut self, index: usize) -> Result<&mut Layer, String> {
+        self.layers.get_mut(index).ok_or_else(|| format!("No layer at index {}", index))
+    }
+
+    pub(crate) fn place(&mut self, index: usize, x: i32, y: i32, opacity: f32, mode: BlendMode) {
+        if let Some(layer) = self.layers.get_mut(index) {
+            layer.x = x;
+            layer.y = y;
+            layer.opacity = opacity.clamp(0.0, 1.0);
+            layer.mode = mode;
+        }
+    }
+
+    pub(crate) fn update_mask(&mut self, index: usize, mask: &[u8]) -> Result<(), String> {
+        let layer = self.layer_mut(index)?;
+        if mask.len() != (layer.width * layer.height) as usize {
+            return Err("Mask must have one byte per layer pixel".to_string());
+        }
+        layer.mask = Some(mask.iter().map(|&m| m as f32 / 255.0).collect());
+        Ok(())
+    }
+
+    pub(crate) fn update_blend_mode(&mut self, index: usize, mode: &str) -> Result<(), String> {
+        let mode = BlendMode::from_name(mode)?;
+        self.layer_mut(index)?.mode = mode;
+        Ok(())
+    }
+
+    pub(crate) fn flatten_rgba(&self) -> Vec<u8> {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let mut canvas = vec![[0f32; 4]; (w * h) as usize];
+        for layer in self.layers.iter().filter(|l| l.visible && l.opacity > 0.0) {
+            for ly in 0..layer.height as i64 {
+                let cy = ly + layer.y as i64;
+                if cy < 0 || cy >= h {
+                    continue;
+                }
+                for lx in 0..layer.width as i64 {
+                    let cx = lx + layer.x as i64;
+                    if cx < 0 || cx >= w {
+                        continue;
+                    }
+                    let i = (ly * layer.width as i64 + lx) as usize;
+                    let coverage = layer.opacity * layer.mask.as_ref().map_or(1.0, |m| m[i]);
+                    if coverage <= 0.0 || layer.pixels[i][3] <= 0.0 {
+                        continue;
+                    }
+                    let source = layer.pixels[i].map(|v| v * coverage);
+                    let target = &mut canvas[(cy * w + cx) as usize];
+                    *target = composite(layer.mode, *target, source);
+                }
+            }
+        }
+        canvas
+            .iter()
+            .flat_map(|&p| {
+                let [r, g, b] = unpremultiply(p);
+                [r, g, b, p[3]].map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8)
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Compositor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Compositor {
+        Compositor { width, height, layers: Vec::new() }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // Add an RGBA layer on top, at the canvas origin; returns its index
+    #[wasm_bindgen]
+    pub fn add_layer(&mut self, data: &[u8], width: u32, height: u32) -> Result<usize, JsValue> {
+        self.push_layer(data, width, height).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_layer(&mut self, index: usize) -> Result<(), JsValue> {
+        if index >= self.layers.len() {
+            return Err(JsValue::from_str(&format!("No layer at index {}", index)));
+        }
+        self.layers.remove(index);
+        Ok(())
+    }
+
+    // Restack a layer; indices of the layers in between shift by one
+    #[wasm_bindgen]
+    pub fn move_layer(&mut self, from: usize, to: usize) -> Result<(), JsValue> {
+        if from >= self.layers.len() || to >= self.layers.len() {
+            return Err(JsValue::from_str("Layer index out of range"));
+        }
+        let layer = self.layers.remove(from);
+        self.layers.insert(to, layer);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_opacity(&mut self, index: usize, opacity: f32) -> Result<(), JsValue> {
+        self.layer_mut(index).map_err(|e| JsValue::from_str(&e))?.opacity = opacity.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    // One of normal, multiply, screen, overlay, darken, lighten, color_dodge, color_burn,
+    // hard_light, soft_light, difference, exclusion, hue, saturation, color, luminosity
+    #[wasm_bindgen]
+    pub fn set_blend_mode(&mut self, index: usize, mode: &str) -> Result<(), JsValue> {
+        self.update_blend_mode(index, mode).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_blend_mode(&self, index: usize) -> Option<String> {
+        self.layers.get(index).map(|l| l.mode.name().to_string())
+    }
+
+    // Layer position on the canvas, in pixels; may be negative or run off the edge
+    #[wasm_bindgen]
+    pub fn set_offset(&mut self, index: usize, x: i32, y: i32) -> Result<(), JsValue> {
+        let layer = self.layer_mut(index).map_err(|e| JsValue::from_str(&e))?;
+        layer.x = x;
+        layer.y = y;
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_visible(&mut self, index: usize, visible: bool) -> Result<(), JsValue> {
+        self.layer_mut(index).map_err(|e| JsValue::from_str(&e))?.visible = visible;
+        Ok(())
+    }
+
+    // Grayscale mask, one byte per layer pixel: 255 shows the layer, 0 hides it
+    #[wasm_bindgen]
+    pub fn set_mask(&mut self, index: usize, mask: &[u8]) -> Result<(), JsValue> {
+        self.update_mask(index, mask).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_mask(&mut self, index: usize) -> Result<(), JsValue> {
+        self.layer_mut(index).map_err(|e| JsValue::from_str(&e))?.mask = None;
+        Ok(())
+    }
+
+    // Composite every visible layer into one straight-alpha RGBA buffer of the canvas size
+    #[wasm_bindgen]
+    pub fn flatten(&self) -> Vec<u8> {
+        self.flatten_rgba()
+    }
+}
+
+// Mask that is 0 inside `inner` and ramps smoothly to 255 at `outer`, both as fractions of the
+// centre-to-corner distance; under a black layer it makes a vignette
+#[wasm_bindgen]
+pub fn radial_mask(width: u32, height: u32, inner: f32, outer: f32) -> Vec<u8> {
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let corner = (cx * cx + cy * cy).sqrt().max(f32::EPSILON);
+    let span = (outer - inner).max(f32::EPSILON);
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as f32 + 0.5 - cx, (i / width) as f32 + 0.5 - cy);
+            let t = (((x * x + y * y).sqrt() / corner - inner) / span).clamp(0.0, 1.0);
+            (t * t * (3.0 - 2.0 * t) * 255.0).round() as u8
+        })
+        .collect()
+}
+
+// Mask for a before/after split: 0 before `position` (0-1 across the width, or down the height
+// when vertical) and 255 after it, with a `feather`-pixel soft edge
+#[wasm_bindgen]
+pub fn split_mask(width: u32, height: u32, position: f32, vertical: bool, feather: f32) -> Vec<u8> {
+    let extent = if vertical { height } else { width } as f32;
+    let edge = position.clamp(0.0, 1.0) * extent;
+    (0..width * height)
+        .map(|i| {
+            let along = if vertical { (i / width) as f32 } else { (i % width) as f32 } + 0.5;
+            let t = if feather > 0.0 { ((along - edge) / feather + 0.5).clamp(0.0, 1.0) } else if along >= edge { 1.0 } else { 0.0 };
+            (t * 255.0).round() as u8
+        })
+        .collect()
+}
@@ -0,0 +1,593 @@
+use wasm_bindgen::prelude::*;
+
+// Longest Huffman code the demo encoder will emit
+const HUFFMAN_MAX_BITS: u8 = 24;
+
+// Shannon entropy over raw bytes (bits per byte), the bound both coders are measured against
+#[wasm_bindgen]
+pub fn byte_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0u32; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+
+    let length = data.len() as f64;
+    let mut entropy = 0.0;
+    for &count in counts.iter().filter(|&&c| c > 0) {
+        let probability = count as f64 / length;
+        entropy -= probability * probability.log2();
+    }
+
+    entropy
+}
+
+// Code lengths for the given symbol frequencies, limited to max_bits.
+// Unused symbols get length 0; a lone symbol gets length 1 so it can still be coded.
+pub(crate) fn huffman_code_lengths(freqs: &[u32], max_bits: u8) -> Vec<u8> {
+    let mut lengths = vec![0u8; freqs.len()];
+    let used: Vec<usize> = (0..freqs.len()).filter(|&i| freqs[i] > 0).collect();
+
+    match used.len() {
+        0 => return lengths,
+        1 => {
+            lengths[used[0]] = 1;
+            return lengths;
+        }
+        _ => {}
+    }
+
+    // Build the tree with a simple two-queue merge: leaves sorted by weight, internal nodes
+    // created in non-decreasing weight order
+    let mut leaves: Vec<(u64, usize)> = used.iter().map(|&i| (freqs[i] as u64, i)).collect();
+    leaves.sort();
+
+    let mut parent = vec![usize::MAX; leaves.len() * 2 - 1];
+    let mut weights: Vec<u64> = leaves.iter().map(|&(w, _)| w).collect();
+    let mut leaf_pos = 0;
+    let mut node_pos = leaves.len();
+
+    let take_min = |weights: &Vec<u64>, leaf_pos: &mut usize, node_pos: &mut usize| -> usize {
+        if *leaf_pos < leaves.len() && (*node_pos >= weights.len() || weights[*leaf_pos] <= weights[*node_pos]) {
+            *leaf_pos += 1;
+            *leaf_pos - 1
+        } else {
+            *node_pos += 1;
+            *node_pos - 1
+        }
+    };
+
+    for _ in 0..leaves.len() - 1 {
+        let a = take_min(&weights, &mut leaf_pos, &mut node_pos);
+        let b = take_min(&weights, &mut leaf_pos, &mut node_pos);
+        let id = weights.len();
+        weights.push(weights[a] + weights[b]);
+        parent[a] = id;
+        parent[b] = id;
+    }
+
+    // Depth of each leaf, walking up towards the root (the last node created)
+    let mut depths = vec![0u32; parent.len()];
+    for node in (0..parent.len() - 1).rev() {
+        depths[node] = depths[parent[node]] + 1;
+    }
+
+    let max_bits = max_bits as u32;
+    for (leaf, &(_, symbol)) in leaves.iter().enumerate() {
+        lengths[symbol] = depths[leaf].min(max_bits) as u8;
+    }
+
+    // Clamping can break the Kraft inequality; lengthen the deepest codes that still have room
+    // until the code is a valid prefix code again
+    let limit = 1u64 << max_bits;
+    let mut kraft: u64 = used.iter().map(|&i| 1u64 << (max_bits - lengths[i] as u32)).sum();
+    while kraft > limit {
+        let symbol = used
+            .iter()
+            .copied()
+            .filter(|&i| (lengths[i] as u32) < max_bits)
+            .max_by_key(|&i| (lengths[i], std::cmp::Reverse(freqs[i])))
+            .expect("at least one code can be lengthened");
+        lengths[symbol] += 1;
+        kraft -= 1u64 << (max_bits - lengths[symbol] as u32);
+    }
+
+    // Lengthening can overshoot and leave the code incomplete; hand the slack back to the most
+    // frequent symbols (Deflate rejects an incomplete code-length code)
+    let mut by_freq = used.clone();
+    by_freq.sort_by_key(|&i| std::cmp::Reverse(freqs[i]));
+    let mut changed = true;
+    while kraft < limit && changed {
+        changed = false;
+        for &symbol in &by_freq {
+            let gain = 1u64 << (max_bits - lengths[symbol] as u32);
+            if lengths[symbol] > 1 && kraft + gain <= limit {
+                lengths[symbol] -= 1;
+                kraft += gain;
+                changed = true;
+            }
+        }
+    }
+
+    lengths
+}
+
+// Canonical codes (MSB-first) for a set of code lengths, as used by Deflate
+pub(crate) fn canonical_codes(lengths: &[u8]) -> Vec<u32> {
+    let max_len = lengths.iter().copied().max().unwrap_or(0) as usize;
+    let mut bl_count = vec![0u32; max_len + 1];
+    for &len in lengths.iter().filter(|&&l| l > 0) {
+        bl_count[len as usize] += 1;
+    }
+
+    let mut next_code = vec![0u32; max_len + 2];
+    let mut code = 0u32;
+    for bits in 1..=max_len {
+        code = (code + bl_count[bits - 1]) << 1;
+        next_code[bits] = code;
+    }
+
+    lengths
+        .iter()
+        .map(|&len| {
+            if len == 0 {
+                0
+            } else {
+                let c = next_code[len as usize];
+                next_code[len as usize] += 1;
+                c
+            }
+        })
+        .collect()
+}
+
+// Table-free canonical Huffman decoder: counts per length plus symbols in canonical order
+pub(crate) struct CanonicalDecoder {
+    counts: Vec<u32>,
+    symbols: Vec<u16>,
+}
+
+impl CanonicalDecoder {
+    pub(crate) fn new(lengths: &[u8]) -> CanonicalDecoder {
+        let max_len = lengths.iter().copied().max().unwrap_or(0) as usize;
+        let mut counts = vec![0u32; max_len + 1];
+        for &len in lengths.iter().filter(|&&l| l > 0) {
+            counts[len as usize] += 1;
+        }
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for len in 1..=max_len {
+            for (symbol, &l) in lengths.iter().enumerate() {
+                if l as usize == len {
+                    symbols.push(symbol as u16);
+                }
+            }
+        }
+
+        CanonicalDecoder { counts, symbols }
+    }
+
+    // Decode one symbol, pulling bits (first code bit first) from next_bit
+    pub(crate) fn decode(&self, mut next_bit: impl FnMut() -> Option<u32>) -> Option<u16> {
+        let mut code = 0i64;
+        let mut first = 0i64;
+        let mut index = 0i64;
+
+        for len in 1..self.counts.len() {
+            code |= next_bit()? as i64;
+            let count = self.counts[len] as i64;
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        None
+    }
+}
+
+// MSB-first bit packing for the demo coders
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_len: usize,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), bit_len: 0 }
+    }
+
+    fn write(&mut self, value: u32, bits: u8) {
+        for i in (0..bits).rev() {
+            if self.bit_len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 != 0 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] |= 0x80 >> (self.bit_len % 8);
+            }
+            self.bit_len += 1;
+        }
+    }
+}
+
+// Huffman coding
+#[wasm_bindgen]
+pub struct HuffmanCode {
+    lengths: Vec<u8>,
+    codes: Vec<u32>,
+    frequencies: Vec<u32>,
+    encoded: Vec<u8>,
+    bit_length: usize,
+    symbol_count: usize,
+    entropy: f64,
+}
+
+#[wasm_bindgen]
+impl HuffmanCode {
+    // Per-byte code lengths (256 entries, 0 for unused bytes)
+    #[wasm_bindgen]
+    pub fn get_code_lengths(&self) -> Vec<u8> {
+        self.lengths.clone()
+    }
+
+    // Per-byte canonical codes, read together with get_code_lengths
+    #[wasm_bindgen]
+    pub fn get_codes(&self) -> Vec<u32> {
+        self.codes.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_frequencies(&self) -> Vec<u32> {
+        self.frequencies.clone()
+    }
+
+    // Code tree as [left, right, symbol, weight] per node, root first.
+    // Leaves have left = right = -1, internal nodes have symbol = -1.
+    #[wasm_bindgen]
+    pub fn get_tree(&self) -> Vec<i32> {
+        let mut nodes: Vec<[i32; 4]> = vec![[-1, -1, -1, 0]];
+
+        for symbol in 0..self.lengths.len() {
+            let len = self.lengths[symbol];
+            if len == 0 {
+                continue;
+            }
+
+            let mut node = 0usize;
+            for i in (0..len).rev() {
+                let side = ((self.codes[symbol] >> i) & 1) as usize;
+                if nodes[node][side] < 0 {
+                    nodes.push([-1, -1, -1, 0]);
+                    nodes[node][side] = (nodes.len() - 1) as i32;
+                }
+                node = nodes[node][side] as usize;
+            }
+            nodes[node][2] = symbol as i32;
+        }
+
+        // Children are always created after their parent, so a reverse sweep sums weights bottom-up
+        for i in (0..nodes.len()).rev() {
+            let weight = if nodes[i][2] >= 0 {
+                self.frequencies[nodes[i][2] as usize] as i32
+            } else {
+                let left = nodes[i][0];
+                let right = nodes[i][1];
+                (if left >= 0 { nodes[left as usize][3] } else { 0 })
+                    + (if right >= 0 { nodes[right as usize][3] } else { 0 })
+            };
+            nodes[i][3] = weight;
+        }
+
+        nodes.into_iter().flatten().collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_encoded(&self) -> Vec<u8> {
+        self.encoded.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_bit_length(&self) -> usize {
+        self.bit_length
+    }
+
+    #[wasm_bindgen]
+    pub fn get_symbol_count(&self) -> usize {
+        self.symbol_count
+    }
+
+    #[wasm_bindgen]
+    pub fn get_entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    // Payload bits per input byte, excluding the code table
+    #[wasm_bindgen]
+    pub fn get_bits_per_symbol(&self) -> f64 {
+        if self.symbol_count == 0 {
+            0.0
+        } else {
+            self.bit_length as f64 / self.symbol_count as f64
+        }
+    }
+
+    // Entropy bound divided by achieved rate (1.0 means the code is optimal)
+    #[wasm_bindgen]
+    pub fn get_efficiency(&self) -> f64 {
+        let bps = self.get_bits_per_symbol();
+        if bps == 0.0 {
+            1.0
+        } else {
+            self.entropy / bps
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn decode(&self) -> Vec<u8> {
+        huffman_decode(&self.encoded, self.bit_length, &self.lengths)
+    }
+}
+
+#[wasm_bindgen]
+pub fn huffman_encode(data: &[u8]) -> HuffmanCode {
+    let mut frequencies = vec![0u32; 256];
+    for &byte in data {
+        frequencies[byte as usize] += 1;
+    }
+
+    let lengths = huffman_code_lengths(&frequencies, HUFFMAN_MAX_BITS);
+    let codes = canonical_codes(&lengths);
+
+    let mut writer = BitWriter::new();
+    for &byte in data {
+        writer.write(codes[byte as usize], lengths[byte as usize]);
+    }
+
+    HuffmanCode {
+        lengths,
+        codes,
+        frequencies,
+        bit_length: writer.bit_len,
+        encoded: writer.bytes,
+        symbol_count: data.len(),
+        entropy: byte_entropy(data),
+    }
+}
+
+// Decode an MSB-first canonical Huffman stream given the 256 per-byte code lengths
+#[wasm_bindgen]
+pub fn huffman_decode(encoded: &[u8], bit_length: usize, code_lengths: &[u8]) -> Vec<u8> {
+    let decoder = CanonicalDecoder::new(code_lengths);
+    let bit_length = bit_length.min(encoded.len() * 8);
+    let mut position = 0usize;
+    let mut output = Vec::new();
+
+    while position < bit_length {
+        let symbol = decoder.decode(|| {
+            if position >= bit_length {
+                return None;
+            }
+            let bit = (encoded[position / 8] >> (7 - position % 8)) & 1;
+            position += 1;
+            Some(bit as u32)
+        });
+
+        match symbol {
+            Some(s) => output.push(s as u8),
+            None => break,
+        }
+    }
+
+    output
+}
+
+// LZ77 / LZSS compression
+// Tokens are flattened as [kind, a, b]: literals are [0, byte, 0], matches are [1, distance, length]
+#[wasm_bindgen]
+pub struct Lz77Result {
+    tokens: Vec<u32>,
+    input_length: usize,
+    offset_bits: u32,
+    length_bits: u32,
+    min_match: u32,
+    entropy: f64,
+}
+
+#[wasm_bindgen]
+impl Lz77Result {
+    #[wasm_bindgen]
+    pub fn get_tokens(&self) -> Vec<u32> {
+        self.tokens.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_token_count(&self) -> usize {
+        self.tokens.len() / 3
+    }
+
+    #[wasm_bindgen]
+    pub fn get_literal_count(&self) -> usize {
+        self.tokens.chunks(3).filter(|t| t[0] == 0).count()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_match_count(&self) -> usize {
+        self.tokens.chunks(3).filter(|t| t[0] == 1).count()
+    }
+
+    // LZSS cost: a flag bit per token, 8 bits per literal, offset + length bits per match
+    #[wasm_bindgen]
+    pub fn get_compressed_bits(&self) -> usize {
+        let match_bits = 1 + self.offset_bits as usize + self.length_bits as usize;
+        self.get_literal_count() * 9 + self.get_match_count() * match_bits
+    }
+
+    #[wasm_bindgen]
+    pub fn get_input_length(&self) -> usize {
+        self.input_length
+    }
+
+    #[wasm_bindgen]
+    pub fn get_entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    #[wasm_bindgen]
+    pub fn get_bits_per_symbol(&self) -> f64 {
+        if self.input_length == 0 {
+            0.0
+        } else {
+            self.get_compressed_bits() as f64 / self.input_length as f64
+        }
+    }
+
+    // Packed LZSS bitstream matching get_compressed_bits
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BitWriter::new();
+        for token in self.tokens.chunks(3) {
+            if token[0] == 0 {
+                writer.write(0, 1);
+                writer.write(token[1], 8);
+            } else {
+                writer.write(1, 1);
+                writer.write(token[1] - 1, self.offset_bits as u8);
+                writer.write(token[2] - self.min_match, self.length_bits as u8);
+            }
+        }
+        writer.bytes
+    }
+
+    #[wasm_bindgen]
+    pub fn decompress(&self) -> Vec<u8> {
+        lz77_decompress(&self.tokens)
+    }
+}
+
+fn bits_for(count: u32) -> u32 {
+    32 - count.saturating_sub(1).leading_zeros()
+}
+
+#[wasm_bindgen]
+pub fn lz77_compress(data: &[u8], window_size: u32, min_match: u32, max_match: u32) -> Lz77Result {
+    const HASH_BITS: u32 = 15;
+    const MAX_CHAIN: usize = 256;
+
+    let window_size = window_size.clamp(1, 1 << 24) as usize;
+    let min_match = min_match.clamp(2, 258);
+    let max_match = max_match.clamp(min_match, min_match + 65535) as usize;
+
+    // Chain positions by a hash of their first few bytes
+    let key_len = (min_match as usize).min(3);
+    let hash_at = |pos: usize| -> usize {
+        let mut h = 0u32;
+        for &byte in &data[pos..pos + key_len] {
+            h = h.wrapping_mul(0x9E3779B1).wrapping_add(byte as u32);
+        }
+        (h >> (32 - HASH_BITS)) as usize
+    };
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let insert = |pos: usize, head: &mut Vec<usize>, prev: &mut Vec<usize>| {
+        if pos + key_len <= data.len() {
+            let h = hash_at(pos);
+            prev[pos] = head[h];
+            head[h] = pos;
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut pos = 0usize;
+
+    while pos < data.len() {
+        let mut best_len = 0usize;
+        let mut best_dist = 0usize;
+
+        if pos + key_len <= data.len() {
+            let limit = max_match.min(data.len() - pos);
+            let mut candidate = head[hash_at(pos)];
+            let mut chain = 0;
+
+            while candidate != usize::MAX && pos - candidate <= window_size && chain < MAX_CHAIN {
+                let mut len = 0;
+                while len < limit && data[candidate + len] == data[pos + len] {
+                    len += 1;
+                }
+                if len > best_len {
+                    best_len = len;
+                    best_dist = pos - candidate;
+                    if len == limit {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                chain += 1;
+            }
+        }
+
+        if best_len >= min_match as usize {
+            tokens.extend_from_slice(&[1, best_dist as u32, best_len as u32]);
+            for p in pos..pos + best_len {
+                insert(p, &mut head, &mut prev);
+            }
+            pos += best_len;
+        } else {
+            tokens.extend_from_slice(&[0, data[pos] as u32, 0]);
+            insert(pos, &mut head, &mut prev);
+            pos += 1;
+        }
+    }
+
+    Lz77Result {
+        tokens,
+        input_length: data.len(),
+        offset_bits: bits_for(window_size as u32),
+        length_bits: bits_for(max_match as u32 - min_match + 1),
+        min_match,
+        entropy: byte_entropy(data),
+    }
+}
+
+#[wasm_bindgen]
+pub fn lz77_decompress(tokens: &[u32]) -> Vec<u8> {
+    let mut output = Vec::new();
+
+    for token in tokens.chunks_exact(3) {
+        if token[0] == 0 {
+            output.push(token[1] as u8);
+        } else {
+            let distance = token[1] as usize;
+            if distance == 0 || distance > output.len() {
+                break;
+            }
+            // Byte-by-byte copy so overlapping matches (distance < length) repeat correctly
+            let start = output.len() - distance;
+            for i in 0..token[2] as usize {
+                output.push(output[start + i]);
+            }
+        }
+    }
+
+    output
+}
+
+// Entropy, Huffman and LZSS rates side by side: [entropy, huffman_bps, lzss_bps, huffman_efficiency]
+#[wasm_bindgen]
+pub fn compression_summary(data: &[u8]) -> Vec<f64> {
+    let huffman = huffman_encode(data);
+    let lzss = lz77_compress(data, 4096, 3, 18);
+
+    vec![
+        huffman.get_entropy(),
+        huffman.get_bits_per_symbol(),
+        lzss.get_bits_per_symbol(),
+        huffman.get_efficiency(),
+    ]
+}
@@ -0,0 +1,315 @@
+use crate::working_space::Channel;
+use crate::transform::BorderMode;
+use wasm_bindgen::prelude::*;
+
+// A convolution kernel in row-major order. The result at each pixel is sum / divisor + bias;
+// a divisor of 0 means "use the sum of the weights" (or 1 when they sum to zero).
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Kernel {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+    divisor: f32,
+    bias: f32,
+}
+
+impl Kernel {
+    pub(crate) fn build(values: Vec<f32>, width: u32, height: u32, divisor: f32, bias: f32) -> Result<Kernel, String> {
+        if width == 0 || height == 0 || values.len() != (width * height) as usize {
+            return Err("Kernel must be non-empty with width * height values".to_string());
+        }
+        if values.iter().any(|v| !v.is_finite()) || !divisor.is_finite() || !bias.is_finite() {
+            return Err("Kernel values, divisor and bias must be finite".to_string());
+        }
+        Ok(Kernel { width, height, values, divisor, bias })
+    }
+
+    fn effective_divisor(&self) -> f32 {
+        if self.divisor != 0.0 {
+            return self.divisor;
+        }
+        let sum: f32 = self.values.iter().sum();
+        if sum.abs() > 1e-6 { sum } else { 1.0 }
+    }
+
+    // Split a rank-1 kernel into column and row vectors (kernel = column * row)
+    fn separate(&self) -> Option<(Vec<f32>, Vec<f32>)> {
+        let (kw, kh) = (self.width as usize, self.height as usize);
+        if kw == 1 || kh == 1 {
+            return None;
+        }
+        let (pivot, &largest) = self
+            .values
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))?;
+        if largest == 0.0 {
+            return None;
+        }
+        let (pr, pc) = (pivot / kw, pivot % kw);
+        let column: Vec<f32> = (0..kh).map(|i| self.values[i * kw + pc]).collect();
+        let row: Vec<f32> = (0..kw).map(|j| self.values[pr * kw + j] / largest).collect();
+        let tolerance = largest.abs() * 1e-5;
+        let rank_one = self
+            .values
+            .chunks_exact(kw)
+            .zip(&column)
+            .all(|(values, c)| values.iter().zip(&row).all(|(v, r)| (v - c * r).abs() <= tolerance));
+        rank_one.then_some((column, row))
+    }
+}
+
+#[wasm_bindgen]
+impl Kernel {
+    #[wasm_bindgen(constructor)]
+    pub fn new(values: &[f32], width: u32, height: u32, divisor: f32, bias: f32) -> Result<Kernel, JsValue> {
+        Kernel::build(values.to_vec(), width, height, divisor, bias).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_values(&self) -> Vec<f32> {
+        self.values.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_divisor(&self) -> f32 {
+        self.effective_divisor()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_bias(&self) -> f32 {
+        self.bias
+    }
+
+    #[wasm_bindgen]
+    pub fn is_separable(&self) -> bool {
+        self.separate().is_some()
+    }
+
+    // Relief lit from the top left; the weights sum to 1 so colors survive
+    #[wasm_bindgen]
+    pub fn emboss(strength: f32) -> Kernel {
+        let s = strength;
+        let values = vec![-2.0 * s, -s, 0.0, -s, 1.0, s, 0.0, s, 2.0 * s];
+        Kernel { width: 3, height: 3, values, divisor: 1.0, bias: 0.0 }
+    }
+
+    #[wasm_bindgen]
+    pub fn box_blur(size: u32) -> Kernel {
+        let size = size.max(1);
+        Kernel { width: size, height: size, values: vec![1.0; (size * size) as usize], divisor: 0.0, bias: 0.0 }
+    }
+
+    // Smear along a line length pixels long, angle degrees clockwise from horizontal. Each cell
+    // is weighted by how much of the line it covers lengthwise and by a tent across it, so
+    // diagonal and fractional-length streaks stay smooth.
+    #[wasm_bindgen]
+    pub fn motion_blur(length: f32, angle: f32) -> Kernel {
+        let length = length.max(1.0);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let weight = |x: i32, y: i32| {
+            let (x, y) = (x as f32, y as f32);
+            let along = x * cos + y * sin;
+            let across = (y * cos - x * sin).abs();
+            let overlap = ((along + 0.5).min(length / 2.0) - (along - 0.5).max(-length / 2.0)).max(0.0);
+            overlap * (1.0 - across).max(0.0)
+        };
+
+        // Start big enough for any angle, then drop empty outer rings
+        let mut half = (length / 2.0 + 1.5).ceil() as i32;
+        while half > 0 && (-half..=half).all(|i| [weight(i, -half), weight(i, half), weight(-half, i), weight(half, i)].iter().all(|&v| v < 1e-6)) {
+            half -= 1;
+        }
+
+        let size = (2 * half + 1) as u32;
+        let values = (-half..=half).flat_map(|y| (-half..=half).map(move |x| (x, y))).map(|(x, y)| weight(x, y)).collect();
+        Kernel { width: size, height: size, values, divisor: 0.0, bias: 0.0 }
+    }
+
+    // Second-derivative edge response, offset by 128 so negative values stay visible
+    #[wasm_bindgen]
+    pub fn laplacian(diagonals: bool) -> Kernel {
+        let values = if diagonals {
+            vec![1.0, 1.0, 1.0, 1.0, -8.0, 1.0, 1.0, 1.0, 1.0]
+        } else {
+            vec![0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0]
+        };
+        Kernel { width: 3, height: 3, values, divisor: 1.0, bias: 128.0 }
+    }
+
+    // (1 + amount) * image - amount * gaussian(image), with the same radius-to-sigma rule as
+    // apply_blur
+    #[wasm_bindgen]
+    pub fn unsharp_mask(radius: f32, amount: f32) -> Kernel {
+        let radius = radius.max(0.5);
+        let sigma = radius / 3.0;
+        let half = radius.ceil() as i32;
+        let size = (2 * half + 1) as usize;
+
+        let gaussian: Vec<f32> = (-half..=half)
+            .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let total: f32 = gaussian.iter().sum();
+
+        let mut values = Vec::with_capacity(size * size);
+        for gy in &gaussian {
+            for gx in &gaussian {
+                values.push(-amount * gy * gx / (total * total));
+            }
+        }
+        values[size * size / 2] += 1.0 + amount;
+        Kernel { width: size as u32, height: size as u32, values, divisor: 1.0, bias: 0.0 }
+    }
+}
+
+// Which of R, G, B and A a filter writes, from a string such as "rgb" or "rgba"
+pub(crate) fn parse_channels(channels: &str) -> Result<[bool; 4], String> {
+    let mut mask = [false; 4];
+    for ch in channels.chars() {
+        let index = "rgba".find(ch.to_ascii_lowercase()).ok_or_else(|| format!("Unknown channel: {}", ch))?;
+        mask[index] = true;
+    }
+    if !mask.contains(&true) {
+        return Err("At least one channel must be selected".to_string());
+    }
+    Ok(mask)
+}
+
+// Source index for every padded position -anchor..size+extra, None where a Zero border applies
+fn index_table(size: usize, taps: usize, border: BorderMode) -> Vec<Option<usize>> {
+    let anchor = (taps as i64 - 1) / 2;
+    (0..(size + taps - 1) as i64).map(|i| border.resolve(i - anchor, size)).collect()
+}
+
+// Correlate an RGBA image with a kernel (applied as written, not flipped, like most editors),
+// writing only the selected channels. Rank-1 kernels run as a row pass then a column pass.
+pub(crate) fn convolve_rgba<S: Channel>(data: &mut [S], width: u32, height: u32, kernel: &Kernel, border: BorderMode, channels: [bool; 4]) {
+    convolve_rgba_with(data, width, height, kernel, border, channels, S::round);
+}
+
+// convolve_rgba with the final store chosen by the caller, so filters that always truncated
+// (apply_sharpen) keep producing the same bytes
+pub(crate) fn convolve_rgba_with<S: Channel>(
+    data: &mut [S],
+    width: u32,
+    height: u32,
+    kernel: &Kernel,
+    border: BorderMode,
+    channels: [bool; 4],
+    store: fn(f32) -> S,
+) {
+    if data.len() != (width * height * 4) as usize || width == 0 || height == 0 {
+        return;
+    }
+
+    let (w, h) = (width as usize, height as usize);
+    let (kw, kh) = (kernel.width as usize, kernel.height as usize);
+    let columns = index_table(w, kw, border);
+    let rows = index_table(h, kh, border);
+    let source: Vec<f32> = data.iter().map(|v| v.value()).collect();
+    let mut sums = vec![0f32; data.len()];
+
+    if let Some((column, row)) = kernel.separate() {
+        let mut horizontal = vec![0f32; data.len()];
+        for y in 0..h {
+            for x in 0..w {
+                let mut acc = [0f32; 4];
+                for (j, &weight) in row.iter().enumerate() {
+                    if let Some(sx) = columns[x + j] {
+                        let p = &source[(y * w + sx) * 4..][..4];
+                        for c in 0..4 {
+                            acc[c] += p[c] * weight;
+                        }
+                    }
+                }
+                horizontal[(y * w + x) * 4..][..4].copy_from_slice(&acc);
+            }
+        }
+        for y in 0..h {
+            for (i, &weight) in column.iter().enumerate() {
+                if let Some(sy) = rows[y + i] {
+                    let src = &horizontal[sy * w * 4..(sy + 1) * w * 4];
+                    for (acc, &v) in sums[y * w * 4..(y + 1) * w * 4].iter_mut().zip(src) {
+                        *acc += v * weight;
+                    }
+                }
+            }
+        }
+    } else {
+        for y in 0..h {
+            for x in 0..w {
+                let mut acc = [0f32; 4];
+                for i in 0..kh {
+                    let Some(sy) = rows[y + i] else { continue };
+                    for j in 0..kw {
+                        let weight = kernel.values[i * kw + j];
+                        if weight == 0.0 {
+                            continue;
+                        }
+                        if let Some(sx) = columns[x + j] {
+                            let p = &source[(sy * w + sx) * 4..][..4];
+                            for c in 0..4 {
+                                acc[c] += p[c] * weight;
+                            }
+                        }
+                    }
+                }
+                sums[(y * w + x) * 4..][..4].copy_from_slice(&acc);
+            }
+        }
+    }
+
+    let divisor = kernel.effective_divisor();
+    for (i, (value, sum)) in data.iter_mut().zip(&sums).enumerate() {
+        if channels[i % 4] {
+            *value = store(sum / divisor + kernel.bias);
+        }
+    }
+}
+
+pub(crate) fn parse_options(border: &str, channels: &str) -> Result<(BorderMode, [bool; 4]), String> {
+    let border = BorderMode::from_name(border).ok_or_else(|| format!("Unknown border mode: {}", border))?;
+    Ok((border, parse_channels(channels)?))
+}
+
+// Filter with an arbitrary kw x kh kernel; border_mode is "clamp", "wrap", "mirror" or "zero" and
+// channels picks which of "rgba" are written
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn convolve(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    kernel: &[f32],
+    kernel_width: u32,
+    kernel_height: u32,
+    divisor: f32,
+    bias: f32,
+    border_mode: &str,
+    channels: &str,
+) -> Result<(), JsValue> {
+    let kernel = Kernel::build(kernel.to_vec(), kernel_width, kernel_height, divisor, bias).map_err(|e| JsValue::from_str(&e))?;
+    apply_kernel(data, width, height, &kernel, border_mode, channels)
+}
+
+#[wasm_bindgen]
+pub fn apply_kernel(data: &mut [u8], width: u32, height: u32, kernel: &Kernel, border_mode: &str, channels: &str) -> Result<(), JsValue> {
+    if data.len() != (width * height * 4) as usize {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    let (border, channels) = parse_options(border_mode, channels).map_err(|e| JsValue::from_str(&e))?;
+    convolve_rgba(data, width, height, kernel, border, channels);
+    Ok(())
+}
+
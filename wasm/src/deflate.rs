@@ -0,0 +1,682 @@
+use crate::compression::{canonical_codes, huffman_code_lengths};
+use wasm_bindgen::prelude::*;
+
+// RFC 1951 length and distance code tables
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const WINDOW_SIZE: usize = 32768;
+const MAX_MATCH: usize = 258;
+const MIN_MATCH: usize = 3;
+
+// Inflate
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u64,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, pos: 0, bit_buf: 0, bit_count: 0 }
+    }
+
+    // Top up to at least `count` bits; past the end we feed zeros and let `consume` catch overruns
+    fn refill(&mut self, count: u32) {
+        while self.bit_count < count {
+            let byte = self.data.get(self.pos).copied().unwrap_or(0);
+            self.bit_buf |= (byte as u64) << self.bit_count;
+            self.pos += 1;
+            self.bit_count += 8;
+        }
+    }
+
+    fn peek(&mut self, count: u32) -> u32 {
+        self.refill(count);
+        (self.bit_buf & ((1u64 << count) - 1)) as u32
+    }
+
+    fn consume(&mut self, count: u32) -> Result<(), String> {
+        self.bit_buf >>= count;
+        self.bit_count -= count;
+        if self.pos > self.data.len() && (self.pos - self.data.len()) * 8 > self.bit_count as usize {
+            return Err("Unexpected end of deflate stream".to_string());
+        }
+        Ok(())
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        if count == 0 {
+            return Ok(0);
+        }
+        let value = self.peek(count);
+        self.consume(count)?;
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        let drop = self.bit_count % 8;
+        self.bit_buf >>= drop;
+        self.bit_count -= drop;
+    }
+
+    // Hand back whole bytes still sitting in the bit buffer
+    fn read_aligned_bytes(&mut self, count: usize) -> Result<Vec<u8>, String> {
+        let mut out = Vec::with_capacity(count);
+        while out.len() < count && self.bit_count >= 8 {
+            out.push(self.bit_buf as u8);
+            self.bit_buf >>= 8;
+            self.bit_count -= 8;
+        }
+        let remaining = count - out.len();
+        let end = self.pos + remaining;
+        if end > self.data.len() {
+            return Err("Stored block runs past end of data".to_string());
+        }
+        out.extend_from_slice(&self.data[self.pos..end]);
+        self.pos = end;
+        Ok(out)
+    }
+}
+
+// Single-level lookup table indexed by the next max_len bits (LSB-first, i.e. bit-reversed codes)
+struct HuffmanTable {
+    entries: Vec<u32>,
+    max_len: u32,
+}
+
+impl HuffmanTable {
+    fn new(lengths: &[u8]) -> Result<HuffmanTable, String> {
+        let max_len = lengths.iter().copied().max().unwrap_or(0) as u32;
+        if max_len == 0 {
+            return Ok(HuffmanTable { entries: Vec::new(), max_len: 0 });
+        }
+
+        let codes = canonical_codes(lengths);
+        let mut entries = vec![0u32; 1 << max_len];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let len = len as u32;
+            if codes[symbol] >> len != 0 {
+                return Err("Over-subscribed Huffman code lengths".to_string());
+            }
+            let reversed = codes[symbol].reverse_bits() >> (32 - len);
+            // Every index whose low `len` bits equal the code maps to this symbol
+            let mut idx = reversed as usize;
+            while idx < entries.len() {
+                entries[idx] = (symbol as u32) << 4 | len;
+                idx += 1 << len;
+            }
+        }
+
+        Ok(HuffmanTable { entries, max_len })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u32, String> {
+        if self.max_len == 0 {
+            return Err("Empty Huffman table".to_string());
+        }
+        let entry = self.entries[reader.peek(self.max_len) as usize];
+        let len = entry & 0xF;
+        if len == 0 {
+            return Err("Invalid Huffman code in deflate stream".to_string());
+        }
+        reader.consume(len)?;
+        Ok(entry >> 4)
+    }
+}
+
+fn fixed_tables() -> (HuffmanTable, HuffmanTable) {
+    let mut lit_lengths = [0u8; 288];
+    lit_lengths[..144].fill(8);
+    lit_lengths[144..256].fill(9);
+    lit_lengths[256..280].fill(7);
+    lit_lengths[280..].fill(8);
+    let dist_lengths = [5u8; 30];
+    (
+        HuffmanTable::new(&lit_lengths).expect("fixed literal table"),
+        HuffmanTable::new(&dist_lengths).expect("fixed distance table"),
+    )
+}
+
+fn read_dynamic_tables(reader: &mut BitReader) -> Result<(HuffmanTable, HuffmanTable), String> {
+    let hlit = reader.bits(5)? as usize + 257;
+    let hdist = reader.bits(5)? as usize + 1;
+    let hclen = reader.bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for &idx in CODE_LENGTH_ORDER.iter().take(hclen) {
+        code_length_lengths[idx] = reader.bits(3)? as u8;
+    }
+    let code_length_table = HuffmanTable::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        let symbol = code_length_table.decode(reader)?;
+        match symbol {
+            0..=15 => lengths.push(symbol as u8),
+            16 => {
+                let previous = *lengths.last().ok_or("Repeat code with no previous length")?;
+                let repeat = 3 + reader.bits(2)? as usize;
+                lengths.extend(std::iter::repeat_n(previous, repeat));
+            }
+            17 => {
+                let repeat = 3 + reader.bits(3)? as usize;
+                lengths.extend(std::iter::repeat_n(0, repeat));
+            }
+            _ => {
+                let repeat = 11 + reader.bits(7)? as usize;
+                lengths.extend(std::iter::repeat_n(0, repeat));
+            }
+        }
+    }
+    if lengths.len() > hlit + hdist {
+        return Err("Code length repeat overflows the table".to_string());
+    }
+
+    Ok((HuffmanTable::new(&lengths[..hlit])?, HuffmanTable::new(&lengths[hlit..])?))
+}
+
+// Decompress a raw deflate stream (no zlib header)
+pub(crate) fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader::new(data);
+    let mut out: Vec<u8> = Vec::with_capacity(data.len() * 4);
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let header = reader.read_aligned_bytes(4)?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err("Stored block length check failed".to_string());
+                }
+                out.extend(reader.read_aligned_bytes(len as usize)?);
+            }
+            kind @ (1 | 2) => {
+                let (lit_table, dist_table) = if kind == 1 { fixed_tables() } else { read_dynamic_tables(&mut reader)? };
+                loop {
+                    let symbol = lit_table.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+
+                    let len_idx = symbol - 257;
+                    if len_idx >= LENGTH_BASE.len() {
+                        return Err("Invalid length symbol".to_string());
+                    }
+                    let length = LENGTH_BASE[len_idx] as usize + reader.bits(LENGTH_EXTRA[len_idx] as u32)? as usize;
+
+                    let dist_idx = dist_table.decode(&mut reader)? as usize;
+                    if dist_idx >= DIST_BASE.len() {
+                        return Err("Invalid distance symbol".to_string());
+                    }
+                    let distance = DIST_BASE[dist_idx] as usize + reader.bits(DIST_EXTRA[dist_idx] as u32)? as usize;
+                    if distance > out.len() {
+                        return Err("Distance reaches before start of output".to_string());
+                    }
+
+                    let start = out.len() - distance;
+                    for i in 0..length {
+                        out.push(out[start + i]);
+                    }
+                }
+            }
+            _ => return Err("Invalid deflate block type".to_string()),
+        }
+
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    // 5552 is the largest run that cannot overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+pub(crate) fn zlib_decompress_bytes(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err("zlib stream too short".to_string());
+    }
+    let cmf = data[0];
+    let flg = data[1];
+    if cmf & 0x0F != 8 || !((cmf as u16) << 8 | flg as u16).is_multiple_of(31) {
+        return Err("Invalid zlib header".to_string());
+    }
+    if flg & 0x20 != 0 {
+        return Err("zlib preset dictionaries are not supported".to_string());
+    }
+
+    let out = inflate(&data[2..])?;
+    let expected = u32::from_be_bytes([data[data.len() - 4], data[data.len() - 3], data[data.len() - 2], data[data.len() - 1]]);
+    if adler32(&out) != expected {
+        return Err("zlib Adler-32 checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+// Deflate
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_buf: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.bit_buf |= (value as u64) << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bit_buf as u8);
+            self.bit_buf >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    // Huffman codes are defined MSB-first but packed starting from the least significant bit
+    fn write_code(&mut self, code: u32, len: u8) {
+        if len > 0 {
+            self.write(code.reverse_bits() >> (32 - len as u32), len as u32);
+        }
+    }
+
+    fn flush_byte(&mut self) {
+        if self.bit_count > 0 {
+            self.write(0, 8 - self.bit_count);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+fn length_symbol(length: usize) -> usize {
+    LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap_or(0)
+}
+
+fn distance_symbol(distance: usize) -> usize {
+    DIST_BASE.iter().rposition(|&base| base as usize <= distance).unwrap_or(0)
+}
+
+// Greedy/lazy hash-chain match finder; chain length and laziness scale with the level
+fn find_tokens(data: &[u8], level: u32) -> Vec<Token> {
+    const HASH_BITS: u32 = 15;
+    let max_chain = [0usize, 4, 8, 16, 32, 64, 128, 256, 1024, 4096][level.min(9) as usize];
+    let lazy = level >= 4;
+
+    let hash_at = |pos: usize| -> usize {
+        let v = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+        (v.wrapping_mul(0x9E3779B1) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut inserted = 0usize;
+
+    let mut insert_until = |end: usize, head: &mut Vec<usize>, prev: &mut Vec<usize>| {
+        while inserted < end {
+            if inserted + MIN_MATCH <= data.len() {
+                let h = hash_at(inserted);
+                prev[inserted] = head[h];
+                head[h] = inserted;
+            }
+            inserted += 1;
+        }
+    };
+
+    let longest_match = |pos: usize, head: &Vec<usize>, prev: &Vec<usize>| -> (usize, usize) {
+        if pos + MIN_MATCH > data.len() {
+            return (0, 0);
+        }
+        let limit = MAX_MATCH.min(data.len() - pos);
+        let mut best = (0, 0);
+        let mut candidate = head[hash_at(pos)];
+        let mut chain = 0;
+        while candidate != usize::MAX && pos - candidate <= WINDOW_SIZE && chain < max_chain {
+            if data[candidate + best.0.min(limit - 1)] == data[pos + best.0.min(limit - 1)] {
+                let mut len = 0;
+                while len < limit && data[candidate + len] == data[pos + len] {
+                    len += 1;
+                }
+                if len > best.0 {
+                    best = (len, pos - candidate);
+                    if len == limit {
+                        break;
+                    }
+                }
+            }
+            candidate = prev[candidate];
+            chain += 1;
+        }
+        best
+    };
+
+    let mut tokens = Vec::with_capacity(data.len() / 2);
+    let mut pos = 0;
+    while pos < data.len() {
+        insert_until(pos, &mut head, &mut prev);
+        let (mut len, mut dist) = if max_chain > 0 { longest_match(pos, &head, &prev) } else { (0, 0) };
+
+        // Lazy evaluation: if the next position has a longer match, emit a literal first
+        if lazy && (MIN_MATCH..32).contains(&len) && pos + 1 < data.len() {
+            insert_until(pos + 1, &mut head, &mut prev);
+            let (next_len, next_dist) = longest_match(pos + 1, &head, &prev);
+            if next_len > len {
+                tokens.push(Token::Literal(data[pos]));
+                pos += 1;
+                len = next_len;
+                dist = next_dist;
+            }
+        }
+
+        if len >= MIN_MATCH {
+            tokens.push(Token::Match { length: len as u16, distance: dist as u16 });
+            pos += len;
+        } else {
+            tokens.push(Token::Literal(data[pos]));
+            pos += 1;
+        }
+    }
+
+    tokens
+}
+
+// Huffman lengths that are always decodable: every alphabet gets at least two codes
+fn safe_code_lengths(freqs: &mut [u32], max_bits: u8) -> Vec<u8> {
+    let mut used = freqs.iter().filter(|&&f| f > 0).count();
+    for f in freqs.iter_mut() {
+        if used >= 2 {
+            break;
+        }
+        if *f == 0 {
+            *f = 1;
+            used += 1;
+        }
+    }
+    huffman_code_lengths(freqs, max_bits)
+}
+
+// Run-length encode code lengths with symbols 16/17/18; returns (symbol, extra_bits_value)
+fn rle_code_lengths(lengths: &[u8]) -> Vec<(u8, u8)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let value = lengths[i];
+        let mut run = 1;
+        while i + run < lengths.len() && lengths[i + run] == value {
+            run += 1;
+        }
+
+        if value == 0 && run >= 3 {
+            let mut left = run;
+            while left >= 11 {
+                let n = left.min(138);
+                out.push((18, (n - 11) as u8));
+                left -= n;
+            }
+            if left >= 3 {
+                out.push((17, (left - 3) as u8));
+                left = 0;
+            }
+            out.extend(std::iter::repeat_n((0, 0), left));
+        } else if value != 0 && run >= 4 {
+            out.push((value, 0));
+            let mut left = run - 1;
+            while left >= 3 {
+                let n = left.min(6);
+                out.push((16, (n - 3) as u8));
+                left -= n;
+            }
+            out.extend(std::iter::repeat_n((value, 0), left));
+        } else {
+            out.extend(std::iter::repeat_n((value, 0), run));
+        }
+        i += run;
+    }
+    out
+}
+
+fn write_dynamic_block(writer: &mut BitWriter, tokens: &[Token], last: bool) {
+    let mut lit_freqs = vec![0u32; 286];
+    let mut dist_freqs = vec![0u32; 30];
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => lit_freqs[byte as usize] += 1,
+            Token::Match { length, distance } => {
+                lit_freqs[257 + length_symbol(length as usize)] += 1;
+                dist_freqs[distance_symbol(distance as usize)] += 1;
+            }
+        }
+    }
+    lit_freqs[256] = 1;
+
+    let lit_lengths = safe_code_lengths(&mut lit_freqs, 15);
+    let dist_lengths = safe_code_lengths(&mut dist_freqs, 15);
+    let lit_codes = canonical_codes(&lit_lengths);
+    let dist_codes = canonical_codes(&dist_lengths);
+
+    let hlit = lit_lengths.iter().rposition(|&l| l > 0).unwrap_or(0).max(256) + 1;
+    let hdist = dist_lengths.iter().rposition(|&l| l > 0).unwrap_or(0) + 1;
+
+    let mut all_lengths = lit_lengths[..hlit].to_vec();
+    all_lengths.extend_from_slice(&dist_lengths[..hdist]);
+    let rle = rle_code_lengths(&all_lengths);
+
+    let mut cl_freqs = vec![0u32; 19];
+    for &(symbol, _) in &rle {
+        cl_freqs[symbol as usize] += 1;
+    }
+    let cl_lengths = safe_code_lengths(&mut cl_freqs, 7);
+    let cl_codes = canonical_codes(&cl_lengths);
+    let hclen = CODE_LENGTH_ORDER.iter().rposition(|&i| cl_lengths[i] > 0).unwrap_or(0).max(3) + 1;
+
+    writer.write(last as u32, 1);
+    writer.write(2, 2);
+    writer.write((hlit - 257) as u32, 5);
+    writer.write((hdist - 1) as u32, 5);
+    writer.write((hclen - 4) as u32, 4);
+    for &idx in CODE_LENGTH_ORDER.iter().take(hclen) {
+        writer.write(cl_lengths[idx] as u32, 3);
+    }
+    for &(symbol, extra) in &rle {
+        writer.write_code(cl_codes[symbol as usize], cl_lengths[symbol as usize]);
+        match symbol {
+            16 => writer.write(extra as u32, 2),
+            17 => writer.write(extra as u32, 3),
+            18 => writer.write(extra as u32, 7),
+            _ => {}
+        }
+    }
+
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => writer.write_code(lit_codes[byte as usize], lit_lengths[byte as usize]),
+            Token::Match { length, distance } => {
+                let ls = length_symbol(length as usize);
+                writer.write_code(lit_codes[257 + ls], lit_lengths[257 + ls]);
+                writer.write(length as u32 - LENGTH_BASE[ls] as u32, LENGTH_EXTRA[ls] as u32);
+                let ds = distance_symbol(distance as usize);
+                writer.write_code(dist_codes[ds], dist_lengths[ds]);
+                writer.write(distance as u32 - DIST_BASE[ds] as u32, DIST_EXTRA[ds] as u32);
+            }
+        }
+    }
+    writer.write_code(lit_codes[256], lit_lengths[256]);
+}
+
+fn write_stored_blocks(writer: &mut BitWriter, data: &[u8], last: bool) {
+    let chunks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(65535).collect() };
+    let count = chunks.len();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        writer.write((last && i + 1 == count) as u32, 1);
+        writer.write(0, 2);
+        writer.flush_byte();
+        let len = chunk.len() as u16;
+        writer.bytes.extend_from_slice(&len.to_le_bytes());
+        writer.bytes.extend_from_slice(&(!len).to_le_bytes());
+        writer.bytes.extend_from_slice(chunk);
+    }
+}
+
+// Raw deflate at level 0 (stored) to 9 (slowest, smallest)
+pub(crate) fn deflate(data: &[u8], level: u32) -> Vec<u8> {
+    let mut writer = BitWriter { bytes: Vec::with_capacity(data.len() / 2 + 64), bit_buf: 0, bit_count: 0 };
+
+    if level == 0 || data.is_empty() {
+        write_stored_blocks(&mut writer, data, true);
+        writer.flush_byte();
+        return writer.bytes;
+    }
+
+    let tokens = find_tokens(data, level);
+
+    // Each block gets its own Huffman tables; fall back to stored when coding would expand it
+    const TOKENS_PER_BLOCK: usize = 1 << 16;
+    let mut consumed = 0usize;
+    let block_count = tokens.len().div_ceil(TOKENS_PER_BLOCK);
+    for (i, block) in tokens.chunks(TOKENS_PER_BLOCK).enumerate() {
+        let last = i + 1 == block_count;
+        let raw_len: usize = block
+            .iter()
+            .map(|t| match t {
+                Token::Literal(_) => 1,
+                Token::Match { length, .. } => *length as usize,
+            })
+            .sum();
+
+        let mut trial = BitWriter { bytes: Vec::new(), bit_buf: writer.bit_buf, bit_count: writer.bit_count };
+        write_dynamic_block(&mut trial, block, last);
+        if trial.bytes.len() > raw_len + 5 * raw_len.div_ceil(65535) + 1 {
+            write_stored_blocks(&mut writer, &data[consumed..consumed + raw_len], last);
+        } else {
+            writer.bytes.extend_from_slice(&trial.bytes);
+            writer.bit_buf = trial.bit_buf;
+            writer.bit_count = trial.bit_count;
+        }
+        consumed += raw_len;
+    }
+
+    writer.flush_byte();
+    writer.bytes
+}
+
+pub(crate) fn zlib_compress_bytes(data: &[u8], level: u32) -> Vec<u8> {
+    let level = level.min(9);
+    let flevel = match level {
+        0..=1 => 0,
+        2..=5 => 1,
+        6 => 2,
+        _ => 3,
+    };
+    let cmf = 0x78u16;
+    let mut flg = flevel << 6;
+    flg += 31 - ((cmf << 8) | flg) % 31;
+
+    let mut out = vec![cmf as u8, flg as u8];
+    out.extend(deflate(data, level));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+// zlib (RFC 1950) wrappers, also handy for the compression lab
+#[wasm_bindgen]
+pub fn zlib_compress(data: &[u8], level: u32) -> Vec<u8> {
+    zlib_compress_bytes(data, level)
+}
+
+#[wasm_bindgen]
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, JsValue> {
+    zlib_decompress_bytes(data).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use std::io::{Read, Write};
+
+    // Empty, runs, noise, and a mix of repeats and noise so every block type gets exercised
+    fn samples() -> Vec<Vec<u8>> {
+        let mut rng = SmallRng::seed_from_u64(7);
+        vec![
+            Vec::new(),
+            vec![0],
+            b"hello hello hello hello".to_vec(),
+            vec![0; 200_000],
+            (0..100_000).map(|_| rng.gen()).collect(),
+            (0..300_000).map(|i| ((i / 7) % 13) as u8 ^ (rng.gen::<u8>() & 1)).collect(),
+            include_bytes!("deflate.rs").to_vec(),
+        ]
+    }
+
+    #[test]
+    fn flate2_decodes_every_level() {
+        for data in samples() {
+            for level in 0..=9 {
+                let compressed = zlib_compress_bytes(&data, level);
+                let mut decoded = Vec::new();
+                ZlibDecoder::new(&compressed[..]).read_to_end(&mut decoded).unwrap();
+                assert_eq!(decoded, data, "level {level}, {} bytes", data.len());
+                assert_eq!(zlib_decompress_bytes(&compressed).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_flate2_output() {
+        for data in samples() {
+            for level in 0..=9 {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+                encoder.write_all(&data).unwrap();
+                let compressed = encoder.finish().unwrap();
+                assert_eq!(zlib_decompress_bytes(&compressed).unwrap(), data, "level {level}, {} bytes", data.len());
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_streams() {
+        let mut compressed = zlib_compress_bytes(b"hello hello hello hello", 6);
+        assert!(zlib_decompress_bytes(&compressed[..4]).is_err());
+        let last = compressed.len() - 1;
+        compressed[last] ^= 1;
+        assert!(zlib_decompress_bytes(&compressed).is_err());
+        assert!(zlib_decompress_bytes(&[0x78, 0x9c, 0xff, 0, 0, 0]).is_err());
+    }
+}
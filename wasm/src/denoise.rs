@@ -0,0 +1,285 @@
+use crate::blur::SummedAreaTable;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+fn check_size(data: &[u8], width: u32, height: u32) -> bool {
+    data.len() == (width * height * 4) as usize && width > 0 && height > 0
+}
+
+// Median of one channel over a (2r+1)^2 window with replicated borders, after Perreault and
+// Hebert: per-column histograms slide down the image, and the window histogram slides along a
+// row by adding one column and dropping another. A coarse 16-bin level picks the segment and
+// the fine 256-bin level is only brought up to date for segments actually searched, so the
+// cost per pixel doesn't grow with the radius.
+fn median_channel(src: &[u8], out: &mut [u8], w: usize, h: usize, radius: usize, channel: usize) {
+    let r = radius as i64;
+    let clamp_x = |x: i64| x.clamp(0, w as i64 - 1) as usize;
+    let clamp_y = |y: i64| y.clamp(0, h as i64 - 1) as usize;
+    let value = |x: usize, y: usize| src[(y * w + x) * 4 + channel] as usize;
+    let half = ((2 * radius + 1) * (2 * radius + 1) / 2) as u32;
+
+    let mut fine = vec![0u32; w * 256];
+    let mut coarse = vec![0u32; w * 16];
+    let update_row = |y: usize, add: bool, fine: &mut [u32], coarse: &mut [u32]| {
+        for x in 0..w {
+            let v = value(x, y);
+            if add {
+                fine[x * 256 + v] += 1;
+                coarse[x * 16 + v / 16] += 1;
+            } else {
+                fine[x * 256 + v] -= 1;
+                coarse[x * 16 + v / 16] -= 1;
+            }
+        }
+    };
+    for dy in -r..=r {
+        update_row(clamp_y(dy), true, &mut fine, &mut coarse);
+    }
+
+    for y in 0..h {
+        if y > 0 {
+            update_row(clamp_y(y as i64 + r), true, &mut fine, &mut coarse);
+            update_row(clamp_y(y as i64 - 1 - r), false, &mut fine, &mut coarse);
+        }
+
+        let mut window_coarse = [0u32; 16];
+        let mut window_fine = [0u32; 256];
+        // Column position each fine segment was last brought up to date for
+        let mut synced = [None::<i64>; 16];
+        for dx in -r..=r {
+            let col = clamp_x(dx);
+            for (k, n) in window_coarse.iter_mut().zip(&coarse[col * 16..(col + 1) * 16]) {
+                *k += n;
+            }
+        }
+
+        for x in 0..w as i64 {
+            if x > 0 {
+                let (add, drop) = (clamp_x(x + r), clamp_x(x - 1 - r));
+                for g in 0..16 {
+                    window_coarse[g] = window_coarse[g] + coarse[add * 16 + g] - coarse[drop * 16 + g];
+                }
+            }
+
+            let mut below = 0u32;
+            let mut segment = 0;
+            while below + window_coarse[segment] <= half {
+                below += window_coarse[segment];
+                segment += 1;
+            }
+
+            let bins = segment * 16..segment * 16 + 16;
+            match synced[segment] {
+                Some(last) if x - last <= 2 * r + 1 => {
+                    for p in last + 1..=x {
+                        let (add, drop) = (clamp_x(p + r), clamp_x(p - 1 - r));
+                        for b in bins.clone() {
+                            window_fine[b] = window_fine[b] + fine[add * 256 + b] - fine[drop * 256 + b];
+                        }
+                    }
+                }
+                _ => {
+                    window_fine[bins.clone()].fill(0);
+                    for dx in -r..=r {
+                        let col = clamp_x(x + dx);
+                        for b in bins.clone() {
+                            window_fine[b] += fine[col * 256 + b];
+                        }
+                    }
+                }
+            }
+            synced[segment] = Some(x);
+
+            let mut bin = segment * 16;
+            while below + window_fine[bin] <= half {
+                below += window_fine[bin];
+                bin += 1;
+            }
+            out[(y * w + x as usize) * 4 + channel] = bin as u8;
+        }
+    }
+}
+
+// Median filter on R, G and B with a (2 * radius + 1) square window; alpha is preserved
+#[wasm_bindgen]
+pub fn median_filter(data: &mut [u8], width: u32, height: u32, radius: u32) {
+    if !check_size(data, width, height) || radius == 0 {
+        return;
+    }
+    let source = data.to_vec();
+    for c in 0..3 {
+        median_channel(&source, data, width as usize, height as usize, radius as usize, c);
+    }
+}
+
+// Bilateral filter: a Gaussian in space times a Gaussian in RGB distance, so smoothing stops at
+// edges. sigma_range is in 0-255 intensity levels.
+#[wasm_bindgen]
+pub fn bilateral_filter(data: &mut [u8], width: u32, height: u32, sigma_spatial: f32, sigma_range: f32) {
+    if !check_size(data, width, height) || sigma_spatial <= 0.0 || sigma_range <= 0.0 {
+        return;
+    }
+
+    let (w, h) = (width as i64, height as i64);
+    let r = (sigma_spatial * 2.0).ceil() as i64;
+    let spatial: Vec<f32> = (-r..=r)
+        .flat_map(|dy| (-r..=r).map(move |dx| (dx * dx + dy * dy) as f32))
+        .map(|d2| (-d2 / (2.0 * sigma_spatial * sigma_spatial)).exp())
+        .collect();
+    // RGB distances run 0..=442
+    let range: Vec<f32> = (0..=442)
+        .map(|d| (-((d * d) as f32) / (2.0 * sigma_range * sigma_range)).exp())
+        .collect();
+
+    let source = data.to_vec();
+    for y in 0..h {
+        for x in 0..w {
+            let center = &source[((y * w + x) * 4) as usize..][..3];
+            let mut acc = [0f32; 3];
+            let mut total = 0f32;
+            for dy in -r..=r {
+                let sy = (y + dy).clamp(0, h - 1);
+                for dx in -r..=r {
+                    let sx = (x + dx).clamp(0, w - 1);
+                    let p = &source[((sy * w + sx) * 4) as usize..][..3];
+                    let d2: i32 = (0..3).map(|c| (p[c] as i32 - center[c] as i32).pow(2)).sum();
+                    let weight = spatial[((dy + r) * (2 * r + 1) + dx + r) as usize] * range[(d2 as f32).sqrt().round() as usize];
+                    for c in 0..3 {
+                        acc[c] += p[c] as f32 * weight;
+                    }
+                    total += weight;
+                }
+            }
+            let out = &mut data[((y * w + x) * 4) as usize..][..3];
+            for c in 0..3 {
+                out[c] = (acc[c] / total).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+// Mean over the (2r+1)^2 window around every pixel of a single-channel plane
+fn box_mean(plane: &[f64], w: usize, h: usize, r: usize) -> Vec<f64> {
+    SummedAreaTable::build(plane, w, h, 1).box_means(r)
+}
+
+// Guided filter (He et al.) with each channel as its own guide: an edge-preserving smoother
+// that runs in O(1) per pixel. epsilon is the regularization on 0-1 intensities; around 0.01
+// smooths areas whose local standard deviation is below ~0.1 (25 levels).
+#[wasm_bindgen]
+pub fn guided_filter(data: &mut [u8], width: u32, height: u32, radius: u32, epsilon: f32) {
+    if !check_size(data, width, height) || radius == 0 || epsilon <= 0.0 {
+        return;
+    }
+
+    let (w, h, r) = (width as usize, height as usize, radius as usize);
+    for c in 0..3 {
+        let guide: Vec<f64> = data.chunks_exact(4).map(|p| p[c] as f64 / 255.0).collect();
+        let squares: Vec<f64> = guide.iter().map(|v| v * v).collect();
+        let mean = box_mean(&guide, w, h, r);
+        let mean_sq = box_mean(&squares, w, h, r);
+
+        let mut a = vec![0f64; w * h];
+        let mut b = vec![0f64; w * h];
+        for i in 0..w * h {
+            let variance = (mean_sq[i] - mean[i] * mean[i]).max(0.0);
+            a[i] = variance / (variance + epsilon as f64);
+            b[i] = mean[i] * (1.0 - a[i]);
+        }
+        let mean_a = box_mean(&a, w, h, r);
+        let mean_b = box_mean(&b, w, h, r);
+
+        for (i, p) in data.chunks_exact_mut(4).enumerate() {
+            p[c] = ((mean_a[i] * guide[i] + mean_b[i]) * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// Non-local means: every pixel becomes a weighted average of the pixels in its search window,
+// weighted by how similar their surrounding patches are. strength is in 0-255 levels (roughly
+// the noise level to remove). Windows are capped (patch radius 3, search radius 10) to keep
+// the cost interactive; each offset is handled for the whole image at once with a summed-area
+// table of squared differences, so the patch size doesn't add to the cost.
+#[wasm_bindgen]
+pub fn non_local_means(data: &mut [u8], width: u32, height: u32, strength: f32, patch_radius: u32, search_radius: u32) {
+    if !check_size(data, width, height) || strength <= 0.0 || search_radius == 0 {
+        return;
+    }
+
+    let (w, h) = (width as usize, height as usize);
+    let (pr, sr) = (patch_radius.min(3) as usize, search_radius.min(10) as i64);
+    let source: Vec<f64> = data.iter().map(|&v| v as f64).collect();
+    let h2 = (strength as f64).powi(2);
+
+    let mut sums = vec![0f64; w * h * 3];
+    let mut weights = vec![0f64; w * h];
+    let mut diff = vec![0f64; w * h];
+    for dy in -sr..=sr {
+        for dx in -sr..=sr {
+            let neighbour = |x: usize, y: usize| {
+                let nx = (x as i64 + dx).clamp(0, w as i64 - 1) as usize;
+                let ny = (y as i64 + dy).clamp(0, h as i64 - 1) as usize;
+                (ny * w + nx) * 4
+            };
+            for y in 0..h {
+                for x in 0..w {
+                    let (a, b) = ((y * w + x) * 4, neighbour(x, y));
+                    diff[y * w + x] = (0..3).map(|c| (source[a + c] - source[b + c]).powi(2)).sum::<f64>() / 3.0;
+                }
+            }
+            let distance = box_mean(&diff, w, h, pr);
+            for y in 0..h {
+                for x in 0..w {
+                    let i = y * w + x;
+                    let weight = (-distance[i] / h2).exp();
+                    let b = neighbour(x, y);
+                    for c in 0..3 {
+                        sums[i * 3 + c] += source[b + c] * weight;
+                    }
+                    weights[i] += weight;
+                }
+            }
+        }
+    }
+
+    for (i, p) in data.chunks_exact_mut(4).enumerate() {
+        for c in 0..3 {
+            p[c] = (sums[i * 3 + c] / weights[i]).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// Additive Gaussian noise on R, G and B with standard deviation sigma (0-255 levels)
+#[wasm_bindgen]
+pub fn add_gaussian_noise(data: &mut [u8], width: u32, height: u32, sigma: f32, seed: u32) {
+    if !check_size(data, width, height) || sigma <= 0.0 {
+        return;
+    }
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    for p in data.chunks_exact_mut(4) {
+        for c in p.iter_mut().take(3) {
+            // Box-Muller
+            let u1 = rng.gen::<f32>().max(f32::MIN_POSITIVE);
+            let u2 = rng.gen::<f32>();
+            let normal = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+            *c = (*c as f32 + normal * sigma).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// Turn a fraction `amount` of the pixels pure black or white
+#[wasm_bindgen]
+pub fn add_salt_and_pepper_noise(data: &mut [u8], width: u32, height: u32, amount: f32, seed: u32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let amount = amount.clamp(0.0, 1.0);
+    for p in data.chunks_exact_mut(4) {
+        if rng.gen::<f32>() < amount {
+            let v = if rng.gen::<bool>() { 255 } else { 0 };
+            p[..3].fill(v);
+        }
+    }
+}
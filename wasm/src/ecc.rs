@@ -0,0 +1,599 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+// GF(256) arithmetic with the QR / CCSDS primitive polynomial x^8 + x^4 + x^3 + x^2 + 1
+pub(crate) struct Gf256 {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+impl Gf256 {
+    pub(crate) fn new() -> Gf256 {
+        let mut exp = [0u8; 512];
+        let mut log = [0u8; 256];
+        let mut x = 1u16;
+
+        for (i, e) in exp.iter_mut().enumerate().take(255) {
+            *e = x as u8;
+            log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= 0x11D;
+            }
+        }
+        for i in 255..512 {
+            exp[i] = exp[i - 255];
+        }
+
+        Gf256 { exp, log }
+    }
+
+    pub(crate) fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        if a == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + 255 - self.log[b as usize] as usize]
+        }
+    }
+
+    pub(crate) fn pow_alpha(&self, power: usize) -> u8 {
+        self.exp[power % 255]
+    }
+
+    fn inverse(&self, a: u8) -> u8 {
+        self.exp[255 - self.log[a as usize] as usize]
+    }
+
+    // Evaluate a polynomial stored highest degree first
+    fn eval_high_first(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+
+    // Evaluate a polynomial stored lowest degree first
+    fn eval_low_first(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().rev().fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+}
+
+// Generator polynomial (x - a^0)(x - a^1)...(x - a^(n-1)), highest degree first
+pub(crate) fn rs_generator(gf: &Gf256, ecc_len: usize) -> Vec<u8> {
+    let mut generator = vec![1u8];
+    for i in 0..ecc_len {
+        let root = gf.pow_alpha(i);
+        let mut next = vec![0u8; generator.len() + 1];
+        for (j, &coef) in generator.iter().enumerate() {
+            next[j] ^= coef;
+            next[j + 1] ^= gf.mul(coef, root);
+        }
+        generator = next;
+    }
+    generator
+}
+
+// Parity bytes for a systematic Reed-Solomon codeword (the QR encoder reuses this)
+pub(crate) fn rs_remainder(gf: &Gf256, data: &[u8], ecc_len: usize) -> Vec<u8> {
+    let generator = rs_generator(gf, ecc_len);
+    let mut remainder = vec![0u8; ecc_len];
+
+    for &byte in data {
+        let factor = byte ^ remainder[0];
+        remainder.remove(0);
+        remainder.push(0);
+        for (r, &g) in remainder.iter_mut().zip(&generator[1..]) {
+            *r ^= gf.mul(g, factor);
+        }
+    }
+
+    remainder
+}
+
+// Hamming(7,4) and SECDED Hamming(8,4)
+// Each nibble becomes one codeword byte: bits 1..7 hold Hamming positions p1 p2 d1 p3 d2 d3 d4,
+// bit 0 holds the overall parity bit in SECDED mode.
+const HAMMING_DATA_POSITIONS: [u8; 4] = [3, 5, 6, 7];
+
+fn hamming_encode_nibble(nibble: u8, secded: bool) -> u8 {
+    let mut word = 0u8;
+    for (i, &pos) in HAMMING_DATA_POSITIONS.iter().enumerate() {
+        if (nibble >> (3 - i)) & 1 != 0 {
+            word |= 1 << pos;
+        }
+    }
+
+    // Parity bit p covers every position whose index has bit p set
+    for parity in [1u8, 2, 4] {
+        let covered = (1..8u8).filter(|pos| pos & parity != 0 && (word >> pos) & 1 != 0).count();
+        if covered % 2 == 1 {
+            word |= 1 << parity;
+        }
+    }
+
+    if secded && word.count_ones() % 2 == 1 {
+        word |= 1;
+    }
+
+    word
+}
+
+fn hamming_syndrome(word: u8) -> u8 {
+    (1..8u8).filter(|pos| (word >> pos) & 1 != 0).fold(0, |s, pos| s ^ pos)
+}
+
+fn hamming_extract_nibble(word: u8) -> u8 {
+    HAMMING_DATA_POSITIONS
+        .iter()
+        .fold(0, |nibble, &pos| (nibble << 1) | ((word >> pos) & 1))
+}
+
+#[wasm_bindgen]
+pub fn hamming_encode(data: &[u8], secded: bool) -> Vec<u8> {
+    let mut codewords = Vec::with_capacity(data.len() * 2);
+    for &byte in data {
+        codewords.push(hamming_encode_nibble(byte >> 4, secded));
+        codewords.push(hamming_encode_nibble(byte & 0x0F, secded));
+    }
+    codewords
+}
+
+#[wasm_bindgen]
+pub struct HammingReport {
+    data: Vec<u8>,
+    syndromes: Vec<u8>,
+    error_locations: Vec<u32>,
+    uncorrectable: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl HammingReport {
+    #[wasm_bindgen]
+    pub fn get_data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    // One syndrome per codeword (0 = no error detected)
+    #[wasm_bindgen]
+    pub fn get_syndromes(&self) -> Vec<u8> {
+        self.syndromes.clone()
+    }
+
+    // Corrected errors as [codeword_index, bit_position] pairs
+    #[wasm_bindgen]
+    pub fn get_error_locations(&self) -> Vec<u32> {
+        self.error_locations.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_corrected_count(&self) -> usize {
+        self.error_locations.len() / 2
+    }
+
+    // Codewords where SECDED detected a double error it could not fix
+    #[wasm_bindgen]
+    pub fn get_uncorrectable(&self) -> Vec<u32> {
+        self.uncorrectable.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn hamming_decode(codewords: &[u8], secded: bool) -> HammingReport {
+    let mut data = Vec::with_capacity(codewords.len() / 2);
+    let mut syndromes = Vec::with_capacity(codewords.len());
+    let mut error_locations = Vec::new();
+    let mut uncorrectable = Vec::new();
+    let mut high_nibble = 0u8;
+
+    for (index, &received) in codewords.iter().enumerate() {
+        let mut word = received;
+        let syndrome = hamming_syndrome(word);
+        syndromes.push(syndrome);
+
+        if secded {
+            let parity_error = word.count_ones() % 2 == 1;
+            match (syndrome, parity_error) {
+                (0, false) => {}
+                // Single error; syndrome 0 means the overall parity bit itself flipped
+                (s, true) => {
+                    word ^= 1 << s;
+                    error_locations.extend_from_slice(&[index as u32, s as u32]);
+                }
+                (_, false) => uncorrectable.push(index as u32),
+            }
+        } else if syndrome != 0 {
+            word ^= 1 << syndrome;
+            error_locations.extend_from_slice(&[index as u32, syndrome as u32]);
+        }
+
+        let nibble = hamming_extract_nibble(word);
+        if index % 2 == 0 {
+            high_nibble = nibble;
+        } else {
+            data.push((high_nibble << 4) | nibble);
+        }
+    }
+
+    HammingReport {
+        data,
+        syndromes,
+        error_locations,
+        uncorrectable,
+    }
+}
+
+// Reed-Solomon over GF(256)
+#[wasm_bindgen]
+pub fn rs_encode(data: &[u8], ecc_len: usize) -> Result<Vec<u8>, JsValue> {
+    if ecc_len == 0 || data.len() + ecc_len > 255 {
+        return Err(JsValue::from_str("Reed-Solomon block must satisfy 0 < ecc_len and data + ecc_len <= 255"));
+    }
+
+    let gf = Gf256::new();
+    let mut codeword = data.to_vec();
+    codeword.extend(rs_remainder(&gf, data, ecc_len));
+    Ok(codeword)
+}
+
+#[wasm_bindgen]
+pub struct RsDecodeReport {
+    corrected: Vec<u8>,
+    syndromes: Vec<u8>,
+    error_positions: Vec<u32>,
+    error_magnitudes: Vec<u8>,
+    ecc_len: usize,
+    success: bool,
+}
+
+#[wasm_bindgen]
+impl RsDecodeReport {
+    // Corrected codeword including parity bytes
+    #[wasm_bindgen]
+    pub fn get_corrected(&self) -> Vec<u8> {
+        self.corrected.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_data(&self) -> Vec<u8> {
+        self.corrected[..self.corrected.len().saturating_sub(self.ecc_len)].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_syndromes(&self) -> Vec<u8> {
+        self.syndromes.clone()
+    }
+
+    // Byte indices into the codeword that were repaired
+    #[wasm_bindgen]
+    pub fn get_error_positions(&self) -> Vec<u32> {
+        self.error_positions.clone()
+    }
+
+    // XOR value applied at each error position
+    #[wasm_bindgen]
+    pub fn get_error_magnitudes(&self) -> Vec<u8> {
+        self.error_magnitudes.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_error_count(&self) -> usize {
+        self.error_positions.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_success(&self) -> bool {
+        self.success
+    }
+}
+
+// Berlekamp-Massey: error locator polynomial, lowest degree first
+fn berlekamp_massey(gf: &Gf256, syndromes: &[u8]) -> Vec<u8> {
+    let mut locator = vec![1u8];
+    let mut previous = vec![1u8];
+    let mut length = 0usize;
+    let mut shift = 1usize;
+    let mut previous_discrepancy = 1u8;
+
+    for n in 0..syndromes.len() {
+        let mut discrepancy = syndromes[n];
+        for i in 1..=length.min(locator.len() - 1) {
+            discrepancy ^= gf.mul(locator[i], syndromes[n - i]);
+        }
+
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = gf.div(discrepancy, previous_discrepancy);
+        let mut next = locator.clone();
+        if next.len() < previous.len() + shift {
+            next.resize(previous.len() + shift, 0);
+        }
+        for (i, &coef) in previous.iter().enumerate() {
+            next[i + shift] ^= gf.mul(scale, coef);
+        }
+
+        if 2 * length <= n {
+            previous = std::mem::replace(&mut locator, next);
+            length = n + 1 - length;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            locator = next;
+            shift += 1;
+        }
+    }
+
+    locator.truncate(length + 1);
+    locator
+}
+
+#[wasm_bindgen]
+pub fn rs_decode(codeword: &[u8], ecc_len: usize) -> RsDecodeReport {
+    let gf = Gf256::new();
+    let n = codeword.len();
+    let mut report = RsDecodeReport {
+        corrected: codeword.to_vec(),
+        syndromes: (0..ecc_len).map(|i| gf.eval_high_first(codeword, gf.pow_alpha(i))).collect(),
+        error_positions: Vec::new(),
+        error_magnitudes: Vec::new(),
+        ecc_len,
+        success: false,
+    };
+
+    if ecc_len == 0 || n > 255 || ecc_len > n {
+        return report;
+    }
+    if report.syndromes.iter().all(|&s| s == 0) {
+        report.success = true;
+        return report;
+    }
+
+    let locator = berlekamp_massey(&gf, &report.syndromes);
+    let error_count = locator.len() - 1;
+    if error_count * 2 > ecc_len {
+        return report;
+    }
+
+    // Chien search: byte j sits at power n-1-j, so it is in error when locator(a^-(n-1-j)) = 0
+    let mut positions = Vec::new();
+    for j in 0..n {
+        let x_inv = gf.inverse(gf.pow_alpha(n - 1 - j));
+        if gf.eval_low_first(&locator, x_inv) == 0 {
+            positions.push(j);
+        }
+    }
+    if positions.len() != error_count {
+        return report;
+    }
+
+    // Forney: evaluator = S(x) * locator(x) mod x^ecc_len, magnitude = X * evaluator(X^-1) / locator'(X^-1)
+    let mut evaluator = vec![0u8; ecc_len];
+    for (i, &s) in report.syndromes.iter().enumerate() {
+        for (j, &l) in locator.iter().enumerate() {
+            if i + j < ecc_len {
+                evaluator[i + j] ^= gf.mul(s, l);
+            }
+        }
+    }
+    // Formal derivative in characteristic 2 keeps only odd-degree terms
+    let derivative: Vec<u8> = (1..locator.len())
+        .map(|i| if i % 2 == 1 { locator[i] } else { 0 })
+        .collect();
+
+    for &j in &positions {
+        let x = gf.pow_alpha(n - 1 - j);
+        let x_inv = gf.inverse(x);
+        let denominator = gf.eval_low_first(&derivative, x_inv);
+        if denominator == 0 {
+            return report;
+        }
+        let magnitude = gf.mul(x, gf.div(gf.eval_low_first(&evaluator, x_inv), denominator));
+        report.corrected[j] ^= magnitude;
+        report.error_positions.push(j as u32);
+        report.error_magnitudes.push(magnitude);
+    }
+
+    report.success = (0..ecc_len).all(|i| gf.eval_high_first(&report.corrected, gf.pow_alpha(i)) == 0);
+    if !report.success {
+        report.corrected = codeword.to_vec();
+    }
+    report
+}
+
+// Block-wise Reed-Solomon for buffers longer than one codeword, e.g. an RGBA image
+#[wasm_bindgen]
+pub fn rs_encode_blocks(data: &[u8], ecc_len: usize) -> Result<Vec<u8>, JsValue> {
+    if ecc_len == 0 || ecc_len >= 255 {
+        return Err(JsValue::from_str("ecc_len must be between 1 and 254"));
+    }
+
+    let gf = Gf256::new();
+    let mut encoded = Vec::with_capacity(data.len() + data.len().div_ceil(255 - ecc_len) * ecc_len);
+    for block in data.chunks(255 - ecc_len) {
+        encoded.extend_from_slice(block);
+        encoded.extend(rs_remainder(&gf, block, ecc_len));
+    }
+    Ok(encoded)
+}
+
+#[wasm_bindgen]
+pub struct RsBlockReport {
+    data: Vec<u8>,
+    block_count: usize,
+    corrected_blocks: usize,
+    failed_blocks: Vec<u32>,
+    corrected_bytes: usize,
+}
+
+#[wasm_bindgen]
+impl RsBlockReport {
+    #[wasm_bindgen]
+    pub fn get_data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_block_count(&self) -> usize {
+        self.block_count
+    }
+
+    #[wasm_bindgen]
+    pub fn get_corrected_blocks(&self) -> usize {
+        self.corrected_blocks
+    }
+
+    #[wasm_bindgen]
+    pub fn get_failed_blocks(&self) -> Vec<u32> {
+        self.failed_blocks.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_corrected_bytes(&self) -> usize {
+        self.corrected_bytes
+    }
+}
+
+// Failed blocks are passed through uncorrected so the damage stays visible
+#[wasm_bindgen]
+pub fn rs_decode_blocks(encoded: &[u8], ecc_len: usize) -> RsBlockReport {
+    let mut report = RsBlockReport {
+        data: Vec::with_capacity(encoded.len()),
+        block_count: 0,
+        corrected_blocks: 0,
+        failed_blocks: Vec::new(),
+        corrected_bytes: 0,
+    };
+
+    if ecc_len == 0 || ecc_len >= 255 {
+        return report;
+    }
+
+    for (index, block) in encoded.chunks(255).enumerate() {
+        report.block_count += 1;
+        if block.len() <= ecc_len {
+            report.failed_blocks.push(index as u32);
+            continue;
+        }
+
+        let decoded = rs_decode(block, ecc_len);
+        if decoded.success {
+            if decoded.get_error_count() > 0 {
+                report.corrected_blocks += 1;
+                report.corrected_bytes += decoded.get_error_count();
+            }
+        } else {
+            report.failed_blocks.push(index as u32);
+        }
+        report.data.extend_from_slice(&decoded.corrected[..block.len() - ecc_len]);
+    }
+
+    report
+}
+
+// Noisy channel helpers
+#[wasm_bindgen]
+pub fn inject_bit_errors(data: &[u8], bit_error_rate: f64, seed: u32) -> Vec<u8> {
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let rate = bit_error_rate.clamp(0.0, 1.0);
+
+    data.iter()
+        .map(|&byte| {
+            let mut flipped = byte;
+            for bit in 0..8 {
+                if rng.gen::<f64>() < rate {
+                    flipped ^= 1 << bit;
+                }
+            }
+            flipped
+        })
+        .collect()
+}
+
+// Replace `count` distinct bytes with a different random value
+#[wasm_bindgen]
+pub fn inject_byte_errors(data: &[u8], count: usize, seed: u32) -> Vec<u8> {
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let mut corrupted = data.to_vec();
+    let mut positions: Vec<usize> = (0..data.len()).collect();
+
+    for i in 0..count.min(data.len()) {
+        let pick = rng.gen_range(i..positions.len());
+        positions.swap(i, pick);
+        let pos = positions[i];
+        corrupted[pos] ^= rng.gen_range(1..=255u8);
+    }
+
+    corrupted
+}
+
+#[wasm_bindgen]
+pub fn inject_burst_error(data: &[u8], start: usize, length: usize) -> Vec<u8> {
+    let mut corrupted = data.to_vec();
+    let end = start.saturating_add(length).min(corrupted.len());
+    for byte in corrupted.iter_mut().take(end).skip(start) {
+        *byte = !*byte;
+    }
+    corrupted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, seq::index::sample, Rng, SeedableRng};
+
+    #[test]
+    fn rs_corrects_up_to_half_the_parity() {
+        let gf = Gf256::new();
+        let mut rng = SmallRng::seed_from_u64(27);
+        for trial in 0..2000 {
+            let ecc_len = rng.gen_range(2..=64);
+            let data: Vec<u8> = (0..rng.gen_range(1..=255 - ecc_len)).map(|_| rng.gen()).collect();
+            let mut codeword = data.clone();
+            codeword.extend(rs_remainder(&gf, &data, ecc_len));
+
+            let mut received = codeword.clone();
+            let error_count = rng.gen_range(0..=ecc_len / 2);
+            let mut positions = sample(&mut rng, codeword.len(), error_count).into_vec();
+            positions.sort_unstable();
+            for &j in &positions {
+                received[j] ^= rng.gen_range(1..=255);
+            }
+
+            let report = rs_decode(&received, ecc_len);
+            assert!(report.is_success(), "trial {trial}: {} errors, ecc {ecc_len}", positions.len());
+            assert_eq!(report.get_corrected(), codeword, "trial {trial}");
+            assert_eq!(report.get_data(), data);
+            let mut found = report.get_error_positions();
+            found.sort_unstable();
+            assert_eq!(found, positions.iter().map(|&j| j as u32).collect::<Vec<_>>(), "trial {trial}");
+        }
+    }
+
+    #[test]
+    fn secded_corrects_one_flip_and_flags_two() {
+        let encoded = hamming_encode(&[0x5A, 0xC3], true);
+
+        for bit in 0..8 {
+            let mut received = encoded.clone();
+            received[1] ^= 1 << bit;
+            let report = hamming_decode(&received, true);
+            assert_eq!(report.get_data(), vec![0x5A, 0xC3], "bit {bit}");
+            assert_eq!(report.get_error_locations(), vec![1, bit]);
+            assert!(report.get_uncorrectable().is_empty());
+        }
+
+        let mut received = encoded.clone();
+        received[2] ^= 0b0010_0100;
+        let report = hamming_decode(&received, true);
+        assert_eq!(report.get_uncorrectable(), vec![2]);
+        assert_eq!(report.get_corrected_count(), 0);
+    }
+}
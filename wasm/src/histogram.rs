@@ -0,0 +1,314 @@
+use wasm_bindgen::prelude::*;
+
+const CHANNEL_NAMES: [&str; 5] = ["red", "green", "blue", "alpha", "luminance"];
+
+// Rec. 601 luma, the same weights as the grayscale color filter
+pub(crate) fn luma(p: &[u8]) -> u8 {
+    ((299 * p[0] as u32 + 587 * p[1] as u32 + 114 * p[2] as u32 + 500) / 1000) as u8
+}
+
+// Move a pixel to a new luma by shifting R, G and B equally. Cb and Cr are differences from
+// luma, so this keeps them (and the hue) unchanged apart from clipping.
+fn set_luma(p: &mut [u8], from: u8, to: f32) {
+    let delta = to - from as f32;
+    for c in p.iter_mut().take(3) {
+        *c = (*c as f32 + delta).round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+fn check_size(data: &[u8], width: u32, height: u32) -> bool {
+    data.len() == (width * height * 4) as usize
+}
+
+// Smallest value whose cumulative count reaches fraction of the total
+fn percentile_of(bins: &[u32; 256], fraction: f64) -> u8 {
+    let total: u64 = bins.iter().map(|&n| n as u64).sum();
+    if total == 0 {
+        return 0;
+    }
+    let target = ((fraction.clamp(0.0, 1.0) * total as f64).ceil() as u64).max(1);
+    let mut seen = 0u64;
+    for (value, &count) in bins.iter().enumerate() {
+        seen += count as u64;
+        if seen >= target {
+            return value as u8;
+        }
+    }
+    255
+}
+
+// Per-channel and luminance histograms of an RGBA image. Channels are indexed
+// 0 = red, 1 = green, 2 = blue, 3 = alpha, 4 = luminance.
+#[wasm_bindgen]
+pub struct Histogram {
+    bins: [[u32; 256]; 5],
+    pixel_count: u32,
+}
+
+impl Histogram {
+    pub(crate) fn compute(data: &[u8]) -> Histogram {
+        let mut bins = [[0u32; 256]; 5];
+        for p in data.chunks_exact(4) {
+            for c in 0..4 {
+                bins[c][p[c] as usize] += 1;
+            }
+            bins[4][luma(p) as usize] += 1;
+        }
+        Histogram { bins, pixel_count: (data.len() / 4) as u32 }
+    }
+
+    fn channel(&self, channel: u32) -> Option<&[u32; 256]> {
+        self.bins.get(channel as usize)
+    }
+}
+
+#[wasm_bindgen]
+impl Histogram {
+    #[wasm_bindgen]
+    pub fn get_red(&self) -> Vec<u32> {
+        self.bins[0].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_green(&self) -> Vec<u32> {
+        self.bins[1].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_blue(&self) -> Vec<u32> {
+        self.bins[2].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_alpha(&self) -> Vec<u32> {
+        self.bins[3].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_luminance(&self) -> Vec<u32> {
+        self.bins[4].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_pixel_count(&self) -> u32 {
+        self.pixel_count
+    }
+
+    #[wasm_bindgen]
+    pub fn get_channel_name(&self, channel: u32) -> String {
+        CHANNEL_NAMES.get(channel as usize).unwrap_or(&"").to_string()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_mean(&self, channel: u32) -> f64 {
+        match self.channel(channel) {
+            Some(bins) if self.pixel_count > 0 => {
+                let sum: f64 = bins.iter().enumerate().map(|(v, &n)| v as f64 * n as f64).sum();
+                sum / self.pixel_count as f64
+            }
+            _ => 0.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_std_dev(&self, channel: u32) -> f64 {
+        match self.channel(channel) {
+            Some(bins) if self.pixel_count > 0 => {
+                let mean = self.get_mean(channel);
+                let variance: f64 = bins
+                    .iter()
+                    .enumerate()
+                    .map(|(v, &n)| (v as f64 - mean).powi(2) * n as f64)
+                    .sum::<f64>()
+                    / self.pixel_count as f64;
+                variance.sqrt()
+            }
+            _ => 0.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_median(&self, channel: u32) -> u8 {
+        self.get_percentile(channel, 50.0)
+    }
+
+    // Value below which percent% of the pixels fall
+    #[wasm_bindgen]
+    pub fn get_percentile(&self, channel: u32, percent: f64) -> u8 {
+        self.channel(channel).map_or(0, |bins| percentile_of(bins, percent / 100.0))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_min(&self, channel: u32) -> u8 {
+        self.channel(channel)
+            .and_then(|bins| bins.iter().position(|&n| n > 0))
+            .unwrap_or(0) as u8
+    }
+
+    #[wasm_bindgen]
+    pub fn get_max(&self, channel: u32) -> u8 {
+        self.channel(channel)
+            .and_then(|bins| bins.iter().rposition(|&n| n > 0))
+            .unwrap_or(0) as u8
+    }
+}
+
+#[wasm_bindgen]
+pub fn histogram(data: &[u8], width: u32, height: u32) -> Result<Histogram, JsValue> {
+    if !check_size(data, width, height) {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    Ok(Histogram::compute(data))
+}
+
+// Luma histogram of the visible pixels; fully transparent pixels often hold arbitrary RGB and
+// would otherwise skew the tone mapping
+pub(crate) fn visible_luma_bins(data: &[u8]) -> [u32; 256] {
+    let mut bins = [0u32; 256];
+    for p in data.chunks_exact(4).filter(|p| p[3] > 0) {
+        bins[luma(p) as usize] += 1;
+    }
+    bins
+}
+
+// Global histogram equalization of luminance; colors keep their chroma
+#[wasm_bindgen]
+pub fn equalize_histogram(data: &mut [u8], width: u32, height: u32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+
+    let bins = visible_luma_bins(data);
+    let total: u64 = bins.iter().map(|&n| n as u64).sum();
+    let cdf_min = bins.iter().find(|&&n| n > 0).copied().unwrap_or(0) as u64;
+    if total <= cdf_min {
+        // Empty or a single tone: nothing to spread
+        return;
+    }
+
+    let mut map = [0f32; 256];
+    let mut cdf = 0u64;
+    for (value, &count) in bins.iter().enumerate() {
+        cdf += count as u64;
+        map[value] = (cdf.saturating_sub(cdf_min) as f32 * 255.0 / (total - cdf_min) as f32).round();
+    }
+
+    for p in data.chunks_exact_mut(4) {
+        let y = luma(p);
+        set_luma(p, y, map[y as usize]);
+    }
+}
+
+// Clip a tile histogram at limit and hand the excess back evenly, then turn it into a tone curve
+fn clipped_tile_map(bins: &mut [u32; 256], limit: u32, pixels: u32) -> [f32; 256] {
+    let mut excess = 0u32;
+    for n in bins.iter_mut() {
+        if *n > limit {
+            excess += *n - limit;
+            *n = limit;
+        }
+    }
+    let share = excess / 256;
+    let remainder = (excess % 256) as usize;
+    for (i, n) in bins.iter_mut().enumerate() {
+        *n += share;
+        // Spread the leftover across the whole range rather than piling it at the dark end
+        if remainder > 0 && (i * remainder) % 256 < remainder {
+            *n += 1;
+        }
+    }
+
+    let mut map = [0f32; 256];
+    let mut cdf = 0u32;
+    for (value, &count) in bins.iter().enumerate() {
+        cdf += count;
+        map[value] = cdf as f32 * 255.0 / pixels.max(1) as f32;
+    }
+    map
+}
+
+// Contrast Limited Adaptive Histogram Equalization on luminance. Each tile_size x tile_size
+// tile gets its own equalization curve with histogram bins capped at clip_limit times the
+// average bin height (<= 0 disables clipping, giving plain adaptive equalization); pixels
+// blend the curves of the four nearest tile centers so tile edges don't show.
+#[wasm_bindgen]
+pub fn apply_clahe(data: &mut [u8], width: u32, height: u32, tile_size: u32, clip_limit: f32) {
+    if !check_size(data, width, height) || tile_size == 0 || width == 0 || height == 0 {
+        return;
+    }
+
+    let (w, h, ts) = (width as usize, height as usize, tile_size as usize);
+    let tiles_x = w.div_ceil(ts);
+    let tiles_y = h.div_ceil(ts);
+    let lumas: Vec<u8> = data.chunks_exact(4).map(luma).collect();
+
+    let mut maps = Vec::with_capacity(tiles_x * tiles_y);
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let (x0, x1) = (tx * ts, ((tx + 1) * ts).min(w));
+            let (y0, y1) = (ty * ts, ((ty + 1) * ts).min(h));
+            let mut bins = [0u32; 256];
+            for y in y0..y1 {
+                for &l in &lumas[y * w + x0..y * w + x1] {
+                    bins[l as usize] += 1;
+                }
+            }
+            let pixels = ((x1 - x0) * (y1 - y0)) as u32;
+            let limit = if clip_limit > 0.0 {
+                ((clip_limit * pixels as f32 / 256.0).ceil() as u32).max(1)
+            } else {
+                u32::MAX
+            };
+            maps.push(clipped_tile_map(&mut bins, limit, pixels));
+        }
+    }
+
+    // Position of a pixel relative to tile centers: the two neighbouring tiles and the blend
+    let neighbours = |pos: usize, tiles: usize| -> (usize, usize, f32) {
+        let f = ((pos as f32 + 0.5) / ts as f32 - 0.5).clamp(0.0, (tiles - 1) as f32);
+        let lo = f.floor() as usize;
+        (lo, (lo + 1).min(tiles - 1), f - lo as f32)
+    };
+
+    for y in 0..h {
+        let (ty0, ty1, fy) = neighbours(y, tiles_y);
+        for x in 0..w {
+            let (tx0, tx1, fx) = neighbours(x, tiles_x);
+            let l = lumas[y * w + x];
+            let at = |tx: usize, ty: usize| maps[ty * tiles_x + tx][l as usize];
+            let top = at(tx0, ty0) * (1.0 - fx) + at(tx1, ty0) * fx;
+            let bottom = at(tx0, ty1) * (1.0 - fx) + at(tx1, ty1) * fx;
+            set_luma(&mut data[(y * w + x) * 4..][..4], l, top * (1.0 - fy) + bottom * fy);
+        }
+    }
+}
+
+// Stretch each of R, G and B to the full 0-255 range, ignoring clip_percent of the darkest and
+// brightest pixels so a few outliers don't pin the endpoints
+#[wasm_bindgen]
+pub fn auto_levels(data: &mut [u8], width: u32, height: u32, clip_percent: f32) {
+    if !check_size(data, width, height) {
+        return;
+    }
+
+    let clip = (clip_percent as f64 / 100.0).clamp(0.0, 0.5);
+    for c in 0..3 {
+        let mut bins = [0u32; 256];
+        for p in data.chunks_exact(4).filter(|p| p[3] > 0) {
+            bins[p[c] as usize] += 1;
+        }
+        let low = percentile_of(&bins, clip) as f32;
+        let high = percentile_of(&bins, 1.0 - clip) as f32;
+        if high <= low {
+            continue;
+        }
+
+        let scale = 255.0 / (high - low);
+        let map: Vec<u8> = (0..256)
+            .map(|v| ((v as f32 - low) * scale).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        for p in data.chunks_exact_mut(4) {
+            p[c] = map[p[c] as usize];
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+// OpenSSH randomart field and symbol ramp
+const BISHOP_WIDTH: usize = 17;
+const BISHOP_HEIGHT: usize = 9;
+const BISHOP_SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^";
+
+fn sha256(input: &str) -> [u8; 32] {
+    Sha256::digest(input.as_bytes()).into()
+}
+
+#[wasm_bindgen]
+pub fn sha256_hex(input: &str) -> String {
+    sha256(input).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// h in degrees, s and l in 0..1
+pub(crate) fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [u8; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let hp = (h.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (hp % 2.0 - 1.0).abs());
+    let (r, g, b) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    [
+        ((r + m) * 255.0).round() as u8,
+        ((g + m) * 255.0).round() as u8,
+        ((b + m) * 255.0).round() as u8,
+    ]
+}
+
+// Foreground colour from the digest tail: any hue, saturation and lightness kept in a readable band
+fn digest_color(digest: &[u8; 32]) -> [u8; 3] {
+    let hue = (((digest[29] as u32) << 4) | (digest[30] as u32 >> 4)) as f32 / 4095.0 * 360.0;
+    let saturation = 0.45 + (digest[31] & 0x0F) as f32 / 15.0 * 0.20;
+    let lightness = 0.50 + (digest[31] >> 4) as f32 / 15.0 * 0.15;
+    hsl_to_rgb(hue, saturation, lightness)
+}
+
+fn fill_pixel(data: &mut [u8], idx: usize, color: [u8; 3]) {
+    data[idx] = color[0];
+    data[idx + 1] = color[1];
+    data[idx + 2] = color[2];
+    data[idx + 3] = 255;
+}
+
+// GitHub-style identicon: 5x5 cells, left three columns from digest bits, mirrored to the right
+#[wasm_bindgen]
+pub fn identicon_grid(input: &str) -> Vec<u8> {
+    let digest = sha256(input);
+    let mut grid = vec![0u8; 25];
+
+    for col in 0..3 {
+        for row in 0..5 {
+            let bit = col * 5 + row;
+            let on = (digest[bit / 8] >> (7 - bit % 8)) & 1;
+            grid[row * 5 + col] = on;
+            grid[row * 5 + (4 - col)] = on;
+        }
+    }
+
+    grid
+}
+
+// Render the identicon into a size x size RGBA buffer with a half-cell margin
+#[wasm_bindgen]
+pub fn identicon_rgba(input: &str, size: u32) -> Vec<u8> {
+    let digest = sha256(input);
+    let grid = identicon_grid(input);
+    let foreground = digest_color(&digest);
+    let background = [240, 240, 240];
+    let size = size.max(1) as usize;
+    let mut data = vec![0u8; size * size * 4];
+
+    // Six cell-widths across: five cells plus two half-cell margins
+    let cell = size as f32 / 6.0;
+    for y in 0..size {
+        for x in 0..size {
+            let cx = ((x as f32 + 0.5) / cell - 0.5).floor();
+            let cy = ((y as f32 + 0.5) / cell - 0.5).floor();
+            let inside = (0.0..5.0).contains(&cx) && (0.0..5.0).contains(&cy);
+            let on = inside && grid[cy as usize * 5 + cx as usize] == 1;
+            fill_pixel(&mut data, (y * size + x) * 4, if on { foreground } else { background });
+        }
+    }
+
+    data
+}
+
+// Drunken bishop walk over the 17x9 field; returns visit counts plus start and end cells
+fn drunken_bishop_walk(digest: &[u8]) -> (Vec<u8>, usize, usize) {
+    let mut field = vec![0u8; BISHOP_WIDTH * BISHOP_HEIGHT];
+    let mut x = BISHOP_WIDTH / 2;
+    let mut y = BISHOP_HEIGHT / 2;
+    let start = y * BISHOP_WIDTH + x;
+
+    // Each byte gives four moves, two bits at a time from the least significant end
+    for &byte in digest {
+        let mut input = byte;
+        for _ in 0..4 {
+            x = if input & 0x1 != 0 { (x + 1).min(BISHOP_WIDTH - 1) } else { x.saturating_sub(1) };
+            y = if input & 0x2 != 0 { (y + 1).min(BISHOP_HEIGHT - 1) } else { y.saturating_sub(1) };
+            let cell = &mut field[y * BISHOP_WIDTH + x];
+            *cell = cell.saturating_add(1);
+            input >>= 2;
+        }
+    }
+
+    (field, start, y * BISHOP_WIDTH + x)
+}
+
+// Raw 17x9 visit counts for charting
+#[wasm_bindgen]
+pub fn drunken_bishop_counts(input: &str) -> Vec<u8> {
+    drunken_bishop_walk(&sha256(input)).0
+}
+
+// OpenSSH-style randomart text, framed as printed by `ssh-keygen -lv` with the digest name in
+// the bottom border
+#[wasm_bindgen]
+pub fn drunken_bishop_art(input: &str) -> String {
+    let (field, start, end) = drunken_bishop_walk(&sha256(input));
+    let mut art = String::from("+-----------------+\n");
+
+    for row in 0..BISHOP_HEIGHT {
+        art.push('|');
+        for col in 0..BISHOP_WIDTH {
+            let idx = row * BISHOP_WIDTH + col;
+            let symbol = if idx == start {
+                'S'
+            } else if idx == end {
+                'E'
+            } else {
+                BISHOP_SYMBOLS[(field[idx] as usize).min(BISHOP_SYMBOLS.len() - 1)] as char
+            };
+            art.push(symbol);
+        }
+        art.push_str("|\n");
+    }
+
+    art.push_str("+----[SHA256]-----+");
+    art
+}
+
+// Render the randart as a width x height RGBA heatmap: more visits, darker cell of the digest hue
+#[wasm_bindgen]
+pub fn drunken_bishop_rgba(input: &str, width: u32, height: u32) -> Vec<u8> {
+    let digest = sha256(input);
+    let (field, start, end) = drunken_bishop_walk(&digest);
+    let hue = (((digest[0] as u32) << 8) | digest[1] as u32) as f32 / 65535.0 * 360.0;
+    let max_visits = field.iter().copied().max().unwrap_or(1).max(1) as f32;
+
+    let palette: Vec<[u8; 3]> = field
+        .iter()
+        .enumerate()
+        .map(|(idx, &visits)| {
+            if idx == start {
+                hsl_to_rgb(hue + 120.0, 0.8, 0.45)
+            } else if idx == end {
+                hsl_to_rgb(hue + 240.0, 0.8, 0.45)
+            } else if visits == 0 {
+                [248, 248, 248]
+            } else {
+                let t = visits as f32 / max_visits;
+                hsl_to_rgb(hue, 0.55 + 0.25 * t, 0.85 - 0.55 * t)
+            }
+        })
+        .collect();
+
+    let width = width.max(1) as usize;
+    let height = height.max(1) as usize;
+    let mut data = vec![0u8; width * height * 4];
+    for y in 0..height {
+        let row = (y * BISHOP_HEIGHT / height).min(BISHOP_HEIGHT - 1);
+        for x in 0..width {
+            let col = (x * BISHOP_WIDTH / width).min(BISHOP_WIDTH - 1);
+            fill_pixel(&mut data, (y * width + x) * 4, palette[row * BISHOP_WIDTH + col]);
+        }
+    }
+
+    data
+}
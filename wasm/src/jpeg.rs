@@ -0,0 +1,1285 @@
+use crate::{flip_horizontal, rotate_180, rotate_270, rotate_90, alloc_rgba, try_zeroed, MAX_IMAGE_PIXELS};
+use wasm_bindgen::prelude::*;
+
+// Natural (row-major) index of the k-th coefficient in zigzag order
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28,
+    35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55,
+    62, 63,
+];
+
+// Codes up to this length are resolved with a single table lookup
+const FAST_BITS: usize = 9;
+
+// Image metadata from the frame header and the EXIF block (APP1)
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct JpegMetadata {
+    width: u32,
+    height: u32,
+    components: u8,
+    progressive: bool,
+    subsampling: String,
+    orientation: u8,
+    make: String,
+    model: String,
+    lens_model: String,
+    date_time: String,
+    exposure_time: f64,
+    f_number: f64,
+    iso: u32,
+    focal_length: f64,
+}
+
+impl Default for JpegMetadata {
+    fn default() -> Self {
+        JpegMetadata {
+            width: 0,
+            height: 0,
+            components: 0,
+            progressive: false,
+            subsampling: String::new(),
+            orientation: 1,
+            make: String::new(),
+            model: String::new(),
+            lens_model: String::new(),
+            date_time: String::new(),
+            exposure_time: 0.0,
+            f_number: 0.0,
+            iso: 0,
+            focal_length: 0.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl JpegMetadata {
+    // Dimensions as stored in the file, before orientation is applied
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // Dimensions after applying the EXIF orientation (orientations 5-8 swap the axes)
+    #[wasm_bindgen]
+    pub fn get_display_width(&self) -> u32 {
+        if self.orientation >= 5 { self.height } else { self.width }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_display_height(&self) -> u32 {
+        if self.orientation >= 5 { self.width } else { self.height }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_components(&self) -> u8 {
+        self.components
+    }
+
+    #[wasm_bindgen]
+    pub fn is_progressive(&self) -> bool {
+        self.progressive
+    }
+
+    // "4:4:4", "4:2:2", "4:2:0", "4:4:0", "4:1:1", "grayscale" or "custom"
+    #[wasm_bindgen]
+    pub fn get_subsampling(&self) -> String {
+        self.subsampling.clone()
+    }
+
+    // EXIF orientation 1-8 (1 when the tag is missing)
+    #[wasm_bindgen]
+    pub fn get_orientation(&self) -> u8 {
+        self.orientation
+    }
+
+    #[wasm_bindgen]
+    pub fn get_make(&self) -> String {
+        self.make.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_model(&self) -> String {
+        self.model.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_lens_model(&self) -> String {
+        self.lens_model.clone()
+    }
+
+    // DateTimeOriginal, falling back to DateTime ("YYYY:MM:DD HH:MM:SS")
+    #[wasm_bindgen]
+    pub fn get_date_time(&self) -> String {
+        self.date_time.clone()
+    }
+
+    // Seconds; 0 when unknown
+    #[wasm_bindgen]
+    pub fn get_exposure_time(&self) -> f64 {
+        self.exposure_time
+    }
+
+    #[wasm_bindgen]
+    pub fn get_f_number(&self) -> f64 {
+        self.f_number
+    }
+
+    #[wasm_bindgen]
+    pub fn get_iso(&self) -> u32 {
+        self.iso
+    }
+
+    // Millimetres
+    #[wasm_bindgen]
+    pub fn get_focal_length(&self) -> f64 {
+        self.focal_length
+    }
+}
+
+// Decoded image in the same RGBA layout the filter functions take
+#[wasm_bindgen]
+pub struct JpegImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    metadata: JpegMetadata,
+}
+
+#[wasm_bindgen]
+impl JpegImage {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_metadata(&self) -> JpegMetadata {
+        self.metadata.clone()
+    }
+}
+
+impl JpegImage {
+    // Native entry point (tests, tools); decode_jpeg wraps this for JS
+    pub fn decode(data: &[u8], auto_orient: bool) -> Result<JpegImage, String> {
+        let mut decoder = Decoder::new(data);
+        decoder.run(false)?;
+        let (width, height, pixels) = decoder.render()?;
+        let metadata = decoder.metadata;
+
+        if auto_orient && metadata.orientation != 1 {
+            let (width, height, pixels) = apply_orientation(&pixels, width, height, metadata.orientation);
+            return Ok(JpegImage { width, height, pixels, metadata });
+        }
+        Ok(JpegImage { width, height, pixels, metadata })
+    }
+}
+
+struct HuffmanTable {
+    // (code length, symbol) for every FAST_BITS-bit prefix; length 0 means "longer code"
+    lookup: Vec<(u8, u8)>,
+    max_code: [i32; 17],
+    min_code: [i32; 17],
+    value_offset: [usize; 17],
+    values: Vec<u8>,
+}
+
+impl HuffmanTable {
+    // Codes are assigned in order of the symbol list, shortest first (JPEG Annex C)
+    fn new(counts: &[u8], values: Vec<u8>) -> Result<HuffmanTable, String> {
+        let mut lookup = vec![(0u8, 0u8); 1 << FAST_BITS];
+        let mut max_code = [-1i32; 17];
+        let mut min_code = [0i32; 17];
+        let mut value_offset = [0usize; 17];
+        let mut code = 0usize;
+        let mut k = 0usize;
+
+        for len in 1..=16 {
+            value_offset[len] = k;
+            min_code[len] = code as i32;
+            for _ in 0..counts[len - 1] {
+                if code >= 1 << len {
+                    return Err("Invalid Huffman table".to_string());
+                }
+                if len <= FAST_BITS {
+                    let shift = FAST_BITS - len;
+                    for fill in 0..1 << shift {
+                        lookup[(code << shift) | fill] = (len as u8, values[k]);
+                    }
+                }
+                code += 1;
+                k += 1;
+            }
+            if counts[len - 1] > 0 {
+                max_code[len] = code as i32 - 1;
+            }
+            code <<= 1;
+        }
+
+        Ok(HuffmanTable {
+            lookup,
+            max_code,
+            min_code,
+            value_offset,
+            values,
+        })
+    }
+}
+
+// MSB-first reader over entropy-coded data: removes 0xFF00 stuffing and stops at markers,
+// feeding zeros afterwards so a truncated scan degrades instead of failing
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u64,
+    count: u32,
+    marker: Option<u8>,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        BitReader {
+            data,
+            pos,
+            bits: 0,
+            count: 0,
+            marker: None,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.count <= 56 {
+            let mut byte = 0;
+            if self.marker.is_none() && self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                self.pos += 1;
+                if byte == 0xFF {
+                    let mut next = self.data.get(self.pos).copied().unwrap_or(0xD9);
+                    while next == 0xFF {
+                        self.pos += 1;
+                        next = self.data.get(self.pos).copied().unwrap_or(0xD9);
+                    }
+                    self.pos += 1;
+                    if next != 0x00 {
+                        self.marker = Some(next);
+                        byte = 0;
+                    }
+                }
+            }
+            self.bits |= (byte as u64) << (56 - self.count);
+            self.count += 8;
+        }
+    }
+
+    fn receive(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        self.fill();
+        let value = (self.bits >> (64 - n)) as u32;
+        self.bits <<= n;
+        self.count -= n;
+        value
+    }
+
+    // Read an n-bit magnitude category value and sign-extend it (JPEG F.2.2.1)
+    fn receive_extend(&mut self, n: u32) -> i32 {
+        if n == 0 {
+            return 0;
+        }
+        let value = self.receive(n) as i32;
+        if value < 1 << (n - 1) {
+            value - (1 << n) + 1
+        } else {
+            value
+        }
+    }
+
+    fn decode(&mut self, table: &HuffmanTable) -> Result<u8, String> {
+        self.fill();
+        let (len, symbol) = table.lookup[(self.bits >> (64 - FAST_BITS)) as usize];
+        if len > 0 {
+            self.bits <<= len;
+            self.count -= len as u32;
+            return Ok(symbol);
+        }
+
+        for len in FAST_BITS + 1..=16 {
+            let code = (self.bits >> (64 - len)) as i32;
+            if code <= table.max_code[len] {
+                self.bits <<= len;
+                self.count -= len as u32;
+                let index = table.value_offset[len] + (code - table.min_code[len]) as usize;
+                return table.values.get(index).copied().ok_or("Corrupt Huffman code".to_string());
+            }
+        }
+        Err("Corrupt Huffman code".to_string())
+    }
+
+    // Discard buffered bits and consume the RSTn marker that must follow
+    fn restart(&mut self) -> Result<(), String> {
+        self.bits = 0;
+        self.count = 0;
+        if self.marker.is_none() {
+            while self.pos + 1 < self.data.len() {
+                if self.data[self.pos] == 0xFF && self.data[self.pos + 1] != 0x00 && self.data[self.pos + 1] != 0xFF {
+                    self.marker = Some(self.data[self.pos + 1]);
+                    self.pos += 2;
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+        match self.marker.take() {
+            Some(0xD0..=0xD7) => Ok(()),
+            _ => Err("Missing JPEG restart marker".to_string()),
+        }
+    }
+
+    // Offset of the marker that ends the scan (skipping any trailing RSTn)
+    fn end_of_scan(&self) -> usize {
+        let mut pos = match self.marker {
+            Some(0xD0..=0xD7) | None => self.pos,
+            Some(_) => return self.pos - 2,
+        };
+        while pos + 1 < self.data.len() {
+            let next = self.data[pos + 1];
+            if self.data[pos] == 0xFF && next != 0x00 && next != 0xFF && !(0xD0..=0xD7).contains(&next) {
+                return pos;
+            }
+            pos += 1;
+        }
+        self.data.len()
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant_table: usize,
+    blocks_w: usize,
+    blocks_h: usize,
+    // Quantized coefficients in natural order, 64 per block
+    coeffs: Vec<i16>,
+    dc_pred: i32,
+    dc_table: usize,
+    ac_table: usize,
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    progressive: bool,
+    h_max: usize,
+    v_max: usize,
+    mcus_x: usize,
+    mcus_y: usize,
+    components: Vec<Component>,
+}
+
+struct Scan {
+    components: Vec<usize>,
+    start: usize,
+    end: usize,
+    approx_high: u32,
+    approx_low: u32,
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    quant: [[u16; 64]; 4],
+    dc_tables: [Option<HuffmanTable>; 4],
+    ac_tables: [Option<HuffmanTable>; 4],
+    restart_interval: usize,
+    adobe_transform: Option<u8>,
+    frame: Option<Frame>,
+    metadata: JpegMetadata,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Decoder {
+            data,
+            quant: [[1; 64]; 4],
+            dc_tables: [None, None, None, None],
+            ac_tables: [None, None, None, None],
+            restart_interval: 0,
+            adobe_transform: None,
+            frame: None,
+            metadata: JpegMetadata::default(),
+        }
+    }
+
+    // Walk the marker segments; with headers_only, stop at the first scan
+    fn run(&mut self, headers_only: bool) -> Result<(), String> {
+        let data = self.data;
+        if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+            return Err("Not a JPEG file".to_string());
+        }
+
+        let mut pos = 2;
+        while pos < data.len() {
+            if data[pos] != 0xFF {
+                return Err("Expected JPEG marker".to_string());
+            }
+            while pos < data.len() && data[pos] == 0xFF {
+                pos += 1;
+            }
+            let Some(&marker) = data.get(pos) else { break };
+            pos += 1;
+
+            match marker {
+                0xD9 => break,
+                0xD0..=0xD8 | 0x01 => continue,
+                _ => {}
+            }
+
+            if pos + 2 > data.len() {
+                return Err("Truncated JPEG segment".to_string());
+            }
+            let length = u16::from_be_bytes([data[pos], data[pos + 1]]) as usize;
+            if length < 2 || pos + length > data.len() {
+                return Err("Truncated JPEG segment".to_string());
+            }
+            let segment = &data[pos + 2..pos + length];
+            pos += length;
+
+            match marker {
+                0xC0..=0xC2 => {
+                    self.read_frame(segment, marker == 0xC2)?;
+                    if headers_only {
+                        return Ok(());
+                    }
+                }
+                0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                    return Err("Unsupported JPEG process (lossless, hierarchical or arithmetic coding)".to_string());
+                }
+                0xC4 => self.read_huffman_tables(segment)?,
+                0xDB => self.read_quant_tables(segment)?,
+                0xDD => {
+                    if segment.len() < 2 {
+                        return Err("Invalid DRI segment".to_string());
+                    }
+                    self.restart_interval = u16::from_be_bytes([segment[0], segment[1]]) as usize;
+                }
+                0xE1 if segment.starts_with(b"Exif\0\0") => parse_exif(&segment[6..], &mut self.metadata),
+                0xEE if segment.starts_with(b"Adobe") && segment.len() >= 12 => {
+                    self.adobe_transform = Some(segment[11]);
+                }
+                0xDA => {
+                    self.allocate_coefficients()?;
+                    let scan = self.read_scan_header(segment)?;
+                    pos = self.decode_scan(&scan, pos)?;
+                }
+                _ => {}
+            }
+        }
+
+        if self.frame.is_none() {
+            return Err("JPEG has no frame header".to_string());
+        }
+        Ok(())
+    }
+
+    fn read_frame(&mut self, segment: &[u8], progressive: bool) -> Result<(), String> {
+        if self.frame.is_some() {
+            return Err("Multiple JPEG frames are not supported".to_string());
+        }
+        if segment.len() < 6 {
+            return Err("Invalid SOF segment".to_string());
+        }
+        if segment[0] != 8 {
+            return Err(format!("Unsupported JPEG sample precision {}", segment[0]));
+        }
+        let height = u16::from_be_bytes([segment[1], segment[2]]) as usize;
+        let width = u16::from_be_bytes([segment[3], segment[4]]) as usize;
+        let count = segment[5] as usize;
+        if width == 0 || height == 0 {
+            return Err("JPEG has zero width or height".to_string());
+        }
+        if !matches!(count, 1 | 3 | 4) || segment.len() < 6 + count * 3 {
+            return Err("Unsupported JPEG component count".to_string());
+        }
+
+        let mut components = Vec::with_capacity(count);
+        for c in segment[6..6 + count * 3].chunks_exact(3) {
+            let (h, v) = ((c[1] >> 4) as usize, (c[1] & 15) as usize);
+            if !(1..=4).contains(&h) || !(1..=4).contains(&v) || c[2] > 3 {
+                return Err("Invalid JPEG component parameters".to_string());
+            }
+            components.push(Component {
+                id: c[0],
+                h,
+                v,
+                quant_table: c[2] as usize,
+                blocks_w: 0,
+                blocks_h: 0,
+                coeffs: Vec::new(),
+                dc_pred: 0,
+                dc_table: 0,
+                ac_table: 0,
+            });
+        }
+
+        let h_max = components.iter().map(|c| c.h).max().unwrap_or(1);
+        let v_max = components.iter().map(|c| c.v).max().unwrap_or(1);
+        if components.iter().any(|c| h_max % c.h != 0 || v_max % c.v != 0) {
+            return Err("Unsupported JPEG sampling factors".to_string());
+        }
+        let mcus_x = width.div_ceil(8 * h_max);
+        let mcus_y = height.div_ceil(8 * v_max);
+        for c in components.iter_mut() {
+            c.blocks_w = mcus_x * c.h;
+            c.blocks_h = mcus_y * c.v;
+        }
+
+        self.metadata.width = width as u32;
+        self.metadata.height = height as u32;
+        self.metadata.components = count as u8;
+        self.metadata.progressive = progressive;
+        self.metadata.subsampling = match (count, h_max / components[count.min(2) - 1].h, v_max / components[count.min(2) - 1].v) {
+            (1, _, _) => "grayscale",
+            (_, 1, 1) => "4:4:4",
+            (_, 2, 1) => "4:2:2",
+            (_, 2, 2) => "4:2:0",
+            (_, 1, 2) => "4:4:0",
+            (_, 4, 1) => "4:1:1",
+            _ => "custom",
+        }
+        .to_string();
+
+        self.frame = Some(Frame {
+            width,
+            height,
+            progressive,
+            h_max,
+            v_max,
+            mcus_x,
+            mcus_y,
+            components,
+        });
+        Ok(())
+    }
+
+    // Coefficient buffers are only needed once image data arrives, so header-only reads never
+    // allocate them
+    fn allocate_coefficients(&mut self) -> Result<(), String> {
+        let frame = self.frame.as_mut().ok_or("JPEG scan before frame header")?;
+        if frame.width as u64 * frame.height as u64 > MAX_IMAGE_PIXELS {
+            return Err(format!("JPEG of {}x{} exceeds the {} pixel limit", frame.width, frame.height, MAX_IMAGE_PIXELS));
+        }
+        for c in frame.components.iter_mut().filter(|c| c.coeffs.is_empty()) {
+            let len = c.blocks_w
+                .checked_mul(c.blocks_h)
+                .and_then(|blocks| blocks.checked_mul(64))
+                .ok_or("JPEG dimensions are too large")?;
+            c.coeffs = try_zeroed(len)?;
+        }
+        Ok(())
+    }
+
+    fn read_huffman_tables(&mut self, mut segment: &[u8]) -> Result<(), String> {
+        while !segment.is_empty() {
+            if segment.len() < 17 {
+                return Err("Invalid DHT segment".to_string());
+            }
+            let (class, id) = (segment[0] >> 4, (segment[0] & 15) as usize);
+            let counts = &segment[1..17];
+            let total: usize = counts.iter().map(|&c| c as usize).sum();
+            if class > 1 || id > 3 || segment.len() < 17 + total {
+                return Err("Invalid DHT segment".to_string());
+            }
+            let table = HuffmanTable::new(counts, segment[17..17 + total].to_vec())?;
+            if class == 0 {
+                self.dc_tables[id] = Some(table);
+            } else {
+                self.ac_tables[id] = Some(table);
+            }
+            segment = &segment[17 + total..];
+        }
+        Ok(())
+    }
+
+    fn read_quant_tables(&mut self, mut segment: &[u8]) -> Result<(), String> {
+        while !segment.is_empty() {
+            let (precision, id) = (segment[0] >> 4, (segment[0] & 15) as usize);
+            let size = if precision == 0 { 64 } else { 128 };
+            if id > 3 || precision > 1 || segment.len() < 1 + size {
+                return Err("Invalid DQT segment".to_string());
+            }
+            for k in 0..64 {
+                self.quant[id][ZIGZAG[k]] = if precision == 0 {
+                    segment[1 + k] as u16
+                } else {
+                    u16::from_be_bytes([segment[1 + k * 2], segment[2 + k * 2]])
+                };
+            }
+            segment = &segment[1 + size..];
+        }
+        Ok(())
+    }
+
+    fn read_scan_header(&mut self, segment: &[u8]) -> Result<Scan, String> {
+        let frame = self.frame.as_mut().ok_or("JPEG scan before frame header")?;
+        let count = *segment.first().ok_or("Invalid SOS segment")? as usize;
+        if count == 0 || count > frame.components.len() || segment.len() < 4 + count * 2 {
+            return Err("Invalid SOS segment".to_string());
+        }
+
+        let mut components = Vec::with_capacity(count);
+        for c in segment[1..1 + count * 2].chunks_exact(2) {
+            let index = frame
+                .components
+                .iter()
+                .position(|comp| comp.id == c[0])
+                .ok_or("SOS references an unknown component")?;
+            frame.components[index].dc_table = (c[1] >> 4) as usize & 3;
+            frame.components[index].ac_table = (c[1] & 15) as usize & 3;
+            components.push(index);
+        }
+
+        let tail = &segment[1 + count * 2..];
+        let scan = if frame.progressive {
+            Scan {
+                components,
+                start: tail[0] as usize,
+                end: tail[1] as usize,
+                approx_high: (tail[2] >> 4) as u32,
+                approx_low: (tail[2] & 15) as u32,
+            }
+        } else {
+            Scan {
+                components,
+                start: 0,
+                end: 63,
+                approx_high: 0,
+                approx_low: 0,
+            }
+        };
+
+        if scan.start > scan.end || scan.end > 63 || (scan.start == 0 && scan.end != 0 && frame.progressive) {
+            return Err("Invalid JPEG spectral selection".to_string());
+        }
+        if scan.start > 0 && scan.components.len() != 1 {
+            return Err("Progressive AC scans must contain a single component".to_string());
+        }
+        let blocks_in_mcu: usize = scan.components.iter().map(|&i| frame.components[i].h * frame.components[i].v).sum();
+        if scan.components.len() > 1 && blocks_in_mcu > 10 {
+            return Err("Too many blocks in JPEG MCU".to_string());
+        }
+        for &i in &scan.components {
+            let comp = &frame.components[i];
+            if scan.start == 0 && scan.approx_high == 0 && self.dc_tables[comp.dc_table].is_none() {
+                return Err("Missing JPEG DC Huffman table".to_string());
+            }
+            if scan.end > 0 && self.ac_tables[comp.ac_table].is_none() {
+                return Err("Missing JPEG AC Huffman table".to_string());
+            }
+        }
+        Ok(scan)
+    }
+
+    // Decode one scan's entropy-coded data into the coefficient buffers; returns the offset of
+    // the marker that follows it
+    fn decode_scan(&mut self, scan: &Scan, pos: usize) -> Result<usize, String> {
+        let frame = self.frame.as_mut().ok_or("JPEG scan before frame header")?;
+        let mut reader = BitReader::new(self.data, pos);
+        let mut eob_run = 0u32;
+
+        // A single-component scan is not interleaved: its MCU is one block and it only covers
+        // the blocks that intersect the image, not the MCU padding
+        let (units_x, units_y) = if scan.components.len() == 1 {
+            let comp = &frame.components[scan.components[0]];
+            (
+                (frame.width * comp.h).div_ceil(frame.h_max).div_ceil(8),
+                (frame.height * comp.v).div_ceil(frame.v_max).div_ceil(8),
+            )
+        } else {
+            (frame.mcus_x, frame.mcus_y)
+        };
+
+        for &i in &scan.components {
+            frame.components[i].dc_pred = 0;
+        }
+
+        for unit in 0..units_x * units_y {
+            if self.restart_interval > 0 && unit > 0 && unit % self.restart_interval == 0 {
+                reader.restart()?;
+                eob_run = 0;
+                for &i in &scan.components {
+                    frame.components[i].dc_pred = 0;
+                }
+            }
+
+            let (ux, uy) = (unit % units_x, unit / units_x);
+            for &i in &scan.components {
+                let comp = &mut frame.components[i];
+                let (bw, bh) = if scan.components.len() == 1 { (1, 1) } else { (comp.h, comp.v) };
+                for by in 0..bh {
+                    for bx in 0..bw {
+                        let row = uy * bh + by;
+                        let col = ux * bw + bx;
+                        let offset = (row * comp.blocks_w + col) * 64;
+                        let block = &mut comp.coeffs[offset..offset + 64];
+                        let dc_table = self.dc_tables[comp.dc_table].as_ref();
+                        let ac_table = self.ac_tables[comp.ac_table].as_ref();
+
+                        if scan.start == 0 {
+                            if scan.approx_high == 0 {
+                                let table = dc_table.ok_or("Missing JPEG DC Huffman table")?;
+                                decode_dc_first(&mut reader, block, table, &mut comp.dc_pred, scan.approx_low)?;
+                            } else if reader.receive(1) == 1 {
+                                block[0] |= 1 << scan.approx_low;
+                            }
+                        }
+                        if scan.end > 0 {
+                            let table = ac_table.ok_or("Missing JPEG AC Huffman table")?;
+                            let start = scan.start.max(1);
+                            if scan.approx_high == 0 {
+                                decode_ac_first(&mut reader, block, table, start, scan.end, scan.approx_low, &mut eob_run)?;
+                            } else {
+                                decode_ac_refine(&mut reader, block, table, start, scan.end, scan.approx_low, &mut eob_run)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(reader.end_of_scan())
+    }
+
+    // Dequantize, inverse-transform, upsample and color-convert to RGBA
+    fn render(&self) -> Result<(u32, u32, Vec<u8>), String> {
+        let frame = self.frame.as_ref().ok_or("JPEG has no frame header")?;
+        if frame.components.iter().any(|c| c.coeffs.is_empty()) {
+            return Err("JPEG has no image data".to_string());
+        }
+        let basis = idct_basis();
+
+        let planes: Vec<Vec<u8>> = frame
+            .components
+            .iter()
+            .map(|comp| {
+                let stride = comp.blocks_w * 8;
+                let mut plane = vec![0u8; stride * comp.blocks_h * 8];
+                let quant = &self.quant[comp.quant_table];
+                let mut block = [0f32; 64];
+                let mut out = [0u8; 64];
+                for row in 0..comp.blocks_h {
+                    for col in 0..comp.blocks_w {
+                        let coeffs = &comp.coeffs[(row * comp.blocks_w + col) * 64..][..64];
+                        for k in 0..64 {
+                            block[k] = coeffs[k] as f32 * quant[k] as f32;
+                        }
+                        idct_block(&block, &basis, &mut out);
+                        for y in 0..8 {
+                            let dst = (row * 8 + y) * stride + col * 8;
+                            plane[dst..dst + 8].copy_from_slice(&out[y * 8..y * 8 + 8]);
+                        }
+                    }
+                }
+                plane
+            })
+            .collect();
+
+        let (width, height) = (frame.width, frame.height);
+        let count = frame.components.len();
+        let is_rgb = count == 3
+            && (self.adobe_transform == Some(0)
+                || frame.components.iter().map(|c| c.id).eq([b'R', b'G', b'B']));
+        let is_ycck = count == 4 && self.adobe_transform == Some(2);
+
+        let mut pixels = alloc_rgba(width as u32, height as u32)?;
+        let mut rows = vec![vec![0u8; width]; count];
+        let mut scratch = vec![0u16; width + 1];
+        for y in 0..height {
+            for (c, comp) in frame.components.iter().enumerate() {
+                let sx = frame.h_max / comp.h;
+                let sy = frame.v_max / comp.v;
+                let layout = PlaneLayout {
+                    stride: comp.blocks_w * 8,
+                    width: width.div_ceil(sx),
+                    height: height.div_ceil(sy),
+                };
+                upsample_row(&planes[c], &layout, sx, sy, y, &mut rows[c], &mut scratch);
+            }
+
+            let out = &mut pixels[y * width * 4..(y + 1) * width * 4];
+            for x in 0..width {
+                let rgb = match count {
+                    1 => [rows[0][x]; 3],
+                    3 if is_rgb => [rows[0][x], rows[1][x], rows[2][x]],
+                    3 => ycbcr_to_rgb(rows[0][x], rows[1][x], rows[2][x]),
+                    _ => {
+                        // Adobe stores CMYK inverted, so each channel is already 255 - ink
+                        let cmy = if is_ycck {
+                            ycbcr_to_rgb(rows[0][x], rows[1][x], rows[2][x])
+                        } else {
+                            [rows[0][x], rows[1][x], rows[2][x]]
+                        };
+                        let k = rows[3][x] as u32;
+                        cmy.map(|v| ((v as u32 * k + 127) / 255) as u8)
+                    }
+                };
+                out[x * 4..x * 4 + 4].copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+            }
+        }
+
+        Ok((width as u32, height as u32, pixels))
+    }
+}
+
+fn decode_dc_first(reader: &mut BitReader, block: &mut [i16], table: &HuffmanTable, pred: &mut i32, shift: u32) -> Result<(), String> {
+    let size = reader.decode(table)? as u32;
+    if size > 11 {
+        return Err("Corrupt JPEG DC coefficient".to_string());
+    }
+    *pred += reader.receive_extend(size);
+    block[0] = (*pred * (1 << shift)) as i16;
+    Ok(())
+}
+
+// First pass over a spectral band (also the whole of a baseline block, with shift 0)
+fn decode_ac_first(
+    reader: &mut BitReader,
+    block: &mut [i16],
+    table: &HuffmanTable,
+    start: usize,
+    end: usize,
+    shift: u32,
+    eob_run: &mut u32,
+) -> Result<(), String> {
+    if *eob_run > 0 {
+        *eob_run -= 1;
+        return Ok(());
+    }
+
+    let mut k = start;
+    while k <= end {
+        let rs = reader.decode(table)?;
+        let (run, size) = ((rs >> 4) as u32, (rs & 15) as u32);
+        if size == 0 {
+            if run < 15 {
+                // End of band, possibly for the next 2^run + extra blocks as well
+                *eob_run = (1 << run) - 1 + reader.receive(run);
+                break;
+            }
+            k += 16;
+            continue;
+        }
+        k += run as usize;
+        if k > 63 {
+            return Err("Corrupt JPEG AC coefficient".to_string());
+        }
+        block[ZIGZAG[k]] = (reader.receive_extend(size) * (1 << shift)) as i16;
+        k += 1;
+    }
+    Ok(())
+}
+
+// Successive-approximation refinement of a spectral band (libjpeg's decode_mcu_AC_refine)
+fn decode_ac_refine(
+    reader: &mut BitReader,
+    block: &mut [i16],
+    table: &HuffmanTable,
+    start: usize,
+    end: usize,
+    shift: u32,
+    eob_run: &mut u32,
+) -> Result<(), String> {
+    let bit = 1i16 << shift;
+    let refine = |reader: &mut BitReader, coeff: &mut i16| {
+        if reader.receive(1) == 1 && *coeff & bit == 0 {
+            *coeff += if *coeff >= 0 { bit } else { -bit };
+        }
+    };
+
+    let mut k = start;
+    if *eob_run == 0 {
+        while k <= end {
+            let rs = reader.decode(table)?;
+            let (mut run, size) = ((rs >> 4) as i32, rs & 15);
+            let mut value = 0;
+            if size != 0 {
+                if size != 1 {
+                    return Err("Corrupt JPEG refinement coefficient".to_string());
+                }
+                value = if reader.receive(1) == 1 { bit } else { -bit };
+            } else if run != 15 {
+                *eob_run = (1 << run) + reader.receive(run as u32);
+                break;
+            }
+
+            // Skip `run` zero-history coefficients, refining the non-zero ones passed on the way
+            while k <= end {
+                let coeff = &mut block[ZIGZAG[k]];
+                if *coeff != 0 {
+                    refine(reader, coeff);
+                } else {
+                    run -= 1;
+                    if run < 0 {
+                        break;
+                    }
+                }
+                k += 1;
+            }
+            if value != 0 && k <= end {
+                block[ZIGZAG[k]] = value;
+            }
+            k += 1;
+        }
+    }
+
+    if *eob_run > 0 {
+        while k <= end {
+            let coeff = &mut block[ZIGZAG[k]];
+            if *coeff != 0 {
+                refine(reader, coeff);
+            }
+            k += 1;
+        }
+        *eob_run -= 1;
+    }
+    Ok(())
+}
+
+// basis[x][u] = C(u)/2 * cos((2x + 1) u pi / 16), so two passes give the 1/4 C(u) C(v) scaling
+fn idct_basis() -> [[f32; 8]; 8] {
+    let mut basis = [[0f32; 8]; 8];
+    for (x, row) in basis.iter_mut().enumerate() {
+        for (u, value) in row.iter_mut().enumerate() {
+            let c = if u == 0 { std::f32::consts::FRAC_1_SQRT_2 } else { 1.0 };
+            *value = c / 2.0 * ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 16.0).cos();
+        }
+    }
+    basis
+}
+
+// Separable 8x8 inverse DCT with level shift; coefficient rows with no AC terms collapse to a constant
+fn idct_block(coeffs: &[f32; 64], basis: &[[f32; 8]; 8], out: &mut [u8; 64]) {
+    let mut tmp = [0f32; 64];
+    for v in 0..8 {
+        let row = &coeffs[v * 8..v * 8 + 8];
+        if row[1..].iter().all(|&c| c == 0.0) {
+            let dc = row[0] * basis[0][0];
+            tmp[v * 8..v * 8 + 8].fill(dc);
+            continue;
+        }
+        for x in 0..8 {
+            tmp[v * 8 + x] = (0..8).map(|u| basis[x][u] * row[u]).sum();
+        }
+    }
+    for y in 0..8 {
+        for x in 0..8 {
+            let value: f32 = (0..8).map(|v| basis[y][v] * tmp[v * 8 + x]).sum();
+            out[y * 8 + x] = (value + 128.5).clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// JFIF full-range YCbCr -> RGB in 16.16 fixed point
+fn ycbcr_to_rgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    let y = (y as i32) << 16;
+    let cb = cb as i32 - 128;
+    let cr = cr as i32 - 128;
+    let r = y + 91881 * cr;
+    let g = y - 22554 * cb - 46802 * cr;
+    let b = y + 116130 * cb;
+    [r, g, b].map(|v| ((v + 32768) >> 16).clamp(0, 255) as u8)
+}
+
+struct PlaneLayout {
+    stride: usize,
+    // Valid samples, excluding MCU padding
+    width: usize,
+    height: usize,
+}
+
+// Produce output row y of a component at full resolution. 2x horizontal, 2x vertical and 2x2 use
+// libjpeg's "fancy" triangle filter so chroma edges don't look blocky; other ratios replicate samples.
+fn upsample_row(plane: &[u8], layout: &PlaneLayout, sx: usize, sy: usize, y: usize, out: &mut [u8], scratch: &mut [u16]) {
+    let last = layout.width - 1;
+    match (sx, sy) {
+        (1, 1) => out.copy_from_slice(&plane[y * layout.stride..y * layout.stride + out.len()]),
+        (2, 1) => {
+            let row = &plane[y * layout.stride..];
+            for (x, value) in out.iter_mut().enumerate() {
+                let i = x / 2;
+                let center = row[i] as u16 * 3;
+                *value = if x.is_multiple_of(2) {
+                    ((center + row[i.saturating_sub(1)] as u16 + 1) >> 2) as u8
+                } else {
+                    ((center + row[(i + 1).min(last)] as u16 + 2) >> 2) as u8
+                };
+            }
+        }
+        (1, 2) => {
+            let near = y / 2;
+            let far = if y.is_multiple_of(2) { near.saturating_sub(1) } else { (near + 1).min(layout.height - 1) };
+            let near_row = &plane[near * layout.stride..];
+            let far_row = &plane[far * layout.stride..];
+            for (x, value) in out.iter_mut().enumerate() {
+                *value = ((near_row[x] as u16 * 3 + far_row[x] as u16 + 2) >> 2) as u8;
+            }
+        }
+        (2, 2) => {
+            let near = y / 2;
+            let far = if y.is_multiple_of(2) { near.saturating_sub(1) } else { (near + 1).min(layout.height - 1) };
+            let near_row = &plane[near * layout.stride..];
+            let far_row = &plane[far * layout.stride..];
+            for i in 0..layout.width {
+                scratch[i] = near_row[i] as u16 * 3 + far_row[i] as u16;
+            }
+            for (x, value) in out.iter_mut().enumerate() {
+                let i = x / 2;
+                let center = scratch[i] * 3;
+                *value = if x.is_multiple_of(2) {
+                    ((center + scratch[i.saturating_sub(1)] + 8) >> 4) as u8
+                } else {
+                    ((center + scratch[(i + 1).min(last)] + 7) >> 4) as u8
+                };
+            }
+        }
+        _ => {
+            let row = &plane[(y / sy) * layout.stride..];
+            for (x, value) in out.iter_mut().enumerate() {
+                *value = row[x / sx];
+            }
+        }
+    }
+}
+
+// Remap RGBA pixels so the image displays upright for EXIF orientations 2-8
+fn apply_orientation(pixels: &[u8], width: u32, height: u32, orientation: u8) -> (u32, u32, Vec<u8>) {
+    let mut out = match orientation {
+        3 | 4 => rotate_180(pixels, width, height),
+        5 | 6 => rotate_90(pixels, width, height),
+        7 | 8 => rotate_270(pixels, width, height),
+        _ => pixels.to_vec(),
+    };
+    let (out_w, out_h) = if orientation >= 5 { (height, width) } else { (width, height) };
+    // Mirrored orientations are the plain rotation followed by a horizontal flip
+    if matches!(orientation, 2 | 4 | 5 | 7) {
+        flip_horizontal(&mut out, out_w, out_h);
+    }
+    (out_w, out_h, out)
+}
+
+// Minimal TIFF/EXIF reader: IFD0 (orientation, make, model, date) and the Exif sub-IFD
+// (exposure, aperture, ISO, focal length, lens). Malformed entries are skipped.
+fn parse_exif(tiff: &[u8], meta: &mut JpegMetadata) {
+    let little_endian = match tiff.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes = tiff.get(offset..offset + 2)?;
+        Some(if little_endian {
+            u16::from_le_bytes([bytes[0], bytes[1]])
+        } else {
+            u16::from_be_bytes([bytes[0], bytes[1]])
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = tiff.get(offset..offset + 4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Some(if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+
+    // (tag, type, count, offset of the value bytes)
+    let read_ifd = |offset: usize| -> Vec<(u16, u16, usize, usize)> {
+        let Some(count) = read_u16(offset) else { return Vec::new() };
+        (0..count as usize)
+            .filter_map(|i| {
+                let entry = offset + 2 + i * 12;
+                let tag = read_u16(entry)?;
+                let kind = read_u16(entry + 2)?;
+                let count = read_u32(entry + 4)? as usize;
+                let unit = match kind {
+                    1 | 2 | 6 | 7 => 1,
+                    3 | 8 => 2,
+                    4 | 9 | 11 => 4,
+                    5 | 10 | 12 => 8,
+                    _ => return None,
+                };
+                let value_offset = if unit * count <= 4 { entry + 8 } else { read_u32(entry + 8)? as usize };
+                tiff.get(value_offset..value_offset + unit * count)?;
+                Some((tag, kind, count, value_offset))
+            })
+            .collect()
+    };
+
+    let ascii = |offset: usize, count: usize| -> String {
+        let bytes = &tiff[offset..offset + count];
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+    };
+    let integer = |kind: u16, offset: usize| -> Option<u32> {
+        match kind {
+            3 => read_u16(offset).map(u32::from),
+            4 => read_u32(offset),
+            _ => None,
+        }
+    };
+    let rational = |kind: u16, offset: usize| -> Option<f64> {
+        if kind != 5 && kind != 10 {
+            return None;
+        }
+        let (num, den) = (read_u32(offset)?, read_u32(offset + 4)?);
+        let (num, den) = if kind == 10 { (num as i32 as f64, den as i32 as f64) } else { (num as f64, den as f64) };
+        (den != 0.0).then(|| num / den)
+    };
+
+    let Some(ifd0) = read_u32(4) else { return };
+    let mut exif_ifd = None;
+    let mut date_time = String::new();
+    for (tag, kind, count, offset) in read_ifd(ifd0 as usize) {
+        match tag {
+            0x0112 => {
+                if let Some(value @ 1..=8) = integer(kind, offset) {
+                    meta.orientation = value as u8;
+                }
+            }
+            0x010F if kind == 2 => meta.make = ascii(offset, count),
+            0x0110 if kind == 2 => meta.model = ascii(offset, count),
+            0x0132 if kind == 2 => date_time = ascii(offset, count),
+            0x8769 => exif_ifd = integer(kind, offset),
+            _ => {}
+        }
+    }
+
+    if let Some(exif_ifd) = exif_ifd {
+        for (tag, kind, count, offset) in read_ifd(exif_ifd as usize) {
+            match tag {
+                0x829A => meta.exposure_time = rational(kind, offset).unwrap_or(0.0),
+                0x829D => meta.f_number = rational(kind, offset).unwrap_or(0.0),
+                0x8827 => meta.iso = integer(kind, offset).unwrap_or(0),
+                0x920A => meta.focal_length = rational(kind, offset).unwrap_or(0.0),
+                0x9003 if kind == 2 => meta.date_time = ascii(offset, count),
+                0xA434 if kind == 2 => meta.lens_model = ascii(offset, count),
+                _ => {}
+            }
+        }
+    }
+    if meta.date_time.is_empty() {
+        meta.date_time = date_time;
+    }
+}
+
+// Decode to RGBA; with auto_orient the EXIF orientation is applied so phone photos come out upright
+#[wasm_bindgen]
+pub fn decode_jpeg(data: &[u8], auto_orient: bool) -> Result<JpegImage, JsValue> {
+    JpegImage::decode(data, auto_orient).map_err(|e| JsValue::from_str(&e))
+}
+
+// Header and EXIF only, without decoding any scans
+#[wasm_bindgen]
+pub fn read_jpeg_metadata(data: &[u8]) -> Result<JpegMetadata, JsValue> {
+    let mut decoder = Decoder::new(data);
+    decoder.run(true).map_err(|e| JsValue::from_str(&e))?;
+    Ok(decoder.metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(format!("{FIXTURES}/{name}")).unwrap()
+    }
+
+    fn reference_rgba(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut decoder = jpeg_decoder::Decoder::new(bytes);
+        let pixels = decoder.decode().unwrap();
+        let info = decoder.info().unwrap();
+        assert_eq!(info.pixel_format, jpeg_decoder::PixelFormat::RGB24);
+        let rgba = pixels.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect();
+        (info.width as u32, info.height as u32, rgba)
+    }
+
+    // SOF0 for a single 8-bit component, optionally followed by a scan header with no data
+    fn jpeg_with_sof(width: u16, height: u16, with_scan: bool) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xC0, 0, 11, 8];
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&[1, 1, 0x11, 0]);
+        if with_scan {
+            data.extend_from_slice(&[0xFF, 0xDA, 0, 8, 1, 1, 0, 0, 63, 0]);
+        }
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn fixtures_match_jpeg_decoder() {
+        for name in ["baseline_420.jpg", "progressive_422.jpg"] {
+            let bytes = fixture(name);
+            let ours = JpegImage::decode(&bytes, false).unwrap();
+            let (width, height, expected) = reference_rgba(&bytes);
+            assert_eq!((ours.get_width(), ours.get_height()), (width, height), "{name}");
+            // IDCT and upsampling rounding differ slightly between decoders
+            let worst = ours.get_pixels().iter().zip(&expected).map(|(a, b)| a.abs_diff(*b)).max().unwrap();
+            assert!(worst <= 3, "{name} differs from jpeg-decoder by up to {worst}");
+        }
+        let progressive = JpegImage::decode(&fixture("progressive_422.jpg"), false).unwrap();
+        assert!(progressive.get_metadata().is_progressive());
+    }
+
+    #[test]
+    fn applies_exif_orientation() {
+        // Stored 24x16 with a yellow quadrant top-left; orientation 6 rotates it a quarter turn clockwise
+        let bytes = fixture("orientation_6.jpg");
+        let stored = JpegImage::decode(&bytes, false).unwrap();
+        assert_eq!((stored.get_width(), stored.get_height()), (24, 16));
+
+        let upright = JpegImage::decode(&bytes, true).unwrap();
+        let meta = upright.get_metadata();
+        assert_eq!(meta.get_orientation(), 6);
+        assert_eq!((upright.get_width(), upright.get_height()), (16, 24));
+        assert_eq!((meta.get_display_width(), meta.get_display_height()), (16, 24));
+
+        let pixels = upright.get_pixels();
+        let yellow = |x: usize, y: usize| {
+            let i = (y * 16 + x) * 4;
+            pixels[i] > 200 && pixels[i + 1] > 200
+        };
+        assert!(yellow(15, 0));
+        assert!(!yellow(0, 0) && !yellow(0, 23) && !yellow(15, 23));
+    }
+
+    #[test]
+    fn rejects_truncated_and_oversized_files() {
+        let bytes = fixture("baseline_420.jpg");
+        assert!(JpegImage::decode(&bytes[..bytes.len() / 2], false).is_err());
+        assert!(JpegImage::decode(&bytes[..2], false).is_err());
+
+        // The header alone is still readable; decoding must refuse before allocating 4 GiB of coefficients
+        let huge = jpeg_with_sof(65535, 65535, true);
+        let mut decoder = Decoder::new(&huge);
+        decoder.run(true).unwrap();
+        assert_eq!((decoder.metadata.get_width(), decoder.metadata.get_height()), (65535, 65535));
+        let err = JpegImage::decode(&huge, false).err().unwrap();
+        assert!(err.contains("pixel limit"), "{err}");
+        assert!(JpegImage::decode(&jpeg_with_sof(64, 64, false), false).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::pipeline::Pipeline;
+use crate::working_space::{decode_srgb, encode_srgb, WorkingSpace};
+use wasm_bindgen::prelude::*;
+
+// Grid size for baked pipelines; 33 points per axis is the usual grading-tool default
+const BAKE_SIZE: u32 = 33;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Interpolation {
+    Trilinear,
+    Tetrahedral,
+}
+
+impl Interpolation {
+    pub(crate) fn from_name(name: &str) -> Result<Interpolation, String> {
+        match name {
+            "trilinear" => Ok(Interpolation::Trilinear),
+            "tetrahedral" => Ok(Interpolation::Tetrahedral),
+            _ => Err(format!("Unknown LUT interpolation: {} (expected trilinear or tetrahedral)", name)),
+        }
+    }
+}
+
+// A 1D or 3D color lookup table in the Adobe/Resolve .cube layout: outputs are 0-1 floats, and
+// in 3D tables red varies fastest
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Lut {
+    title: String,
+    three_d: bool,
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    table: Vec<[f32; 3]>,
+}
+
+fn parse_numbers(words: &[&str], count: usize, line: usize) -> Result<Vec<f32>, String> {
+    if words.len() != count {
+        return Err(format!("line {}: expected {} values, found {}", line, count, words.len()));
+    }
+    words
+        .iter()
+        .map(|word| {
+            word.parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("line {}: invalid number '{}'", line, word))
+        })
+        .collect()
+}
+
+fn parse_triple(words: &[&str], line: usize) -> Result<[f32; 3], String> {
+    let v = parse_numbers(words, 3, line)?;
+    Ok([v[0], v[1], v[2]])
+}
+
+impl Lut {
+    pub(crate) fn parse(text: &str) -> Result<Lut, String> {
+        let mut title = String::new();
+        let (mut size_1d, mut size_3d) = (None, None);
+        let mut domain_min = [0f32; 3];
+        let mut domain_max = [1f32; 3];
+        let mut table = Vec::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let content = raw.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = content.split_whitespace().collect();
+            let keyword = words[0];
+            if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if !table.is_empty() {
+                    return Err(format!("line {}: {} after table data", line, keyword));
+                }
+                let size = |words: &[&str], max: usize| -> Result<usize, String> {
+                    words
+                        .get(1)
+                        .and_then(|w| w.parse::<usize>().ok())
+                        .filter(|n| (2..=max).contains(n))
+                        .ok_or_else(|| format!("line {}: {} must be between 2 and {}", line, keyword, max))
+                };
+                match keyword {
+                    "TITLE" => title = content["TITLE".len()..].trim().trim_matches('"').to_string(),
+                    "LUT_1D_SIZE" => size_1d = Some(size(&words, 65536)?),
+                    "LUT_3D_SIZE" => size_3d = Some(size(&words, 256)?),
+                    // Resolve spells the domain as an input range shared by all channels
+                    "DOMAIN_MIN" => domain_min = parse_triple(&words[1..], line)?,
+                    "DOMAIN_MAX" => domain_max = parse_triple(&words[1..], line)?,
+                    "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                        let range = parse_numbers(&words[1..], 2, line)?;
+                        domain_min = [range[0]; 3];
+                        domain_max = [range[1]; 3];
+                    }
+                    _ => return Err(format!("line {}: unknown keyword {}", line, keyword)),
+                }
+            } else {
+                table.push(parse_triple(&words, line)?);
+            }
+        }
+
+        let (three_d, size) = match (size_1d, size_3d) {
+            (Some(_), Some(_)) => return Err("Files with both a 1D and a 3D table are not supported".to_string()),
+            (Some(n), None) => (false, n),
+            (None, Some(n)) => (true, n),
+            (None, None) => return Err("Missing LUT_1D_SIZE or LUT_3D_SIZE".to_string()),
+        };
+        let expected = if three_d { size * size * size } else { size };
+        if table.len() != expected {
+            return Err(format!("Expected {} table entries, found {}", expected, table.len()));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be greater than DOMAIN_MIN".to_string());
+        }
+        Ok(Lut { title, three_d, size, domain_min, domain_max, table })
+    }
+
+    // Sample a filter chain on a size^3 grid. Only per-pixel stages can be baked; blur, sharpen
+    // and edge depend on neighbours, so a color lookup can't reproduce them.
+    pub(crate) fn bake(pipeline: &Pipeline, size: u32) -> Result<Lut, String> {
+        if !(2..=129).contains(&size) {
+            return Err("LUT size must be between 2 and 129".to_string());
+        }
+        if let Some((i, stage)) = pipeline.stages().iter().enumerate().find(|(_, s)| s.is_spatial()) {
+            return Err(format!("stage {}: \"{}\" depends on neighbouring pixels and can't be baked into a LUT", i, stage.op()));
+        }
+
+        let n = size as usize;
+        let linear = pipeline.working_space() == WorkingSpace::Linear;
+        let level = |i: usize| {
+            let v = i as f64 / (n - 1) as f64;
+            (if linear { decode_srgb(v) } else { v } * 255.0) as f32
+        };
+        // The grid as an n^2 x n float image, red fastest like the .cube table
+        let mut values = Vec::with_capacity(n * n * n * 4);
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n {
+                    values.extend_from_slice(&[level(r), level(g), level(b), 255.0]);
+                }
+            }
+        }
+        for stage in pipeline.stages() {
+            stage.run(&mut values, (n * n) as u32, n as u32);
+        }
+
+        let table = values
+            .chunks_exact(4)
+            .map(|p| {
+                let unit = [p[0], p[1], p[2]].map(|v| (v / 255.0) as f64);
+                unit.map(|v| if linear { encode_srgb(v) as f32 } else { v as f32 })
+            })
+            .collect();
+        Ok(Lut {
+            title: "Baked pipeline".to_string(),
+            three_d: true,
+            size: n,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+        })
+    }
+
+    // Grid coordinate (0..size-1) of an 8-bit channel value
+    fn coordinate(&self, value: u8, channel: usize) -> f32 {
+        let (lo, hi) = (self.domain_min[channel], self.domain_max[channel]);
+        ((value as f32 / 255.0 - lo) / (hi - lo)).clamp(0.0, 1.0) * (self.size - 1) as f32
+    }
+
+    fn lookup_1d(&self, rgb: [u8; 3]) -> [f32; 3] {
+        let mut out = [0f32; 3];
+        for (c, o) in out.iter_mut().enumerate() {
+            let x = self.coordinate(rgb[c], c);
+            let i = (x as usize).min(self.size - 2);
+            let t = x - i as f32;
+            *o = self.table[i][c] * (1.0 - t) + self.table[i + 1][c] * t;
+        }
+        out
+    }
+
+    fn lookup_3d(&self, rgb: [u8; 3], interpolation: Interpolation) -> [f32; 3] {
+        let n = self.size;
+        let mut base = [0usize; 3];
+        let mut f = [0f32; 3];
+        for c in 0..3 {
+            let x = self.coordinate(rgb[c], c);
+            base[c] = (x as usize).min(n - 2);
+            f[c] = x - base[c] as f32;
+        }
+        // Corner (dr, dg, db) of the cell
+        let at = |dr: usize, dg: usize, db: usize| self.table[(base[0] + dr) + (base[1] + dg) * n + (base[2] + db) * n * n];
+        let lerp = |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t);
+
+        match interpolation {
+            Interpolation::Trilinear => {
+                let c00 = lerp(at(0, 0, 0), at(1, 0, 0), f[0]);
+                let c10 = lerp(at(0, 1, 0), at(1, 1, 0), f[0]);
+                let c01 = lerp(at(0, 0, 1), at(1, 0, 1), f[0]);
+                let c11 = lerp(at(0, 1, 1), at(1, 1, 1), f[0]);
+                lerp(lerp(c00, c10, f[1]), lerp(c01, c11, f[1]), f[2])
+            }
+            // Split the cell into six tetrahedra along its grey diagonal and blend the four
+            // corners of the one containing the point; cheaper than trilinear and keeps neutrals
+            // on the diagonal
+            Interpolation::Tetrahedral => {
+                let [fr, fg, fb] = f;
+                let (steps, weights) = if fr > fg {
+                    if fg > fb {
+                        ([(1, 0, 0), (1, 1, 0)], [fr, fg, fb])
+                    } else if fr > fb {
+                        ([(1, 0, 0), (1, 0, 1)], [fr, fb, fg])
+                    } else {
+                        ([(0, 0, 1), (1, 0, 1)], [fb, fr, fg])
+                    }
+                } else if fb > fg {
+                    ([(0, 0, 1), (0, 1, 1)], [fb, fg, fr])
+                } else if fb > fr {
+                    ([(0, 1, 0), (0, 1, 1)], [fg, fb, fr])
+                } else {
+                    ([(0, 1, 0), (1, 1, 0)], [fg, fr, fb])
+                };
+                let corners = [at(0, 0, 0), at(steps[0].0, steps[0].1, steps[0].2), at(steps[1].0, steps[1].1, steps[1].2), at(1, 1, 1)];
+                [0, 1, 2].map(|c| {
+                    corners[0][c]
+                        + weights[0] * (corners[1][c] - corners[0][c])
+                        + weights[1] * (corners[2][c] - corners[1][c])
+                        + weights[2] * (corners[3][c] - corners[2][c])
+                })
+            }
+        }
+    }
+
+    pub(crate) fn apply_rgba(&self, data: &mut [u8], interpolation: Interpolation) {
+        for p in data.chunks_exact_mut(4) {
+            let rgb = [p[0], p[1], p[2]];
+            let out = if self.three_d { self.lookup_3d(rgb, interpolation) } else { self.lookup_1d(rgb) };
+            for c in 0..3 {
+                p[c] = (out[c] * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Lut {
+    // Parse the text of a .cube file
+    #[wasm_bindgen]
+    pub fn from_cube(text: &str) -> Result<Lut, JsValue> {
+        Lut::parse(text).map_err(|e| JsValue::from_str(&e))
+    }
+
+    // Bake a pipeline of per-pixel stages into a size^3 LUT (33 if size is 0)
+    #[wasm_bindgen]
+    pub fn from_pipeline(pipeline: &Pipeline, size: u32) -> Result<Lut, JsValue> {
+        let size = if size == 0 { BAKE_SIZE } else { size };
+        Lut::bake(pipeline, size).map_err(|e| JsValue::from_str(&e))
+    }
+
+    // Serialize back to .cube text
+    #[wasm_bindgen]
+    pub fn to_cube(&self) -> String {
+        let mut out = String::new();
+        if !self.title.is_empty() {
+            out.push_str(&format!("TITLE \"{}\"\n", self.title));
+        }
+        let keyword = if self.three_d { "LUT_3D_SIZE" } else { "LUT_1D_SIZE" };
+        out.push_str(&format!("{} {}\n", keyword, self.size));
+        let [r0, g0, b0] = self.domain_min;
+        let [r1, g1, b1] = self.domain_max;
+        out.push_str(&format!("DOMAIN_MIN {} {} {}\nDOMAIN_MAX {} {} {}\n", r0, g0, b0, r1, g1, b1));
+        for [r, g, b] in &self.table {
+            out.push_str(&format!("{:.6} {:.6} {:.6}\n", r, g, b));
+        }
+        out
+    }
+
+    #[wasm_bindgen]
+    pub fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    // 1 or 3
+    #[wasm_bindgen]
+    pub fn get_dimensions(&self) -> u32 {
+        if self.three_d { 3 } else { 1 }
+    }
+
+    // Points per axis
+    #[wasm_bindgen]
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_domain_min(&self) -> Vec<f32> {
+        self.domain_min.to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_domain_max(&self) -> Vec<f32> {
+        self.domain_max.to_vec()
+    }
+}
+
+// Apply a LUT in place; interpolation is "trilinear" or "tetrahedral" (ignored for 1D tables).
+// Alpha is preserved.
+#[wasm_bindgen]
+pub fn apply_lut(data: &mut [u8], width: u32, height: u32, lut: &Lut, interpolation: &str) -> Result<(), JsValue> {
+    if data.len() != (width * height * 4) as usize {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    let interpolation = Interpolation::from_name(interpolation).map_err(|e| JsValue::from_str(&e))?;
+    lut.apply_rgba(data, interpolation);
+    Ok(())
+}
+
+// One-shot helper: bake a pipeline definition (see Pipeline::from_json) into a 33^3 LUT
+#[wasm_bindgen]
+pub fn bake_lut(pipeline_json: &str) -> Result<Lut, JsValue> {
+    let pipeline = Pipeline::parse(pipeline_json).map_err(|e| JsValue::from_str(&e))?;
+    Lut::bake(&pipeline, BAKE_SIZE).map_err(|e| JsValue::from_str(&e))
+}
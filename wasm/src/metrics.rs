@@ -0,0 +1,167 @@
+use crate::canny::gaussian_smooth;
+use wasm_bindgen::prelude::*;
+
+// Peak signal-to-noise ratio in dB over the R, G and B channels; identical images give infinity
+pub(crate) fn psnr_rgba(a: &[u8], b: &[u8]) -> Result<f64, String> {
+    if a.len() != b.len() || a.is_empty() || !a.len().is_multiple_of(4) {
+        return Err("Images must be non-empty RGBA buffers of the same size".to_string());
+    }
+
+    let (mut squared, mut count) = (0u64, 0u64);
+    for (p, q) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+        for c in 0..3 {
+            let d = p[c] as i64 - q[c] as i64;
+            squared += (d * d) as u64;
+        }
+        count += 3;
+    }
+
+    if squared == 0 {
+        return Ok(f64::INFINITY);
+    }
+    let mse = squared as f64 / count as f64;
+    Ok(10.0 * (255.0 * 255.0 / mse).log10())
+}
+
+#[wasm_bindgen]
+pub fn psnr(a: &[u8], b: &[u8]) -> Result<f64, JsValue> {
+    psnr_rgba(a, b).map_err(|e| JsValue::from_str(&e))
+}
+
+// SSIM constants from Wang et al. for 8-bit data: an 11-tap Gaussian window (sigma 1.5 covers
+// 3 sigma either side) and the stabilizers (K * L)^2
+const SSIM_SIGMA: f32 = 1.5;
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+// MS-SSIM scale weights from Wang, Simoncelli and Bovik (2003), finest scale first
+const MS_SSIM_WEIGHTS: [f64; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+
+fn check_pair(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<(), String> {
+    if a.len() != b.len() || a.is_empty() || a.len() != (width * height * 4) as usize {
+        return Err("Images must be non-empty RGBA buffers of width * height * 4 bytes".to_string());
+    }
+    Ok(())
+}
+
+// R, G and B as separate float planes
+fn planes(data: &[u8]) -> [Vec<f32>; 3] {
+    [0, 1, 2].map(|c| data.chunks_exact(4).map(|p| p[c] as f32).collect())
+}
+
+// Mean SSIM and mean contrast-structure term of one plane pair, with Gaussian-weighted local
+// statistics and clamped borders so small images still work
+fn ssim_terms(x: &[f32], y: &[f32], width: usize, height: usize) -> (f64, f64) {
+    let blur = |v: Vec<f32>| gaussian_smooth(&v, width, height, SSIM_SIGMA);
+    let mu_x = blur(x.to_vec());
+    let mu_y = blur(y.to_vec());
+    let xx = blur(x.iter().map(|v| v * v).collect());
+    let yy = blur(y.iter().map(|v| v * v).collect());
+    let xy = blur(x.iter().zip(y).map(|(a, b)| a * b).collect());
+
+    let (mut ssim, mut cs) = (0f64, 0f64);
+    for i in 0..x.len() {
+        let (mx, my) = (mu_x[i] as f64, mu_y[i] as f64);
+        let var_x = (xx[i] as f64 - mx * mx).max(0.0);
+        let var_y = (yy[i] as f64 - my * my).max(0.0);
+        let cov = xy[i] as f64 - mx * my;
+        let structure = (2.0 * cov + C2) / (var_x + var_y + C2);
+        cs += structure;
+        ssim += (2.0 * mx * my + C1) / (mx * mx + my * my + C1) * structure;
+    }
+    let n = x.len() as f64;
+    (ssim / n, cs / n)
+}
+
+// Halve a plane by averaging 2x2 blocks (odd edges are dropped, as in the reference code)
+fn downsample(plane: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let (w, h) = (width / 2, height / 2);
+    let mut out = Vec::with_capacity(w * h);
+    for y in 0..h {
+        for x in 0..w {
+            let at = |dx: usize, dy: usize| plane[(2 * y + dy) * width + 2 * x + dx];
+            out.push((at(0, 0) + at(1, 0) + at(0, 1) + at(1, 1)) / 4.0);
+        }
+    }
+    out
+}
+
+// Structural similarity in [-1, 1] (1 = identical), averaged over R, G and B
+pub(crate) fn ssim_rgba(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<f64, String> {
+    check_pair(a, b, width, height)?;
+    let (pa, pb) = (planes(a), planes(b));
+    let total: f64 = pa.iter().zip(&pb).map(|(x, y)| ssim_terms(x, y, width as usize, height as usize).0).sum();
+    Ok(total / 3.0)
+}
+
+// Multi-scale SSIM: contrast-structure at each halving plus full SSIM at the coarsest scale.
+// Images too small for five scales use as many as keep the coarsest side at 8 pixels or more,
+// with the weights renormalized.
+pub(crate) fn ms_ssim_rgba(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<f64, String> {
+    check_pair(a, b, width, height)?;
+    let mut scales = 1;
+    while scales < MS_SSIM_WEIGHTS.len() && (width.min(height) >> scales) >= 8 {
+        scales += 1;
+    }
+    let weights = &MS_SSIM_WEIGHTS[..scales];
+    let weight_total: f64 = weights.iter().sum();
+
+    let mut total = 0.0;
+    for (mut x, mut y) in planes(a).into_iter().zip(planes(b)) {
+        let (mut w, mut h) = (width as usize, height as usize);
+        let mut product = 1.0;
+        for (scale, weight) in weights.iter().enumerate() {
+            let (ssim, cs) = ssim_terms(&x, &y, w, h);
+            // Negative terms would make fractional powers undefined
+            let term = if scale + 1 == scales { ssim } else { cs };
+            product *= term.max(0.0).powf(weight / weight_total);
+            if scale + 1 < scales {
+                x = downsample(&x, w, h);
+                y = downsample(&y, w, h);
+                w /= 2;
+                h /= 2;
+            }
+        }
+        total += product;
+    }
+    Ok(total / 3.0)
+}
+
+// Heat-map stops from black through purple and red to pale yellow
+const HEATMAP: [[f32; 3]; 5] = [[0.0, 0.0, 0.0], [80.0, 18.0, 123.0], [200.0, 40.0, 60.0], [250.0, 140.0, 30.0], [252.0, 250.0, 190.0]];
+
+fn heat(t: f32) -> [u8; 3] {
+    let pos = t.clamp(0.0, 1.0) * (HEATMAP.len() - 1) as f32;
+    let i = (pos as usize).min(HEATMAP.len() - 2);
+    let f = pos - i as f32;
+    [0, 1, 2].map(|c| (HEATMAP[i][c] + (HEATMAP[i + 1][c] - HEATMAP[i][c]) * f).round() as u8)
+}
+
+// Largest per-channel difference of every pixel (alpha included) rendered as an opaque heat map;
+// gain scales the difference so small errors are visible (1 maps a 255 difference to the top)
+pub(crate) fn diff_heatmap_rgba(a: &[u8], b: &[u8], width: u32, height: u32, gain: f32) -> Result<Vec<u8>, String> {
+    check_pair(a, b, width, height)?;
+    Ok(a.chunks_exact(4)
+        .zip(b.chunks_exact(4))
+        .flat_map(|(p, q)| {
+            let diff = p.iter().zip(q).map(|(&u, &v)| u.abs_diff(v)).max().unwrap_or(0);
+            let [r, g, b] = heat(diff as f32 * gain / 255.0);
+            [r, g, b, 255]
+        })
+        .collect())
+}
+
+#[wasm_bindgen]
+pub fn ssim(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<f64, JsValue> {
+    ssim_rgba(a, b, width, height).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn ms_ssim(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<f64, JsValue> {
+    ms_ssim_rgba(a, b, width, height).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn diff_heatmap(a: &[u8], b: &[u8], width: u32, height: u32, gain: f32) -> Result<Vec<u8>, JsValue> {
+    diff_heatmap_rgba(a, b, width, height, gain).map_err(|e| JsValue::from_str(&e))
+}
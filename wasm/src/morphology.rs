@@ -0,0 +1,371 @@
+use crate::blur::SummedAreaTable;
+use crate::canny::gaussian_smooth;
+use crate::histogram::{luma, visible_luma_bins};
+use wasm_bindgen::prelude::*;
+
+fn check_size(data: &[u8], width: u32, height: u32) -> bool {
+    data.len() == (width * height * 4) as usize
+}
+
+// Write a binary result back as black/white pixels, keeping alpha
+fn store_binary(data: &mut [u8], foreground: impl Fn(usize, u8) -> bool) {
+    for (i, p) in data.chunks_exact_mut(4).enumerate() {
+        let v = if foreground(i, luma(p)) { 255 } else { 0 };
+        p[..3].fill(v);
+    }
+}
+
+// Luma level that best separates the visible pixels into two classes (maximum between-class
+// variance); pixels above it are foreground
+pub(crate) fn otsu_level(data: &[u8]) -> u8 {
+    let bins = visible_luma_bins(data);
+    let total: f64 = bins.iter().map(|&n| n as f64).sum();
+    let weighted: f64 = bins.iter().enumerate().map(|(v, &n)| v as f64 * n as f64).sum();
+    let (mut below, mut below_sum) = (0f64, 0f64);
+    let (mut best, mut best_variance) = (0u8, -1f64);
+    for (level, &n) in bins.iter().enumerate() {
+        below += n as f64;
+        below_sum += level as f64 * n as f64;
+        let above = total - below;
+        if below == 0.0 || above == 0.0 {
+            continue;
+        }
+        let diff = below_sum / below - (weighted - below_sum) / above;
+        let variance = below * above * diff * diff;
+        if variance > best_variance {
+            best_variance = variance;
+            best = level as u8;
+        }
+    }
+    best
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AdaptiveMethod {
+    Mean,
+    Gaussian,
+}
+
+impl AdaptiveMethod {
+    pub(crate) fn from_name(name: &str) -> Result<AdaptiveMethod, String> {
+        match name {
+            "mean" => Ok(AdaptiveMethod::Mean),
+            "gaussian" => Ok(AdaptiveMethod::Gaussian),
+            _ => Err(format!("Unknown adaptive threshold method: {} (expected mean or gaussian)", name)),
+        }
+    }
+}
+
+pub(crate) fn check_block_size(block_size: u32) -> Result<(), String> {
+    if block_size < 3 || block_size.is_multiple_of(2) {
+        return Err("Block size must be an odd number of at least 3".to_string());
+    }
+    Ok(())
+}
+
+// Compare every pixel with the mean (or Gaussian-weighted mean, with OpenCV's sigma for the
+// block) of its block_size neighbourhood minus offset. Copes with uneven lighting that defeats
+// a single global level, as in photographed documents.
+pub(crate) fn adaptive_threshold(data: &mut [u8], width: u32, height: u32, method: AdaptiveMethod, block_size: u32, offset: f32) {
+    let (w, h) = (width as usize, height as usize);
+    let lumas: Vec<u8> = data.chunks_exact(4).map(luma).collect();
+    let local: Vec<f32> = match method {
+        AdaptiveMethod::Mean => {
+            let values: Vec<f64> = lumas.iter().map(|&v| v as f64).collect();
+            SummedAreaTable::build(&values, w, h, 1).box_means(block_size as usize / 2).iter().map(|&m| m as f32).collect()
+        }
+        AdaptiveMethod::Gaussian => {
+            let sigma = 0.3 * ((block_size as f32 - 1.0) * 0.5 - 1.0) + 0.8;
+            let values: Vec<f32> = lumas.iter().map(|&v| v as f32).collect();
+            gaussian_smooth(&values, w, h, sigma)
+        }
+    };
+    store_binary(data, |i, _| lumas[i] as f32 > local[i] - offset);
+}
+
+// Binarize on luma: above level becomes white, the rest black; alpha is kept
+#[wasm_bindgen]
+pub fn threshold(data: &mut [u8], width: u32, height: u32, level: u8) {
+    if !check_size(data, width, height) {
+        return;
+    }
+    store_binary(data, |_, l| l > level);
+}
+
+// Binarize at the Otsu level and return it
+#[wasm_bindgen]
+pub fn threshold_otsu(data: &mut [u8], width: u32, height: u32) -> u8 {
+    if !check_size(data, width, height) {
+        return 0;
+    }
+    let level = otsu_level(data);
+    store_binary(data, |_, l| l > level);
+    level
+}
+
+// Locally adaptive binarization; method is "mean" or "gaussian", block_size is odd and offset
+// is subtracted from the local level (positive values keep faint background out)
+#[wasm_bindgen]
+pub fn threshold_adaptive(data: &mut [u8], width: u32, height: u32, method: &str, block_size: u32, offset: f32) -> Result<(), JsValue> {
+    let method = AdaptiveMethod::from_name(method).map_err(|e| JsValue::from_str(&e))?;
+    check_block_size(block_size).map_err(|e| JsValue::from_str(&e))?;
+    if !check_size(data, width, height) {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    adaptive_threshold(data, width, height, method, block_size, offset);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MorphOp {
+    Erode,
+    Dilate,
+    Open,
+    Close,
+    TopHat,
+    BlackHat,
+    Gradient,
+}
+
+impl MorphOp {
+    pub(crate) fn from_name(name: &str) -> Result<MorphOp, String> {
+        match name {
+            "erode" => Ok(MorphOp::Erode),
+            "dilate" => Ok(MorphOp::Dilate),
+            "open" => Ok(MorphOp::Open),
+            "close" => Ok(MorphOp::Close),
+            "top_hat" => Ok(MorphOp::TopHat),
+            "black_hat" => Ok(MorphOp::BlackHat),
+            "gradient" => Ok(MorphOp::Gradient),
+            _ => Err(format!(
+                "Unknown morphological operation: {} (expected erode, dilate, open, close, top_hat, black_hat or gradient)",
+                name
+            )),
+        }
+    }
+}
+
+// Neighbourhood for the morphological operators, as offsets from the anchor pixel. Rectangles
+// are kept as their half-extents too so they can run as two separable 1D passes.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct StructuringElement {
+    offsets: Vec<(i32, i32)>,
+    rect: Option<(u32, u32)>,
+    width: u32,
+    height: u32,
+}
+
+impl StructuringElement {
+    pub(crate) fn shape(shape: &str, radius_x: u32, radius_y: u32) -> Result<StructuringElement, String> {
+        let (rx, ry) = (radius_x as i32, radius_y as i32);
+        let inside: Box<dyn Fn(i32, i32) -> bool> = match shape {
+            "rect" => Box::new(|_, _| true),
+            "cross" => Box::new(|dx, dy| dx == 0 || dy == 0),
+            "ellipse" => Box::new(move |dx, dy| {
+                let nx = dx as f32 / (rx as f32).max(0.5);
+                let ny = dy as f32 / (ry as f32).max(0.5);
+                nx * nx + ny * ny <= 1.0
+            }),
+            _ => return Err(format!("Unknown structuring element shape: {} (expected rect, cross or ellipse)", shape)),
+        };
+        let offsets = (-ry..=ry).flat_map(|dy| (-rx..=rx).map(move |dx| (dx, dy))).filter(|&(dx, dy)| inside(dx, dy)).collect();
+        Ok(StructuringElement {
+            offsets,
+            rect: (shape == "rect").then_some((radius_x, radius_y)),
+            width: 2 * radius_x + 1,
+            height: 2 * radius_y + 1,
+        })
+    }
+
+    pub(crate) fn from_mask_bytes(mask: &[u8], width: u32, height: u32) -> Result<StructuringElement, String> {
+        if width == 0 || height == 0 || mask.len() != (width * height) as usize {
+            return Err("Mask must have one byte per element of a non-empty width x height grid".to_string());
+        }
+        let (ax, ay) = ((width / 2) as i32, (height / 2) as i32);
+        let offsets: Vec<(i32, i32)> = mask
+            .iter()
+            .enumerate()
+            .filter(|(_, &m)| m != 0)
+            .map(|(i, _)| ((i as u32 % width) as i32 - ax, (i as u32 / width) as i32 - ay))
+            .collect();
+        if offsets.is_empty() {
+            return Err("Structuring element mask has no set elements".to_string());
+        }
+        Ok(StructuringElement { offsets, rect: None, width, height })
+    }
+}
+
+#[wasm_bindgen]
+impl StructuringElement {
+    // "rect", "cross" or "ellipse" spanning (2 * radius_x + 1) x (2 * radius_y + 1)
+    #[wasm_bindgen(constructor)]
+    pub fn new(shape: &str, radius_x: u32, radius_y: u32) -> Result<StructuringElement, JsValue> {
+        StructuringElement::shape(shape, radius_x, radius_y).map_err(|e| JsValue::from_str(&e))
+    }
+
+    // Arbitrary element from a width x height grid of bytes (non-zero = set), anchored at its centre
+    #[wasm_bindgen]
+    pub fn from_mask(mask: &[u8], width: u32, height: u32) -> Result<StructuringElement, JsValue> {
+        StructuringElement::from_mask_bytes(mask, width, height).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // The element as a width x height grid of 0/1 bytes
+    #[wasm_bindgen]
+    pub fn get_mask(&self) -> Vec<u8> {
+        let (ax, ay) = ((self.width / 2) as i32, (self.height / 2) as i32);
+        let mut mask = vec![0u8; (self.width * self.height) as usize];
+        for &(dx, dy) in &self.offsets {
+            mask[((dy + ay) as u32 * self.width + (dx + ax) as u32) as usize] = 1;
+        }
+        mask
+    }
+}
+
+// Running min (erode) or max (dilate) along one axis of a single-channel plane
+fn line_extreme(plane: &[u8], width: usize, height: usize, radius: usize, horizontal: bool, dilate: bool) -> Vec<u8> {
+    let mut out = vec![0u8; plane.len()];
+    let (len, lines) = if horizontal { (width, height) } else { (height, width) };
+    let index = |line: usize, k: usize| if horizontal { line * width + k } else { k * width + line };
+    for line in 0..lines {
+        for k in 0..len {
+            let window = (k.saturating_sub(radius)..(k + radius + 1).min(len)).map(|j| plane[index(line, j)]);
+            out[index(line, k)] = if dilate { window.max() } else { window.min() }.unwrap_or(0);
+        }
+    }
+    out
+}
+
+// Grayscale erosion or dilation of one plane; offsets falling outside the image are ignored
+fn extreme(plane: &[u8], width: usize, height: usize, element: &StructuringElement, dilate: bool) -> Vec<u8> {
+    if let Some((rx, ry)) = element.rect {
+        let rows = line_extreme(plane, width, height, rx as usize, true, dilate);
+        return line_extreme(&rows, width, height, ry as usize, false, dilate);
+    }
+    let mut out = vec![0u8; plane.len()];
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let mut acc: Option<u8> = None;
+            for &(dx, dy) in &element.offsets {
+                // Dilation reflects the element so asymmetric elements stay duals of erosion
+                let (sx, sy) = if dilate { (x - dx, y - dy) } else { (x + dx, y + dy) };
+                if sx < 0 || sy < 0 || sx >= width as i32 || sy >= height as i32 {
+                    continue;
+                }
+                let v = plane[sy as usize * width + sx as usize];
+                acc = Some(acc.map_or(v, |a| if dilate { a.max(v) } else { a.min(v) }));
+            }
+            out[y as usize * width + x as usize] = acc.unwrap_or(plane[y as usize * width + x as usize]);
+        }
+    }
+    out
+}
+
+fn morph_plane(plane: &[u8], width: usize, height: usize, op: MorphOp, element: &StructuringElement) -> Vec<u8> {
+    let erode = |p: &[u8]| extreme(p, width, height, element, false);
+    let dilate = |p: &[u8]| extreme(p, width, height, element, true);
+    let subtract = |a: &[u8], b: &[u8]| a.iter().zip(b).map(|(&a, &b)| a.saturating_sub(b)).collect();
+    match op {
+        MorphOp::Erode => erode(plane),
+        MorphOp::Dilate => dilate(plane),
+        MorphOp::Open => dilate(&erode(plane)),
+        MorphOp::Close => erode(&dilate(plane)),
+        MorphOp::TopHat => subtract(plane, &dilate(&erode(plane))),
+        MorphOp::BlackHat => subtract(&erode(&dilate(plane)), plane),
+        MorphOp::Gradient => subtract(&dilate(plane), &erode(plane)),
+    }
+}
+
+// Apply a morphological operator to R, G and B independently (min/max filters, so binary
+// images stay binary); alpha is kept
+pub(crate) fn morphology_rgba(data: &mut [u8], width: u32, height: u32, op: MorphOp, element: &StructuringElement) {
+    let (w, h) = (width as usize, height as usize);
+    for c in 0..3 {
+        let plane: Vec<u8> = data.chunks_exact(4).map(|p| p[c]).collect();
+        let result = morph_plane(&plane, w, h, op, element);
+        for (p, v) in data.chunks_exact_mut(4).zip(result) {
+            p[c] = v;
+        }
+    }
+}
+
+// op is erode, dilate, open, close, top_hat (image minus its opening), black_hat (closing minus
+// the image) or gradient (dilation minus erosion)
+#[wasm_bindgen]
+pub fn morphology(data: &mut [u8], width: u32, height: u32, op: &str, element: &StructuringElement) -> Result<(), JsValue> {
+    let op = MorphOp::from_name(op).map_err(|e| JsValue::from_str(&e))?;
+    if !check_size(data, width, height) {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    morphology_rgba(data, width, height, op, element);
+    Ok(())
+}
+
+// Zhang-Suen thinning of the white (luma >= 128) foreground to a one-pixel-wide skeleton;
+// returns the number of passes it took
+pub(crate) fn skeletonize_rgba(data: &mut [u8], width: u32, height: u32) -> u32 {
+    let (w, h) = (width as i64, height as i64);
+    let mut fg: Vec<bool> = data.chunks_exact(4).map(|p| luma(p) >= 128).collect();
+    let at = |fg: &[bool], x: i64, y: i64| x >= 0 && y >= 0 && x < w && y < h && fg[(y * w + x) as usize];
+    let mut passes = 0;
+    loop {
+        passes += 1;
+        let mut changed = false;
+        for step in 0..2 {
+            let mut remove = Vec::new();
+            for y in 0..h {
+                for x in 0..w {
+                    if !fg[(y * w + x) as usize] {
+                        continue;
+                    }
+                    // P2..P9, clockwise from north
+                    let n = [
+                        at(&fg, x, y - 1),
+                        at(&fg, x + 1, y - 1),
+                        at(&fg, x + 1, y),
+                        at(&fg, x + 1, y + 1),
+                        at(&fg, x, y + 1),
+                        at(&fg, x - 1, y + 1),
+                        at(&fg, x - 1, y),
+                        at(&fg, x - 1, y - 1),
+                    ];
+                    let neighbours = n.iter().filter(|&&b| b).count();
+                    let transitions = (0..8).filter(|&i| !n[i] && n[(i + 1) % 8]).count();
+                    let (p2, p4, p6, p8) = (n[0], n[2], n[4], n[6]);
+                    // P2.P4.P6 = P4.P6.P8 = 0 on the first sub-iteration, P2.P4.P8 = P2.P6.P8 = 0 on the second
+                    let clear = if step == 0 { !(p4 && p6 && (p2 || p8)) } else { !(p2 && p8 && (p4 || p6)) };
+                    if (2..=6).contains(&neighbours) && transitions == 1 && clear {
+                        remove.push((y * w + x) as usize);
+                    }
+                }
+            }
+            changed |= !remove.is_empty();
+            for i in remove {
+                fg[i] = false;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    store_binary(data, |i, _| fg[i]);
+    passes
+}
+
+#[wasm_bindgen]
+pub fn skeletonize(data: &mut [u8], width: u32, height: u32) -> u32 {
+    if !check_size(data, width, height) {
+        return 0;
+    }
+    skeletonize_rgba(data, width, height)
+}
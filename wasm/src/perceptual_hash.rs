@@ -0,0 +1,123 @@
+use crate::histogram::luma;
+use crate::resize::{resize_rgba, ResizeFilter};
+use wasm_bindgen::prelude::*;
+
+// Size of the pHash DCT input and of the low-frequency corner kept from it
+const DCT_SIZE: usize = 32;
+const DCT_KEEP: usize = 8;
+
+// Luma of the image shrunk to width x height. Transparent areas are flattened onto white first
+// so a logo hashes the same with or without its background, and the box (area) filter averages
+// every source pixel so fine detail and noise don't alias into the hash.
+fn shrink_gray(data: &[u8], width: u32, height: u32, to_width: u32, to_height: u32) -> Result<Vec<f32>, String> {
+    if data.len() != (width * height * 4) as usize {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    let flat: Vec<u8> = data
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let a = p[3] as u32;
+            let y = ((luma(p) as u32 * a + 255 * (255 - a) + 127) / 255) as u8;
+            [y, y, y, 255]
+        })
+        .collect();
+    let small = resize_rgba(&flat, width, height, to_width, to_height, ResizeFilter::Area)?;
+    Ok(small.chunks_exact(4).map(|p| p[0] as f32).collect())
+}
+
+// Pack bits (most significant first) into a 64-bit hash
+fn pack(bits: impl Iterator<Item = bool>) -> u64 {
+    bits.fold(0u64, |hash, bit| (hash << 1) | bit as u64)
+}
+
+// Average hash: 8x8 luma thresholded at its mean
+pub(crate) fn average_hash(data: &[u8], width: u32, height: u32) -> Result<u64, String> {
+    let gray = shrink_gray(data, width, height, 8, 8)?;
+    let mean = gray.iter().sum::<f32>() / gray.len() as f32;
+    Ok(pack(gray.iter().map(|&v| v > mean)))
+}
+
+// Difference hash: 9x8 luma, one bit per horizontal neighbour pair (set where it gets brighter)
+pub(crate) fn difference_hash(data: &[u8], width: u32, height: u32) -> Result<u64, String> {
+    let gray = shrink_gray(data, width, height, 9, 8)?;
+    Ok(pack(gray.chunks_exact(9).flat_map(|row| row.windows(2).map(|pair| pair[1] > pair[0]))))
+}
+
+// Perceptual hash: 2D DCT-II of 32x32 luma; the 8x8 lowest frequencies are thresholded at their
+// median, so the hash follows the coarse structure and ignores brightness, gamma and small edits
+pub(crate) fn perceptual_hash(data: &[u8], width: u32, height: u32) -> Result<u64, String> {
+    let gray = shrink_gray(data, width, height, DCT_SIZE as u32, DCT_SIZE as u32)?;
+    let n = DCT_SIZE as f64;
+    let basis: Vec<f64> = (0..DCT_KEEP * DCT_SIZE)
+        .map(|i| {
+            let (k, x) = (i / DCT_SIZE, i % DCT_SIZE);
+            (std::f64::consts::PI * (2.0 * x as f64 + 1.0) * k as f64 / (2.0 * n)).cos()
+        })
+        .collect();
+    let dct_1d = |get: &dyn Fn(usize) -> f64, k: usize| (0..DCT_SIZE).map(|x| get(x) * basis[k * DCT_SIZE + x]).sum::<f64>();
+
+    // Rows first (only the kept frequencies), then columns
+    let rows: Vec<f64> = (0..DCT_SIZE * DCT_KEEP)
+        .map(|i| {
+            let (y, k) = (i / DCT_KEEP, i % DCT_KEEP);
+            dct_1d(&|x| gray[y * DCT_SIZE + x] as f64, k)
+        })
+        .collect();
+    let coefficients: Vec<f64> = (0..DCT_KEEP * DCT_KEEP)
+        .map(|i| {
+            let (ky, kx) = (i / DCT_KEEP, i % DCT_KEEP);
+            dct_1d(&|y| rows[y * DCT_KEEP + kx], ky)
+        })
+        .collect();
+
+    let mut sorted = coefficients.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0;
+    Ok(pack(coefficients.iter().map(|&c| c > median)))
+}
+
+fn to_hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+fn parse_hash(hex: &str) -> Result<u64, String> {
+    if hex.len() != 16 {
+        return Err(format!("Expected a 16-digit hex hash, got {:?}", hex));
+    }
+    u64::from_str_radix(hex, 16).map_err(|_| format!("Invalid hex hash: {:?}", hex))
+}
+
+// Number of differing bits between two hashes of the same kind
+pub(crate) fn hamming_distance(a: &str, b: &str) -> Result<u32, String> {
+    Ok((parse_hash(a)? ^ parse_hash(b)?).count_ones())
+}
+
+// Perceptual hashes are 16-digit hex strings (64 bits, most significant first), comparable with
+// phash_distance; unlike the text hashes above, similar images give similar hashes
+#[wasm_bindgen]
+pub fn ahash(data: &[u8], width: u32, height: u32) -> Result<String, JsValue> {
+    average_hash(data, width, height).map(to_hex).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn dhash(data: &[u8], width: u32, height: u32) -> Result<String, JsValue> {
+    difference_hash(data, width, height).map(to_hex).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn phash(data: &[u8], width: u32, height: u32) -> Result<String, JsValue> {
+    perceptual_hash(data, width, height).map(to_hex).map_err(|e| JsValue::from_str(&e))
+}
+
+// Hamming distance between two perceptual hashes (0 = same, 64 = every bit differs)
+#[wasm_bindgen]
+pub fn phash_distance(a: &str, b: &str) -> Result<u32, JsValue> {
+    hamming_distance(a, b).map_err(|e| JsValue::from_str(&e))
+}
+
+// Whether two hashes are within max_distance bits; around 10 of 64 is the usual cut-off for
+// "the same picture" after resizing, recompression or small color changes
+#[wasm_bindgen]
+pub fn hashes_similar(a: &str, b: &str, max_distance: u32) -> Result<bool, JsValue> {
+    hamming_distance(a, b).map(|d| d <= max_distance).map_err(|e| JsValue::from_str(&e))
+}
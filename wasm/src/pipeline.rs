@@ -0,0 +1,554 @@
+use crate::working_space::{decode_linear, encode_linear, parse_working_space, Channel, WorkingSpace};
+use crate::{brightness, color_filter, contrast, gaussian_blur, now_ms, sharpen, sobel_edges};
+use std::ops::RangeInclusive;
+use wasm_bindgen::prelude::*;
+
+const COLOR_FILTERS: [&str; 6] = ["sepia", "grayscale", "invert", "red", "green", "blue"];
+
+// Deepest array/object nesting accepted, so hostile input can't exhaust the stack
+const MAX_JSON_DEPTH: usize = 64;
+
+// Minimal JSON value, enough for pipeline definitions without pulling in serde
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse(text: &'a str) -> Result<Json, String> {
+        let mut parser = JsonParser { bytes: text.as_bytes(), pos: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(format!("Unexpected trailing characters at offset {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at offset {}", byte as char, self.pos))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(format!("Invalid literal at offset {}", self.pos))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(&open @ (b'{' | b'[')) => {
+                if self.depth == MAX_JSON_DEPTH {
+                    return Err(format!("Nesting deeper than {} levels at offset {}", MAX_JSON_DEPTH, self.pos));
+                }
+                self.depth += 1;
+                let value = if open == b'{' { self.object() } else { self.array() };
+                self.depth -= 1;
+                value
+            }
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(format!("Unexpected character at offset {}", self.pos)),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or("");
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| format!("Invalid number '{}' at offset {}", text, start))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while self.pos < self.bytes.len() && !matches!(self.bytes[self.pos], b'"' | b'\\') {
+                self.pos += 1;
+            }
+            out.push_str(std::str::from_utf8(&self.bytes[start..self.pos]).map_err(|e| e.to_string())?);
+
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    let escape = *self.bytes.get(self.pos + 1).ok_or("Unterminated escape")?;
+                    self.pos += 2;
+                    match escape {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'n' => out.push('\n'),
+                        b't' => out.push('\t'),
+                        b'r' => out.push('\r'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // A high surrogate followed by an escaped low surrogate is one code point;
+                            // unpaired halves become U+FFFD
+                            if (0xD800..0xDC00).contains(&code) && self.bytes[self.pos..].starts_with(b"\\u") {
+                                let saved = self.pos;
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if (0xDC00..0xE000).contains(&low) {
+                                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                                } else {
+                                    self.pos = saved;
+                                }
+                            }
+                            out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                        }
+                        _ => return Err(format!("Invalid escape at offset {}", self.pos - 1)),
+                    }
+                }
+                _ => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    // Four hex digits of a \u escape
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex = self.bytes.get(self.pos..self.pos + 4).ok_or("Truncated \\u escape")?;
+        let code = u32::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16)
+            .map_err(|_| "Invalid \\u escape".to_string())?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(format!("Expected ',' or ']' at offset {}", self.pos)),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(format!("Expected ',' or '}}' at offset {}", self.pos)),
+            }
+        }
+    }
+}
+
+// One validated pipeline step, mapping onto an existing filter function
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Stage {
+    Blur { radius: f32 },
+    Sharpen { strength: f32 },
+    Edge,
+    Brightness { factor: f32 },
+    Contrast { factor: f32 },
+    Color { filter: String },
+}
+
+impl Stage {
+    // A stage from the op names ImageProcessor::apply takes, with value as its one parameter
+    pub(crate) fn from_op(op: &str, value: f32) -> Option<Stage> {
+        match op {
+            "blur" => Some(Stage::Blur { radius: value }),
+            "sharpen" => Some(Stage::Sharpen { strength: value }),
+            "edge" => Some(Stage::Edge),
+            "brightness" => Some(Stage::Brightness { factor: value }),
+            "contrast" => Some(Stage::Contrast { factor: value }),
+            _ if COLOR_FILTERS.contains(&op) => Some(Stage::Color { filter: op.to_string() }),
+            _ => None,
+        }
+    }
+
+    fn from_json(value: &Json) -> Result<Stage, String> {
+        let fields = match value {
+            Json::Object(fields) => fields,
+            _ => return Err("stage must be an object".to_string()),
+        };
+        let field = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v);
+        let op = match field("op") {
+            Some(Json::String(op)) => op.as_str(),
+            Some(_) => return Err("\"op\" must be a string".to_string()),
+            None => return Err("missing \"op\"".to_string()),
+        };
+
+        let allowed: &[&str] = match op {
+            "blur" => &["radius"],
+            "sharpen" => &["strength"],
+            "edge" => &[],
+            "brightness" | "contrast" => &["factor"],
+            "color" => &["filter"],
+            _ => return Err(format!("unknown op \"{}\"", op)),
+        };
+        if let Some((key, _)) = fields.iter().find(|(k, _)| k != "op" && !allowed.contains(&k.as_str())) {
+            return Err(format!("unknown parameter \"{}\" for op \"{}\"", key, op));
+        }
+
+        // Checked after the cast so values past f32 range can't turn into infinity; the bounds keep
+        // shared pipelines from asking for huge kernels or contrast's pole at 259
+        let number = |name: &str, range: RangeInclusive<f32>| -> Result<f32, String> {
+            match field(name) {
+                Some(Json::Number(n)) => {
+                    let value = *n as f32;
+                    if !range.contains(&value) {
+                        return Err(format!("\"{}\" must be between {} and {}", name, range.start(), range.end()));
+                    }
+                    Ok(value)
+                }
+                Some(_) => Err(format!("\"{}\" must be a number", name)),
+                None => Err(format!("op \"{}\" requires \"{}\"", op, name)),
+            }
+        };
+
+        let stage = match op {
+            "blur" => Stage::Blur { radius: number("radius", 0.0..=100.0)? },
+            "sharpen" => Stage::Sharpen { strength: number("strength", 0.0..=10.0)? },
+            "edge" => Stage::Edge,
+            "brightness" => Stage::Brightness { factor: number("factor", 0.0..=10.0)? },
+            "contrast" => Stage::Contrast { factor: number("factor", -255.0..=255.0)? },
+            _ => match field("filter") {
+                Some(Json::String(filter)) if COLOR_FILTERS.contains(&filter.as_str()) => {
+                    Stage::Color { filter: filter.clone() }
+                }
+                Some(Json::String(filter)) => {
+                    return Err(format!("unknown color filter \"{}\" (expected one of {})", filter, COLOR_FILTERS.join(", ")));
+                }
+                Some(_) => return Err("\"filter\" must be a string".to_string()),
+                None => return Err("op \"color\" requires \"filter\"".to_string()),
+            },
+        };
+
+        Ok(stage)
+    }
+
+    pub(crate) fn op(&self) -> &'static str {
+        match self {
+            Stage::Blur { .. } => "blur",
+            Stage::Sharpen { .. } => "sharpen",
+            Stage::Edge => "edge",
+            Stage::Brightness { .. } => "brightness",
+            Stage::Contrast { .. } => "contrast",
+            Stage::Color { .. } => "color",
+        }
+    }
+
+    // Whether the result depends on neighbouring pixels rather than on the pixel's own color
+    pub(crate) fn is_spatial(&self) -> bool {
+        matches!(self, Stage::Blur { .. } | Stage::Sharpen { .. } | Stage::Edge)
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            Stage::Blur { radius } => format!("{{\"op\":\"blur\",\"radius\":{}}}", radius),
+            Stage::Sharpen { strength } => format!("{{\"op\":\"sharpen\",\"strength\":{}}}", strength),
+            Stage::Edge => "{\"op\":\"edge\"}".to_string(),
+            Stage::Brightness { factor } => format!("{{\"op\":\"brightness\",\"factor\":{}}}", factor),
+            Stage::Contrast { factor } => format!("{{\"op\":\"contrast\",\"factor\":{}}}", factor),
+            Stage::Color { filter } => format!("{{\"op\":\"color\",\"filter\":\"{}\"}}", filter),
+        }
+    }
+
+    pub(crate) fn run<S: Channel>(&self, data: &mut [S], width: u32, height: u32) {
+        match self {
+            Stage::Blur { radius } => gaussian_blur(data, width, height, *radius),
+            Stage::Sharpen { strength } => sharpen(data, width, height, *strength),
+            Stage::Edge => sobel_edges(data, width, height),
+            Stage::Brightness { factor } => brightness(data, width, height, *factor),
+            Stage::Contrast { factor } => contrast(data, width, height, *factor),
+            Stage::Color { filter } => color_filter(data, width, height, filter),
+        }
+    }
+}
+
+// An edit defined as data, e.g. [{"op":"blur","radius":3},{"op":"color","filter":"sepia"}], or
+// {"working_space":"linear","stages":[...]} to run every stage on linear light
+#[wasm_bindgen]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    working_space: WorkingSpace,
+    timings: Vec<f64>,
+}
+
+impl Pipeline {
+    pub(crate) fn parse(json: &str) -> Result<Pipeline, String> {
+        let (items, working_space) = match JsonParser::parse(json)? {
+            Json::Array(items) => (items, WorkingSpace::Srgb),
+            Json::Object(fields) => {
+                if let Some((key, _)) = fields.iter().find(|(k, _)| k != "stages" && k != "working_space") {
+                    return Err(format!("unknown pipeline field \"{}\"", key));
+                }
+                let field = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+                let working_space = match field("working_space") {
+                    Some(Json::String(name)) => parse_working_space(&name)?,
+                    Some(_) => return Err("\"working_space\" must be a string".to_string()),
+                    None => WorkingSpace::Srgb,
+                };
+                match field("stages") {
+                    Some(Json::Array(items)) => (items, working_space),
+                    Some(_) => return Err("\"stages\" must be an array".to_string()),
+                    None => return Err("missing \"stages\"".to_string()),
+                }
+            }
+            _ => return Err("Pipeline must be a JSON array of stages or an object with \"stages\"".to_string()),
+        };
+        let stages = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| Stage::from_json(item).map_err(|e| format!("stage {}: {}", i, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pipeline { stages, working_space, timings: Vec::new() })
+    }
+
+    pub(crate) fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    pub(crate) fn working_space(&self) -> WorkingSpace {
+        self.working_space
+    }
+
+    pub(crate) fn execute(&mut self, data: &mut [u8], width: u32, height: u32) -> Result<(), String> {
+        if data.len() != (width * height * 4) as usize {
+            return Err("Image data length does not match width * height * 4".to_string());
+        }
+
+        self.timings.clear();
+        match self.working_space {
+            WorkingSpace::Srgb => {
+                for stage in &self.stages {
+                    let start = now_ms();
+                    stage.run(data, width, height);
+                    self.timings.push(now_ms() - start);
+                }
+            }
+            // Decode once and keep the intermediate results as floats, so chained stages don't
+            // round to bytes in between
+            WorkingSpace::Linear => {
+                let mut values = decode_linear(data);
+                for stage in &self.stages {
+                    let start = now_ms();
+                    stage.run(&mut values, width, height);
+                    self.timings.push(now_ms() - start);
+                }
+                encode_linear(&values, data);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Pipeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Pipeline {
+        Pipeline { stages: Vec::new(), working_space: WorkingSpace::Srgb, timings: Vec::new() }
+    }
+
+    // Parse and validate; errors name the offending stage instead of silently skipping it
+    #[wasm_bindgen]
+    pub fn from_json(json: &str) -> Result<Pipeline, JsValue> {
+        Pipeline::parse(json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> String {
+        let stages: Vec<String> = self.stages.iter().map(Stage::to_json).collect();
+        match self.working_space {
+            WorkingSpace::Srgb => format!("[{}]", stages.join(",")),
+            space => format!("{{\"working_space\":\"{}\",\"stages\":[{}]}}", space.name(), stages.join(",")),
+        }
+    }
+
+    // "srgb" (the default) or "linear"
+    #[wasm_bindgen]
+    pub fn set_working_space(&mut self, working_space: &str) -> Result<(), JsValue> {
+        self.working_space = parse_working_space(working_space).map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_working_space(&self) -> String {
+        self.working_space.name().to_string()
+    }
+
+    // Append a single stage given as a JSON object
+    #[wasm_bindgen]
+    pub fn push_stage(&mut self, json: &str) -> Result<(), JsValue> {
+        let stage = JsonParser::parse(json)
+            .and_then(|value| Stage::from_json(&value))
+            .map_err(|e| JsValue::from_str(&format!("stage {}: {}", self.stages.len(), e)))?;
+        self.stages.push(stage);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.stages.clear();
+        self.timings.clear();
+    }
+
+    // Run every stage in one call; per-stage timings are available afterwards
+    #[wasm_bindgen]
+    pub fn run(&mut self, data: &mut [u8], width: u32, height: u32) -> Result<(), JsValue> {
+        self.execute(data, width, height).map_err(|e| JsValue::from_str(&e))
+    }
+
+    // Milliseconds per stage from the last run, in stage order
+    #[wasm_bindgen]
+    pub fn get_timings(&self) -> Vec<f64> {
+        self.timings.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_total_time(&self) -> f64 {
+        self.timings.iter().sum()
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+// Validate a pipeline definition without running it; returns an empty string when valid
+#[wasm_bindgen]
+pub fn validate_pipeline(json: &str) -> String {
+    match Pipeline::parse(json) {
+        Ok(_) => String::new(),
+        Err(e) => e,
+    }
+}
+
+// One-shot helper: parse and run, returning per-stage timings
+#[wasm_bindgen]
+pub fn run_pipeline(json: &str, data: &mut [u8], width: u32, height: u32) -> Result<Vec<f64>, JsValue> {
+    let mut pipeline = Pipeline::from_json(json)?;
+    pipeline.run(data, width, height)?;
+    Ok(pipeline.get_timings())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::png::{encode_png_rgba, PngImage};
+
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pipeline_golden.png");
+    const GOLDEN_PIPELINE: &str = r#"{"working_space":"linear","stages":[
+        {"op":"blur","radius":2},{"op":"sharpen","strength":1.5},
+        {"op":"contrast","factor":30},{"op":"color","filter":"sepia"}]}"#;
+
+    // Gradients with hard diagonal stripes, so both smooth areas and edges are covered
+    fn test_image(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let stripe = if (x + y) % 16 < 8 { 40 } else { 210 };
+                data.extend_from_slice(&[(x * 255 / (width - 1)) as u8, (y * 255 / (height - 1)) as u8, stripe, 255]);
+            }
+        }
+        data
+    }
+
+    // Set UPDATE_GOLDEN=1 to rewrite the fixture after an intentional change to a filter
+    #[test]
+    fn matches_golden_png() {
+        let (width, height) = (48, 32);
+        let mut data = test_image(width, height);
+        Pipeline::parse(GOLDEN_PIPELINE).unwrap().execute(&mut data, width, height).unwrap();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(GOLDEN, encode_png_rgba(&data, width, height, 9, "adaptive").unwrap()).unwrap();
+        }
+        let golden = PngImage::decode(&std::fs::read(GOLDEN).unwrap()).unwrap();
+        assert_eq!((golden.get_width(), golden.get_height()), (width, height));
+        // Allow one level of rounding drift between float implementations
+        let worst = data.iter().zip(golden.get_pixels()).map(|(a, b)| a.abs_diff(b)).max().unwrap();
+        assert!(worst <= 1, "pipeline output differs from the golden image by up to {worst}");
+    }
+
+    #[test]
+    fn range_errors_name_the_stage() {
+        let err = Pipeline::parse(r#"[{"op":"blur","radius":3},{"op":"contrast","factor":300}]"#).err().unwrap();
+        assert!(err.starts_with("stage 1: "), "{err}");
+        assert!(Pipeline::parse(r#"[{"op":"blur","radius":1e9}]"#).is_err());
+    }
+}
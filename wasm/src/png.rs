@@ -0,0 +1,501 @@
+use crate::crc32;
+use crate::deflate::{zlib_compress_bytes, zlib_decompress_bytes};
+use wasm_bindgen::prelude::*;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+// Adam7 passes as (x_start, y_start, x_step, y_step)
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+// Decoded image in the same RGBA layout the filter functions take
+#[wasm_bindgen]
+pub struct PngImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+#[wasm_bindgen]
+impl PngImage {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+
+    // 0 gray, 2 RGB, 3 palette, 4 gray + alpha, 6 RGBA
+    #[wasm_bindgen]
+    pub fn get_color_type(&self) -> u8 {
+        self.color_type
+    }
+
+    #[wasm_bindgen]
+    pub fn is_interlaced(&self) -> bool {
+        self.interlaced
+    }
+}
+
+impl PngImage {
+    // Native entry point (tests, tools); decode_png wraps this for JS
+    pub fn decode(data: &[u8]) -> Result<PngImage, String> {
+        if data.len() < 8 || data[..8] != PNG_SIGNATURE {
+            return Err("Not a PNG file".to_string());
+        }
+
+        let mut header: Option<(u32, u32, u8, u8, bool)> = None;
+        let mut palette: Vec<[u8; 4]> = Vec::new();
+        let mut transparency: Vec<u8> = Vec::new();
+        let mut idat = Vec::new();
+        let mut pos = 8;
+
+        while pos + 12 <= data.len() {
+            let length = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+            let chunk_end = pos + 12 + length;
+            if chunk_end > data.len() {
+                return Err("Truncated PNG chunk".to_string());
+            }
+            let kind = &data[pos + 4..pos + 8];
+            let body = &data[pos + 8..pos + 8 + length];
+            let stored_crc = u32::from_be_bytes([data[chunk_end - 4], data[chunk_end - 3], data[chunk_end - 2], data[chunk_end - 1]]);
+            if crc32(&data[pos + 4..pos + 8 + length]) != stored_crc {
+                return Err(format!("CRC mismatch in {} chunk", String::from_utf8_lossy(kind)));
+            }
+
+            match kind {
+                b"IHDR" => {
+                    if length != 13 {
+                        return Err("Invalid IHDR chunk".to_string());
+                    }
+                    let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                    let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+                    if body[10] != 0 || body[11] != 0 {
+                        return Err("Unsupported PNG compression or filter method".to_string());
+                    }
+                    header = Some((width, height, body[8], body[9], body[12] == 1));
+                }
+                b"PLTE" => {
+                    palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2], 255]).collect();
+                }
+                b"tRNS" => transparency = body.to_vec(),
+                b"IDAT" => idat.extend_from_slice(body),
+                b"IEND" => break,
+                _ => {}
+            }
+            pos = chunk_end;
+        }
+
+        let (width, height, bit_depth, color_type, interlaced) = header.ok_or("Missing IHDR chunk")?;
+        let channels = match (color_type, bit_depth) {
+            (0, 1 | 2 | 4 | 8 | 16) => 1,
+            (2, 8 | 16) => 3,
+            (3, 1 | 2 | 4 | 8) => 1,
+            (4, 8 | 16) => 2,
+            (6, 8 | 16) => 4,
+            _ => return Err(format!("Invalid color type {} with bit depth {}", color_type, bit_depth)),
+        };
+        if width == 0 || height == 0 {
+            return Err("PNG has zero width or height".to_string());
+        }
+        if color_type == 3 {
+            if palette.is_empty() {
+                return Err("Palette image without PLTE chunk".to_string());
+            }
+            for (entry, &alpha) in palette.iter_mut().zip(&transparency) {
+                entry[3] = alpha;
+            }
+        }
+
+        let raw = zlib_decompress_bytes(&idat)?;
+        let bits_per_pixel = channels * bit_depth as usize;
+
+        // The header alone could claim gigapixels; only allocate once the image data is really
+        // there (a filter byte plus the packed samples for every row of every pass)
+        let passes: &[(usize, usize, usize, usize)] = if interlaced { &ADAM7 } else { &[(0, 0, 1, 1)] };
+        let filtered_size: u64 = passes
+            .iter()
+            .map(|&(x0, y0, dx, dy)| {
+                let pass_width = (width as u64).saturating_sub(x0 as u64).div_ceil(dx as u64);
+                let pass_height = (height as u64).saturating_sub(y0 as u64).div_ceil(dy as u64);
+                if pass_width == 0 { 0 } else { pass_height * (1 + (pass_width * bits_per_pixel as u64).div_ceil(8)) }
+            })
+            .sum();
+        if (raw.len() as u64) < filtered_size {
+            return Err("PNG image data is shorter than its dimensions require".to_string());
+        }
+        let rgba_size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(4))
+            .ok_or("PNG dimensions are too large")?;
+
+        let mut decoder = SampleDecoder {
+            color_type,
+            bit_depth,
+            palette,
+            transparency,
+            pixels: vec![0u8; rgba_size],
+        };
+
+        let mut offset = 0;
+        if interlaced {
+            for &(x0, y0, dx, dy) in &ADAM7 {
+                let pass_width = (width as usize).saturating_sub(x0).div_ceil(dx);
+                let pass_height = (height as usize).saturating_sub(y0).div_ceil(dy);
+                if pass_width == 0 || pass_height == 0 {
+                    continue;
+                }
+                let rows = unfilter(&raw[offset.min(raw.len())..], pass_width, pass_height, bits_per_pixel)?;
+                offset += pass_height * (1 + (pass_width * bits_per_pixel).div_ceil(8));
+                for (row_idx, row) in rows.iter().enumerate() {
+                    decoder.write_row(row, pass_width, |i| (y0 + row_idx * dy) * width as usize + x0 + i * dx);
+                }
+            }
+        } else {
+            let rows = unfilter(&raw, width as usize, height as usize, bits_per_pixel)?;
+            for (y, row) in rows.iter().enumerate() {
+                decoder.write_row(row, width as usize, |i| y * width as usize + i);
+            }
+        }
+
+        Ok(PngImage {
+            width,
+            height,
+            pixels: decoder.pixels,
+            bit_depth,
+            color_type,
+            interlaced,
+        })
+    }
+}
+
+// Expands unfiltered scanlines of any color type / depth into RGBA8
+struct SampleDecoder {
+    color_type: u8,
+    bit_depth: u8,
+    palette: Vec<[u8; 4]>,
+    transparency: Vec<u8>,
+    pixels: Vec<u8>,
+}
+
+impl SampleDecoder {
+    // Sample n of a row, reduced to 8 bits (16-bit keeps the high byte, low depths are rescaled)
+    fn sample(&self, row: &[u8], n: usize, scale: bool) -> (u8, u16) {
+        match self.bit_depth {
+            16 => {
+                let v = u16::from_be_bytes([row[n * 2], row[n * 2 + 1]]);
+                ((v >> 8) as u8, v)
+            }
+            8 => (row[n], row[n] as u16),
+            depth => {
+                let per_byte = 8 / depth as usize;
+                let shift = 8 - depth as usize * (n % per_byte + 1);
+                let max = (1u16 << depth) - 1;
+                let v = (row[n / per_byte] as u16 >> shift) & max;
+                let value = if scale { (v * 255 / max) as u8 } else { v as u8 };
+                (value, v)
+            }
+        }
+    }
+
+    fn transparent_key(&self, index: usize) -> Option<u16> {
+        let bytes = self.transparency.get(index * 2..index * 2 + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn write_row(&mut self, row: &[u8], count: usize, target: impl Fn(usize) -> usize) {
+        for i in 0..count {
+            let rgba = match self.color_type {
+                0 => {
+                    let (g, raw) = self.sample(row, i, true);
+                    let alpha = if self.transparent_key(0) == Some(raw) { 0 } else { 255 };
+                    [g, g, g, alpha]
+                }
+                2 => {
+                    let (r, rr) = self.sample(row, i * 3, true);
+                    let (g, rg) = self.sample(row, i * 3 + 1, true);
+                    let (b, rb) = self.sample(row, i * 3 + 2, true);
+                    let keyed = self.transparent_key(0) == Some(rr)
+                        && self.transparent_key(1) == Some(rg)
+                        && self.transparent_key(2) == Some(rb);
+                    [r, g, b, if keyed { 0 } else { 255 }]
+                }
+                3 => {
+                    let (index, _) = self.sample(row, i, false);
+                    self.palette.get(index as usize).copied().unwrap_or([0, 0, 0, 255])
+                }
+                4 => {
+                    let (g, _) = self.sample(row, i * 2, true);
+                    let (a, _) = self.sample(row, i * 2 + 1, true);
+                    [g, g, g, a]
+                }
+                _ => {
+                    let (r, _) = self.sample(row, i * 4, true);
+                    let (g, _) = self.sample(row, i * 4 + 1, true);
+                    let (b, _) = self.sample(row, i * 4 + 2, true);
+                    let (a, _) = self.sample(row, i * 4 + 3, true);
+                    [r, g, b, a]
+                }
+            };
+            let idx = target(i) * 4;
+            self.pixels[idx..idx + 4].copy_from_slice(&rgba);
+        }
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Undo per-scanline filters; bpp is the byte distance to the corresponding byte of the previous pixel
+fn unfilter(raw: &[u8], width: usize, height: usize, bits_per_pixel: usize) -> Result<Vec<Vec<u8>>, String> {
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let bpp = bits_per_pixel.div_ceil(8).max(1);
+    if raw.len() < height * (stride + 1) {
+        return Err("PNG image data is truncated".to_string());
+    }
+
+    let mut rows: Vec<Vec<u8>> = Vec::with_capacity(height);
+    let mut previous = vec![0u8; stride];
+    for y in 0..height {
+        let start = y * (stride + 1);
+        let filter = raw[start];
+        let mut row = raw[start + 1..start + 1 + stride].to_vec();
+
+        for i in 0..stride {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = previous[i];
+            let c = if i >= bpp { previous[i - bpp] } else { 0 };
+            row[i] = match filter {
+                0 => row[i],
+                1 => row[i].wrapping_add(a),
+                2 => row[i].wrapping_add(b),
+                3 => row[i].wrapping_add(((a as u16 + b as u16) / 2) as u8),
+                4 => row[i].wrapping_add(paeth(a, b, c)),
+                _ => return Err(format!("Invalid PNG filter type {}", filter)),
+            };
+        }
+
+        previous.clone_from(&row);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn filter_row(filter: u8, row: &[u8], previous: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    out.push(filter);
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = previous[i];
+        let c = if i >= bpp { previous[i - bpp] } else { 0 };
+        out.push(match filter {
+            0 => row[i],
+            1 => row[i].wrapping_sub(a),
+            2 => row[i].wrapping_sub(b),
+            3 => row[i].wrapping_sub(((a as u16 + b as u16) / 2) as u8),
+            _ => row[i].wrapping_sub(paeth(a, b, c)),
+        });
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Encode RGBA8 as PNG. filter is "none", "sub", "up", "average", "paeth" or "adaptive"
+// (per-row minimum sum of absolute differences); level is the deflate level 0-9.
+// Fully opaque images are written as RGB to save a channel.
+pub fn encode_png_rgba(data: &[u8], width: u32, height: u32, level: u32, filter: &str) -> Result<Vec<u8>, String> {
+    if width == 0 || height == 0 || data.len() != (width * height * 4) as usize {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    let fixed_filter = match filter {
+        "none" => Some(0u8),
+        "sub" => Some(1),
+        "up" => Some(2),
+        "average" => Some(3),
+        "paeth" => Some(4),
+        "adaptive" => None,
+        _ => return Err(format!("Unknown PNG filter: {}", filter)),
+    };
+
+    let opaque = data.chunks_exact(4).all(|p| p[3] == 255);
+    let channels = if opaque { 3 } else { 4 };
+    let stride = width as usize * channels;
+
+    let mut filtered = Vec::with_capacity((stride + 1) * height as usize);
+    let mut previous = vec![0u8; stride];
+    let mut row = Vec::with_capacity(stride);
+    let mut candidate = Vec::with_capacity(stride + 1);
+    for y in 0..height as usize {
+        row.clear();
+        for px in data[y * width as usize * 4..(y + 1) * width as usize * 4].chunks_exact(4) {
+            row.extend_from_slice(&px[..channels]);
+        }
+
+        match fixed_filter {
+            Some(f) => filter_row(f, &row, &previous, channels, &mut filtered),
+            None => {
+                // Score each filter by treating output bytes as signed and summing magnitudes
+                let mut best = (u64::MAX, 0u8);
+                for f in 0..5u8 {
+                    candidate.clear();
+                    filter_row(f, &row, &previous, channels, &mut candidate);
+                    let score: u64 = candidate[1..].iter().map(|&b| (b as i8).unsigned_abs() as u64).sum();
+                    if score < best.0 {
+                        best = (score, f);
+                    }
+                }
+                filter_row(best.1, &row, &previous, channels, &mut filtered);
+            }
+        }
+        previous.clone_from(&row);
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, if opaque { 2 } else { 6 }, 0, 0, 0]);
+
+    let mut out = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(&mut out, b"IDAT", &zlib_compress_bytes(&filtered, level));
+    write_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+#[wasm_bindgen]
+pub fn decode_png(data: &[u8]) -> Result<PngImage, JsValue> {
+    PngImage::decode(data).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn encode_png(data: &[u8], width: u32, height: u32, level: u32, filter: &str) -> Result<Vec<u8>, JsValue> {
+    encode_png_rgba(data, width, height, level, filter).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    fn reference_encode(raw: &[u8], width: u32, height: u32, color: ColorType, depth: BitDepth) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = Encoder::new(&mut out, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        encoder.write_header().unwrap().write_image_data(raw).unwrap();
+        out
+    }
+
+    // The png crate's decode, expanded to the RGBA8 our decoder produces
+    fn reference_decode(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut decoder = Decoder::new(bytes);
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16 | Transformations::ALPHA);
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        let rgba = match info.color_type {
+            ColorType::Rgba => buf,
+            ColorType::GrayscaleAlpha => buf.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            other => panic!("unexpected output color type {other:?}"),
+        };
+        (info.width, info.height, rgba)
+    }
+
+    #[test]
+    fn decode_matches_png_crate() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let cases = [
+            (ColorType::Rgba, BitDepth::Eight),
+            (ColorType::Rgb, BitDepth::Eight),
+            (ColorType::Grayscale, BitDepth::One),
+            (ColorType::Grayscale, BitDepth::Eight),
+            (ColorType::GrayscaleAlpha, BitDepth::Eight),
+            (ColorType::Rgba, BitDepth::Sixteen),
+        ];
+        for (width, height) in [(1u32, 1u32), (3, 5), (17, 9), (33, 31)] {
+            for (color, depth) in cases {
+                let stride = (width as usize * color.samples() * depth as usize).div_ceil(8);
+                let raw: Vec<u8> = (0..stride * height as usize).map(|_| rng.gen()).collect();
+                let bytes = reference_encode(&raw, width, height, color, depth);
+
+                let ours = PngImage::decode(&bytes).unwrap();
+                let (ref_width, ref_height, ref_pixels) = reference_decode(&bytes);
+                assert_eq!((ours.get_width(), ours.get_height()), (ref_width, ref_height));
+                assert_eq!(ours.get_pixels(), ref_pixels, "{color:?} {depth:?} {width}x{height}");
+            }
+        }
+    }
+
+    #[test]
+    fn png_crate_decodes_our_output() {
+        let mut rng = SmallRng::seed_from_u64(9);
+        for (width, height) in [(1u32, 1u32), (7, 3), (64, 40)] {
+            for opaque in [true, false] {
+                let data: Vec<u8> = (0..width * height)
+                    .flat_map(|i| [(i % 256) as u8, rng.gen::<u8>() & 0xF0, 77, if opaque { 255 } else { rng.gen() }])
+                    .collect();
+                for filter in ["none", "sub", "up", "average", "paeth", "adaptive"] {
+                    for level in [0, 6, 9] {
+                        let bytes = encode_png_rgba(&data, width, height, level, filter).unwrap();
+                        assert_eq!(reference_decode(&bytes), (width, height, data.clone()), "{filter} level {level}");
+                        assert_eq!(PngImage::decode(&bytes).unwrap().get_pixels(), data);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_bad_crc_and_signature() {
+        let bytes = encode_png_rgba(&[1, 2, 3, 255], 1, 1, 6, "none").unwrap();
+        let mut bad_crc = bytes.clone();
+        bad_crc[20] ^= 1;
+        assert!(PngImage::decode(&bad_crc).is_err());
+        assert!(PngImage::decode(&bytes[1..]).is_err());
+        assert!(PngImage::decode(&bytes[..30]).is_err());
+        assert!(encode_png_rgba(&[0; 3], 1, 1, 6, "none").is_err());
+    }
+}
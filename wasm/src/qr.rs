@@ -0,0 +1,635 @@
+use crate::ecc::{rs_remainder, Gf256};
+use wasm_bindgen::prelude::*;
+
+// ECC codewords per block and number of blocks, indexed [level L/M/Q/H][version]
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+const ALPHANUMERIC_CHARSET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+#[derive(Clone, Copy, PartialEq)]
+enum QrMode {
+    Numeric,
+    Alphanumeric,
+    Byte,
+}
+
+impl QrMode {
+    fn indicator(self) -> u32 {
+        match self {
+            QrMode::Numeric => 0x1,
+            QrMode::Alphanumeric => 0x2,
+            QrMode::Byte => 0x4,
+        }
+    }
+
+    fn char_count_bits(self, version: usize) -> usize {
+        let range = match version {
+            1..=9 => 0,
+            10..=26 => 1,
+            _ => 2,
+        };
+        match self {
+            QrMode::Numeric => [10, 12, 14][range],
+            QrMode::Alphanumeric => [9, 11, 13][range],
+            QrMode::Byte => [8, 16, 16][range],
+        }
+    }
+
+    fn data_bits(self, len: usize) -> usize {
+        match self {
+            QrMode::Numeric => len / 3 * 10 + [0, 4, 7][len % 3],
+            QrMode::Alphanumeric => len / 2 * 11 + (len % 2) * 6,
+            QrMode::Byte => len * 8,
+        }
+    }
+}
+
+fn parse_ecc_level(level: &str) -> Result<usize, String> {
+    match level.to_ascii_uppercase().as_str() {
+        "L" => Ok(0),
+        "M" => Ok(1),
+        "Q" => Ok(2),
+        "H" => Ok(3),
+        _ => Err(format!("Unknown QR error correction level: {}", level)),
+    }
+}
+
+// Modules available for data + ECC once function patterns are removed
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize, level: usize) -> usize {
+    num_raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[level][version] as usize * NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+
+    let size = version * 4 + 17;
+    let num_align = version / 7 + 2;
+    let step = (version * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+    let mut positions: Vec<usize> = (0..num_align - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+struct BitBuffer {
+    bits: Vec<bool>,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            self.bits.push((value >> i) & 1 != 0);
+        }
+    }
+}
+
+struct QrMatrix {
+    size: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl QrMatrix {
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize, level: usize) {
+        let size = self.size;
+
+        // Timing patterns
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        // Finder patterns with separators
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let x = cx as i32 + dx;
+                    let y = cy as i32 + dy;
+                    if x >= 0 && y >= 0 && (x as usize) < size && (y as usize) < size {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+
+        // Alignment patterns, skipping the three that would overlap finders
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &ax) in positions.iter().enumerate() {
+            for (j, &ay) in positions.iter().enumerate() {
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function((ax as i32 + dx) as usize, (ay as i32 + dy) as usize, dark);
+                    }
+                }
+            }
+        }
+
+        // Reserve format areas (real bits are written once the mask is known)
+        self.draw_format_bits(level, 0);
+
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | rem;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let a = size - 11 + i % 3;
+                let b = i / 3;
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, level: usize, mask: usize) {
+        let size = self.size;
+        let level_bits = [1u32, 0, 3, 2][level];
+        let data = level_bits << 3 | mask as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+
+        // First copy around the top-left finder
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // Second copy split between the other two finders
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    // Zig-zag placement of codeword bits in two-column strips from the bottom-right
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total_bits = codewords.len() * 8;
+        let mut i = 0usize;
+        let mut right = size as i32 - 1;
+
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.is_function[y * size + x] && i < total_bits {
+                        self.modules[y * size + x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let idx = y * self.size + x;
+                if invert && !self.is_function[idx] {
+                    self.modules[idx] = !self.modules[idx];
+                }
+            }
+        }
+    }
+
+    // Standard mask penalty: long runs, 2x2 blocks, finder-like patterns and dark balance
+    fn penalty_score(&self) -> u32 {
+        let size = self.size;
+        let mut penalty = 0u32;
+
+        for horizontal in [true, false] {
+            for line in 0..size {
+                let at = |i: usize| if horizontal { self.get(i, line) } else { self.get(line, i) };
+
+                let mut run = 1;
+                for i in 1..size {
+                    if at(i) == at(i - 1) {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            penalty += 3 + (run - 5);
+                        }
+                        run = 1;
+                    }
+                }
+                if run >= 5 {
+                    penalty += 3 + (run - 5);
+                }
+
+                // Finder-like 1:1:3:1:1 runs with four light modules on either side, where the
+                // quiet zone counts as light. Runs alternate light/dark, starting with light.
+                let mut runs = vec![size];
+                for i in 0..size {
+                    if at(i) == (runs.len() % 2 == 0) {
+                        *runs.last_mut().unwrap() += 1;
+                    } else {
+                        runs.push(1);
+                    }
+                }
+                if runs.len() % 2 == 0 {
+                    runs.push(0);
+                }
+                *runs.last_mut().unwrap() += size;
+                for window in runs.windows(7).step_by(2) {
+                    let n = window[1];
+                    if window[2] == n && window[3] == n * 3 && window[4] == n && window[5] == n {
+                        let before = window[0] >= n * 4 && window[6] >= n;
+                        let after = window[6] >= n * 4 && window[0] >= n;
+                        penalty += 40 * (before as u32 + after as u32);
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.get(x, y);
+                if c == self.get(x + 1, y) && c == self.get(x, y + 1) && c == self.get(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = (size * size) as i64;
+        let dark = self.modules.iter().filter(|&&m| m).count() as i64;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        penalty + k as u32 * 10
+    }
+}
+
+// Split data into blocks, append Reed-Solomon parity and interleave
+fn add_ecc_and_interleave(data: &[u8], version: usize, level: usize) -> Vec<u8> {
+    let gf = Gf256::new();
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize;
+    let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[level][version] as usize;
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let mut blocks = Vec::with_capacity(num_blocks);
+    let mut k = 0;
+    for i in 0..num_blocks {
+        let data_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        let mut block = data[k..k + data_len].to_vec();
+        k += data_len;
+        let ecc = rs_remainder(&gf, &block, block_ecc_len);
+        // Short blocks get a placeholder byte so every block has the same layout
+        if i < num_short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+#[wasm_bindgen]
+pub struct QrCode {
+    version: u32,
+    size: u32,
+    mask: u32,
+    ecc_level: String,
+    modules: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl QrCode {
+    #[wasm_bindgen]
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    #[wasm_bindgen]
+    pub fn get_size(&self) -> u32 {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_mask(&self) -> u32 {
+        self.mask
+    }
+
+    #[wasm_bindgen]
+    pub fn get_ecc_level(&self) -> String {
+        self.ecc_level.clone()
+    }
+
+    // size * size modules, row-major, 1 = dark
+    #[wasm_bindgen]
+    pub fn get_modules(&self) -> Vec<u8> {
+        self.modules.clone()
+    }
+
+    // Pixel width/height of to_rgba for the same scale and quiet-zone border
+    #[wasm_bindgen]
+    pub fn get_image_size(&self, scale: u32, border: u32) -> u32 {
+        (self.size + border * 2) * scale.max(1)
+    }
+
+    // Render as an RGBA buffer (black on white) ready for ImageData or the filter functions
+    #[wasm_bindgen]
+    pub fn to_rgba(&self, scale: u32, border: u32) -> Vec<u8> {
+        let scale = scale.max(1);
+        let image_size = self.get_image_size(scale, border) as usize;
+        let mut data = vec![255u8; image_size * image_size * 4];
+
+        for y in 0..self.size as usize {
+            for x in 0..self.size as usize {
+                if self.modules[y * self.size as usize + x] == 0 {
+                    continue;
+                }
+                for py in 0..scale as usize {
+                    let row = (y + border as usize) * scale as usize + py;
+                    for px in 0..scale as usize {
+                        let col = (x + border as usize) * scale as usize + px;
+                        let idx = (row * image_size + col) * 4;
+                        data[idx] = 0;
+                        data[idx + 1] = 0;
+                        data[idx + 2] = 0;
+                    }
+                }
+            }
+        }
+
+        data
+    }
+}
+
+fn encode_segment(data: &[u8], mode: QrMode, version: usize, buffer: &mut BitBuffer) {
+    buffer.push(mode.indicator(), 4);
+    buffer.push(data.len() as u32, mode.char_count_bits(version));
+
+    match mode {
+        QrMode::Numeric => {
+            for chunk in data.chunks(3) {
+                let value = chunk.iter().fold(0u32, |acc, &d| acc * 10 + (d - b'0') as u32);
+                buffer.push(value, chunk.len() * 3 + 1);
+            }
+        }
+        QrMode::Alphanumeric => {
+            let index = |c: u8| ALPHANUMERIC_CHARSET.bytes().position(|a| a == c).unwrap_or(0) as u32;
+            for chunk in data.chunks(2) {
+                if chunk.len() == 2 {
+                    buffer.push(index(chunk[0]) * 45 + index(chunk[1]), 11);
+                } else {
+                    buffer.push(index(chunk[0]), 6);
+                }
+            }
+        }
+        QrMode::Byte => {
+            for &byte in data {
+                buffer.push(byte as u32, 8);
+            }
+        }
+    }
+}
+
+fn encode_qr(data: &[u8], mode: QrMode, level: usize, version: u32, mask: i32) -> Result<QrCode, String> {
+    if version > 40 {
+        return Err("QR version must be 0 (auto) or 1-40".to_string());
+    }
+    if !(-1..8).contains(&mask) {
+        return Err(format!("QR mask must be -1 (auto) or 0-7, got {}", mask));
+    }
+
+    let fits = |v: usize| 4 + mode.char_count_bits(v) + mode.data_bits(data.len()) <= num_data_codewords(v, level) * 8;
+    let version = if version == 0 {
+        (1..=40).find(|&v| fits(v)).ok_or("Data too long for a QR code")?
+    } else if fits(version as usize) {
+        version as usize
+    } else {
+        return Err(format!("Data does not fit in QR version {}", version));
+    };
+
+    // Segment, terminator, byte alignment, then alternating pad bytes
+    let capacity_bits = num_data_codewords(version, level) * 8;
+    let mut buffer = BitBuffer { bits: Vec::with_capacity(capacity_bits) };
+    encode_segment(data, mode, version, &mut buffer);
+    let terminator = (capacity_bits - buffer.bits.len()).min(4);
+    buffer.push(0, terminator);
+    buffer.push(0, (8 - buffer.bits.len() % 8) % 8);
+    let mut pad = 0xEC;
+    while buffer.bits.len() < capacity_bits {
+        buffer.push(pad, 8);
+        pad ^= 0xEC ^ 0x11;
+    }
+
+    let codewords: Vec<u8> = buffer
+        .bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, &b| (acc << 1) | b as u8))
+        .collect();
+    let all_codewords = add_ecc_and_interleave(&codewords, version, level);
+
+    let size = version * 4 + 17;
+    let mut matrix = QrMatrix {
+        size,
+        modules: vec![false; size * size],
+        is_function: vec![false; size * size],
+    };
+    matrix.draw_function_patterns(version, level);
+    matrix.draw_codewords(&all_codewords);
+
+    // Try every mask unless one was requested; XOR-ing twice undoes a mask
+    let mask = if mask >= 0 {
+        mask as usize
+    } else {
+        let mut best = (u32::MAX, 0);
+        for candidate in 0..8 {
+            matrix.apply_mask(candidate);
+            matrix.draw_format_bits(level, candidate);
+            let score = matrix.penalty_score();
+            if score < best.0 {
+                best = (score, candidate);
+            }
+            matrix.apply_mask(candidate);
+        }
+        best.1
+    };
+    matrix.apply_mask(mask);
+    matrix.draw_format_bits(level, mask);
+
+    Ok(QrCode {
+        version: version as u32,
+        size: size as u32,
+        mask: mask as u32,
+        ecc_level: ["L", "M", "Q", "H"][level].to_string(),
+        modules: matrix.modules.iter().map(|&m| m as u8).collect(),
+    })
+}
+
+pub(crate) fn encode_text(text: &str, ecc_level: &str, mode: &str, version: u32, mask: i32) -> Result<QrCode, String> {
+    let level = parse_ecc_level(ecc_level)?;
+    let bytes = text.as_bytes();
+    let is_numeric = bytes.iter().all(|b| b.is_ascii_digit());
+    let is_alphanumeric = bytes.iter().all(|&b| ALPHANUMERIC_CHARSET.as_bytes().contains(&b));
+
+    let mode = match mode {
+        "auto" | "" => {
+            if is_numeric {
+                QrMode::Numeric
+            } else if is_alphanumeric {
+                QrMode::Alphanumeric
+            } else {
+                QrMode::Byte
+            }
+        }
+        "numeric" if is_numeric => QrMode::Numeric,
+        "alphanumeric" if is_alphanumeric => QrMode::Alphanumeric,
+        "byte" => QrMode::Byte,
+        "numeric" | "alphanumeric" => {
+            return Err(format!("Text cannot be encoded in {} mode", mode));
+        }
+        _ => return Err(format!("Unknown QR mode: {}", mode)),
+    };
+
+    encode_qr(bytes, mode, level, version, mask)
+}
+
+// Encode text. mode is "auto", "numeric", "alphanumeric" or "byte"; version 0 picks the
+// smallest that fits; mask -1 selects the lowest-penalty mask.
+#[wasm_bindgen]
+pub fn qr_encode(text: &str, ecc_level: &str, mode: &str, version: u32, mask: i32) -> Result<QrCode, JsValue> {
+    encode_text(text, ecc_level, mode, version, mask).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn qr_encode_bytes(data: &[u8], ecc_level: &str, version: u32, mask: i32) -> Result<QrCode, JsValue> {
+    parse_ecc_level(ecc_level)
+        .and_then(|level| encode_qr(data, QrMode::Byte, level, version, mask))
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+// One-call helper for the crypto demo: auto mode/version/mask, rendered straight to RGBA
+#[wasm_bindgen]
+pub fn qr_to_rgba(text: &str, ecc_level: &str, scale: u32, border: u32) -> Result<Vec<u8>, JsValue> {
+    let code = encode_text(text, ecc_level, "auto", 0, -1).map_err(|e| JsValue::from_str(&e))?;
+    Ok(code.to_rgba(scale, border))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qrcodegen::{Mask, QrCodeEcc, QrSegment, Version};
+
+    // qrcodegen with ECC boosting off, so both sides stay at the requested level
+    fn reference(text: &str, level: usize, mask: i32) -> qrcodegen::QrCode {
+        let ecc = [QrCodeEcc::Low, QrCodeEcc::Medium, QrCodeEcc::Quartile, QrCodeEcc::High][level];
+        let mask = (mask >= 0).then(|| Mask::new(mask as u8));
+        let segments = QrSegment::make_segments(text);
+        qrcodegen::QrCode::encode_segments_advanced(&segments, ecc, Version::MIN, Version::MAX, mask, false).unwrap()
+    }
+
+    #[test]
+    fn matches_qrcodegen() {
+        let long_text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let texts = ["0123456789", "HELLO WORLD $%*+-./:", "https://example.com/?q=qr", "h\u{e9}llo \u{1f600}", long_text.as_str()];
+        for text in texts {
+            for level in 0..4 {
+                for mask in [-1, 0, 3, 7] {
+                    let ours = encode_text(text, ["L", "M", "Q", "H"][level], "auto", 0, mask).unwrap();
+                    let theirs = reference(text, level, mask);
+                    let label = format!("{text:.20?} level {level} mask {mask}");
+                    assert_eq!(ours.get_version(), theirs.version().value() as u32, "{label}");
+                    assert_eq!(ours.get_mask(), theirs.mask().value() as u32, "{label}");
+                    let size = theirs.size();
+                    let expected: Vec<u8> = (0..size).flat_map(|y| (0..size).map(move |x| (y, x))).map(|(y, x)| theirs.get_module(x, y) as u8).collect();
+                    assert_eq!(ours.get_modules(), expected, "{label}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        for mask in [-2, 8, i32::MAX] {
+            let err = encode_text("HELLO", "M", "auto", 0, mask).err().unwrap();
+            assert!(err.contains("mask"), "{err}");
+        }
+        assert!(encode_text("HELLO", "M", "auto", 41, -1).is_err());
+        assert!(encode_text("HELLO", "X", "auto", 0, -1).is_err());
+        assert!(encode_text("hello", "M", "alphanumeric", 0, -1).is_err());
+        assert!(encode_text(&"9".repeat(8000), "L", "auto", 0, -1).is_err());
+    }
+}
@@ -0,0 +1,653 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+type Rgb = [f32; 3];
+
+// A color with the number of pixels it stands for
+type Weighted = (Rgb, u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum QuantizeMethod {
+    MedianCut,
+    KMeans,
+    Octree,
+}
+
+impl QuantizeMethod {
+    pub(crate) fn from_name(name: &str) -> Option<QuantizeMethod> {
+        match name {
+            "median_cut" => Some(QuantizeMethod::MedianCut),
+            "kmeans" => Some(QuantizeMethod::KMeans),
+            "octree" => Some(QuantizeMethod::Octree),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Dither {
+    None,
+    FloydSteinberg,
+    Atkinson,
+    Bayer(usize),
+    BlueNoise,
+}
+
+impl Dither {
+    pub(crate) fn from_name(name: &str) -> Option<Dither> {
+        match name {
+            "none" => Some(Dither::None),
+            "floyd_steinberg" => Some(Dither::FloydSteinberg),
+            "atkinson" => Some(Dither::Atkinson),
+            "bayer2" => Some(Dither::Bayer(2)),
+            "bayer4" => Some(Dither::Bayer(4)),
+            "bayer8" => Some(Dither::Bayer(8)),
+            "blue_noise" => Some(Dither::BlueNoise),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse_options(method: &str, dither: &str) -> Result<(QuantizeMethod, Dither), String> {
+    let method = QuantizeMethod::from_name(method)
+        .ok_or_else(|| format!("Unknown quantizer: {} (expected median_cut, kmeans or octree)", method))?;
+    Ok((method, parse_dither(dither)?))
+}
+
+pub(crate) fn parse_dither(dither: &str) -> Result<Dither, String> {
+    Dither::from_name(dither).ok_or_else(|| {
+        format!("Unknown dither mode: {} (expected none, floyd_steinberg, atkinson, bayer2, bayer4, bayer8 or blue_noise)", dither)
+    })
+}
+
+fn check_size(data: &[u8], width: u32, height: u32) -> Result<(), String> {
+    if data.len() != (width * height * 4) as usize {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    Ok(())
+}
+
+fn distance2(a: Rgb, b: Rgb) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// Distinct colors with pixel counts, in a fixed order. Fully transparent pixels don't vote
+// unless the whole image is transparent.
+fn color_counts(data: &[u8]) -> Vec<Weighted> {
+    let mut counts: HashMap<u32, u32> = HashMap::new();
+    for p in data.chunks_exact(4).filter(|p| p[3] > 0) {
+        *counts.entry(u32::from_be_bytes([0, p[0], p[1], p[2]])).or_insert(0) += 1;
+    }
+    if counts.is_empty() {
+        for p in data.chunks_exact(4) {
+            *counts.entry(u32::from_be_bytes([0, p[0], p[1], p[2]])).or_insert(0) += 1;
+        }
+    }
+    let mut keys: Vec<(u32, u32)> = counts.into_iter().collect();
+    keys.sort_unstable();
+    keys.into_iter()
+        .map(|(key, count)| {
+            let [_, r, g, b] = key.to_be_bytes();
+            ([r as f32, g as f32, b as f32], count)
+        })
+        .collect()
+}
+
+fn weighted_mean(colors: &[Weighted]) -> Rgb {
+    let mut sum = [0f64; 3];
+    let mut total = 0f64;
+    for (color, count) in colors {
+        for c in 0..3 {
+            sum[c] += color[c] as f64 * *count as f64;
+        }
+        total += *count as f64;
+    }
+    sum.map(|s| (s / total.max(1.0)) as f32)
+}
+
+// Heckbert's median cut: keep splitting the box that is most populous times widest, at the
+// pixel median of its longest side
+fn median_cut(colors: &[Weighted], k: usize) -> Vec<Rgb> {
+    let mut boxes: Vec<Vec<Weighted>> = vec![colors.to_vec()];
+    while boxes.len() < k {
+        let extent = |b: &[Weighted]| -> (usize, f32) {
+            (0..3)
+                .map(|c| {
+                    let (lo, hi) = b.iter().fold((f32::MAX, f32::MIN), |(lo, hi), (v, _)| (lo.min(v[c]), hi.max(v[c])));
+                    (c, hi - lo)
+                })
+                .fold((0, -1.0), |best, e| if e.1 > best.1 { e } else { best })
+        };
+        let best = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let population: u64 = b.iter().map(|(_, n)| *n as u64).sum();
+                (i, extent(b).1 as f64 * population as f64)
+            })
+            .fold(None, |best: Option<(usize, f64)>, e| match best {
+                Some(b) if b.1 >= e.1 => Some(b),
+                _ => Some(e),
+            });
+        let Some((index, _)) = best else { break };
+
+        let mut cell = boxes.swap_remove(index);
+        let (channel, _) = extent(&cell);
+        cell.sort_by(|a, b| a.0[channel].total_cmp(&b.0[channel]));
+        let half: u64 = cell.iter().map(|(_, n)| *n as u64).sum::<u64>() / 2;
+        let mut running = 0u64;
+        let mut split = 1;
+        for (i, (_, n)) in cell.iter().enumerate() {
+            running += *n as u64;
+            if running >= half {
+                split = (i + 1).clamp(1, cell.len() - 1);
+                break;
+            }
+        }
+        let upper = cell.split_off(split);
+        boxes.push(cell);
+        boxes.push(upper);
+    }
+    boxes.iter().map(|b| weighted_mean(b)).collect()
+}
+
+// Merge colors into 5-bit-per-channel cells, keeping each cell's mean, so k-means iterations
+// cost the same however many distinct colors the image has
+fn bin_colors(colors: &[Weighted]) -> Vec<Weighted> {
+    let mut cells: HashMap<u32, ([f64; 3], u32)> = HashMap::new();
+    for (color, count) in colors {
+        let key = color.iter().fold(0u32, |k, &v| (k << 5) | (v as u32 >> 3));
+        let cell = cells.entry(key).or_insert(([0.0; 3], 0));
+        for (sum, &v) in cell.0.iter_mut().zip(color) {
+            *sum += v as f64 * *count as f64;
+        }
+        cell.1 += count;
+    }
+    let mut keys: Vec<_> = cells.into_iter().collect();
+    keys.sort_unstable_by_key(|(key, _)| *key);
+    keys.into_iter()
+        .map(|(_, (sum, count))| (sum.map(|s| (s / count as f64) as f32), count))
+        .collect()
+}
+
+fn nearest(palette: &[Rgb], color: Rgb) -> usize {
+    let mut best = (0, f32::MAX);
+    for (i, &p) in palette.iter().enumerate() {
+        let d = distance2(p, color);
+        if d < best.1 {
+            best = (i, d);
+        }
+    }
+    best.0
+}
+
+const KMEANS_ITERATIONS: usize = 24;
+
+// Lloyd's k-means with k-means++ seeding; returns the centres and the pixels in each cluster
+fn kmeans(colors: &[Weighted], k: usize, seed: u32) -> (Vec<Rgb>, Vec<u64>) {
+    let samples = bin_colors(colors);
+    let k = k.min(samples.len());
+    if k == 0 {
+        return (Vec::new(), Vec::new());
+    }
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let weighted_pick = |rng: &mut SmallRng, weights: &[f64]| {
+        let total: f64 = weights.iter().sum();
+        let mut target = rng.gen::<f64>() * total;
+        for (i, w) in weights.iter().enumerate() {
+            target -= w;
+            if target < 0.0 {
+                return i;
+            }
+        }
+        weights.len() - 1
+    };
+
+    let counts: Vec<f64> = samples.iter().map(|(_, n)| *n as f64).collect();
+    let mut centres = vec![samples[weighted_pick(&mut rng, &counts)].0];
+    let mut closest: Vec<f32> = samples.iter().map(|(c, _)| distance2(*c, centres[0])).collect();
+    while centres.len() < k {
+        let weights: Vec<f64> = closest.iter().zip(&counts).map(|(&d, &n)| d as f64 * n).collect();
+        if weights.iter().sum::<f64>() <= 0.0 {
+            break;
+        }
+        let centre = samples[weighted_pick(&mut rng, &weights)].0;
+        for (d, (c, _)) in closest.iter_mut().zip(&samples) {
+            *d = d.min(distance2(*c, centre));
+        }
+        centres.push(centre);
+    }
+
+    let mut population = vec![0u64; centres.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![[0f64; 3]; centres.len()];
+        population.fill(0);
+        for (color, count) in &samples {
+            let i = nearest(&centres, *color);
+            for c in 0..3 {
+                sums[i][c] += color[c] as f64 * *count as f64;
+            }
+            population[i] += *count as u64;
+        }
+        let mut moved = 0f32;
+        for (i, centre) in centres.iter_mut().enumerate() {
+            // An empty cluster keeps its old centre
+            if population[i] > 0 {
+                let next = sums[i].map(|s| (s / population[i] as f64) as f32);
+                moved = moved.max(distance2(*centre, next));
+                *centre = next;
+            }
+        }
+        if moved < 0.25 {
+            break;
+        }
+    }
+    (centres, population)
+}
+
+#[derive(Default)]
+struct OctreeNode {
+    children: [usize; 8],
+    sum: [u64; 3],
+    count: u64,
+    leaf: bool,
+}
+
+// Gervautz-Purgathofer octree: every color gets a leaf at depth 8, then the deepest nodes with
+// the fewest pixels are folded into their parents until k leaves are left
+fn octree(colors: &[Weighted], k: usize) -> Vec<Rgb> {
+    // Node 0 is the root, so 0 doubles as "no child"
+    let mut nodes = vec![OctreeNode::default()];
+    let mut levels: Vec<Vec<usize>> = vec![vec![0]; 1];
+    levels.resize(8, Vec::new());
+    let mut leaves = 0usize;
+
+    for (color, count) in colors {
+        let rgb = color.map(|v| v as u8);
+        let mut node = 0;
+        for depth in 0..8 {
+            let bit = 7 - depth;
+            let child = ((((rgb[0] >> bit) & 1) << 2) | (((rgb[1] >> bit) & 1) << 1) | ((rgb[2] >> bit) & 1)) as usize;
+            if nodes[node].children[child] == 0 {
+                nodes.push(OctreeNode { leaf: depth == 7, ..Default::default() });
+                let id = nodes.len() - 1;
+                nodes[node].children[child] = id;
+                if depth == 7 {
+                    leaves += 1;
+                } else {
+                    levels[depth + 1].push(id);
+                }
+            }
+            node = nodes[node].children[child];
+        }
+        for (sum, &v) in nodes[node].sum.iter_mut().zip(&rgb) {
+            *sum += v as u64 * *count as u64;
+        }
+        nodes[node].count += *count as u64;
+    }
+
+    // Deeper levels are folded first, so the nodes being folded only have leaf children
+    for depth in (0..8).rev() {
+        if leaves <= k {
+            break;
+        }
+        let population = |node: &OctreeNode, nodes: &[OctreeNode]| -> u64 {
+            node.children.iter().filter(|&&c| c != 0).map(|&c| nodes[c].count).sum()
+        };
+        let mut level = std::mem::take(&mut levels[depth]);
+        level.sort_by_key(|&id| std::cmp::Reverse(population(&nodes[id], &nodes)));
+        while leaves > k {
+            let Some(&id) = level.last() else { break };
+            let mut children: Vec<usize> = nodes[id].children.iter().copied().filter(|&c| c != 0).collect();
+            let excess = leaves - k;
+            let merge = |nodes: &mut [OctreeNode], into: usize, from: usize| {
+                let (sum, count) = (nodes[from].sum, nodes[from].count);
+                for (total, v) in nodes[into].sum.iter_mut().zip(sum) {
+                    *total += v;
+                }
+                nodes[into].count += count;
+            };
+            if children.len() - 1 <= excess {
+                level.pop();
+                for &child in &children {
+                    merge(&mut nodes, id, child);
+                }
+                nodes[id].children = [0; 8];
+                nodes[id].leaf = true;
+                leaves = leaves + 1 - children.len();
+            } else {
+                // Folding the whole node would leave fewer than k colors, so only merge its
+                // smallest children into one
+                children.sort_by_key(|&c| nodes[c].count);
+                let keep = children[excess];
+                for &child in &children[..excess] {
+                    merge(&mut nodes, keep, child);
+                    for slot in nodes[id].children.iter_mut().filter(|slot| **slot == child) {
+                        *slot = 0;
+                    }
+                }
+                leaves = k;
+            }
+        }
+    }
+
+    let mut palette = Vec::new();
+    let mut stack = vec![0];
+    while let Some(id) = stack.pop() {
+        let node = &nodes[id];
+        if node.leaf {
+            if node.count > 0 {
+                palette.push(node.sum.map(|s| (s as f64 / node.count as f64) as f32));
+            }
+        } else {
+            stack.extend(node.children.iter().filter(|&&c| c != 0));
+        }
+    }
+    palette
+}
+
+fn bayer_matrix(n: usize) -> Vec<f32> {
+    let mut m = vec![0u32];
+    let mut size = 1;
+    while size < n {
+        let mut next = vec![0u32; size * size * 4];
+        for y in 0..size {
+            for x in 0..size {
+                let v = m[y * size + x] * 4;
+                next[y * 2 * size + x] = v;
+                next[y * 2 * size + x + size] = v + 2;
+                next[(y + size) * 2 * size + x] = v + 3;
+                next[(y + size) * 2 * size + x + size] = v + 1;
+            }
+        }
+        m = next;
+        size *= 2;
+    }
+    m.iter().map(|&v| (v as f32 + 0.5) / (n * n) as f32 - 0.5).collect()
+}
+
+const BLUE_NOISE_SIZE: usize = 64;
+
+// A 64x64 blue-noise threshold tile made with Ulichney's void-and-cluster method; thresholds
+// are centred on zero like the Bayer ones
+fn blue_noise() -> &'static [f32] {
+    static TILE: OnceLock<Vec<f32>> = OnceLock::new();
+    TILE.get_or_init(|| {
+        let n = BLUE_NOISE_SIZE;
+        let sigma = 1.5f32;
+        // Toroidal Gaussian energy each point spreads over the tile
+        let spread: Vec<f32> = (0..n * n)
+            .map(|i| {
+                let (dx, dy) = ((i % n).min(n - i % n) as f32, (i / n).min(n - i / n) as f32);
+                (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let mut energy = vec![0f32; n * n];
+        let mut set = vec![false; n * n];
+        let toggle = |energy: &mut [f32], set: &mut [bool], at: usize, on: bool| {
+            set[at] = on;
+            let (ax, ay) = (at % n, at / n);
+            let sign = if on { 1.0 } else { -1.0 };
+            for (i, e) in energy.iter_mut().enumerate() {
+                let (dx, dy) = ((i % n + n - ax) % n, (i / n + n - ay) % n);
+                *e += sign * spread[dy * n + dx];
+            }
+        };
+        let tightest = |energy: &[f32], set: &[bool]| {
+            (0..n * n).filter(|&i| set[i]).max_by(|&a, &b| energy[a].total_cmp(&energy[b])).unwrap_or(0)
+        };
+        let largest_void = |energy: &[f32], set: &[bool]| {
+            (0..n * n).filter(|&i| !set[i]).min_by(|&a, &b| energy[a].total_cmp(&energy[b])).unwrap_or(0)
+        };
+
+        // Initial pattern: a tenth of the cells, spread out by moving clusters into voids
+        let mut rng = SmallRng::seed_from_u64(0);
+        let initial = n * n / 10;
+        let mut placed = 0;
+        while placed < initial {
+            let at = rng.gen_range(0..n * n);
+            if !set[at] {
+                toggle(&mut energy, &mut set, at, true);
+                placed += 1;
+            }
+        }
+        for _ in 0..n * n {
+            let cluster = tightest(&energy, &set);
+            toggle(&mut energy, &mut set, cluster, false);
+            let void = largest_void(&energy, &set);
+            toggle(&mut energy, &mut set, void, true);
+            if void == cluster {
+                break;
+            }
+        }
+
+        let mut rank = vec![0usize; n * n];
+        let (mut phase_energy, mut phase_set) = (energy.clone(), set.clone());
+        for r in (0..initial).rev() {
+            let cluster = tightest(&phase_energy, &phase_set);
+            toggle(&mut phase_energy, &mut phase_set, cluster, false);
+            rank[cluster] = r;
+        }
+        for r in initial..n * n {
+            let void = largest_void(&energy, &set);
+            toggle(&mut energy, &mut set, void, true);
+            rank[void] = r;
+        }
+        rank.iter().map(|&r| (r as f32 + 0.5) / (n * n) as f32 - 0.5).collect()
+    })
+}
+
+// Palette index for every pixel, with the chosen dithering
+fn remap(data: &[u8], width: usize, height: usize, palette: &[Rgb], dither: Dither) -> Vec<u8> {
+    let mut cache: HashMap<u32, u8> = HashMap::new();
+    let mut lookup = |color: Rgb| -> u8 {
+        let rgb = color.map(|v| v.round().clamp(0.0, 255.0) as u8);
+        *cache
+            .entry(u32::from_be_bytes([0, rgb[0], rgb[1], rgb[2]]))
+            .or_insert_with(|| nearest(palette, rgb.map(|v| v as f32)) as u8)
+    };
+    let pixel = |i: usize| -> Rgb { [data[i * 4] as f32, data[i * 4 + 1] as f32, data[i * 4 + 2] as f32] };
+
+    match dither {
+        Dither::None => (0..width * height).map(|i| lookup(pixel(i))).collect(),
+        Dither::Bayer(_) | Dither::BlueNoise => {
+            let (size, thresholds) = match dither {
+                Dither::Bayer(n) => (n, bayer_matrix(n)),
+                _ => (BLUE_NOISE_SIZE, blue_noise().to_vec()),
+            };
+            // Roughly the gap between neighbouring palette levels per channel
+            let levels = (palette.len() as f32).cbrt();
+            let spread = if levels > 1.0 { (255.0 / (levels - 1.0)).min(255.0) } else { 255.0 };
+            (0..width * height)
+                .map(|i| {
+                    let t = thresholds[(i / width % size) * size + i % width % size] * spread;
+                    lookup(pixel(i).map(|v| v + t))
+                })
+                .collect()
+        }
+        Dither::FloydSteinberg | Dither::Atkinson => {
+            // (dx, dy, weight) relative to a left-to-right scan
+            let (taps, divisor): (&[(i64, usize, f32)], f32) = if dither == Dither::FloydSteinberg {
+                (&[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)], 16.0)
+            } else {
+                // Atkinson only passes on 6/8 of the error, which keeps highlights and shadows clean
+                (&[(1, 0, 1.0), (2, 0, 1.0), (-1, 1, 1.0), (0, 1, 1.0), (1, 1, 1.0), (0, 2, 1.0)], 8.0)
+            };
+            let mut values: Vec<Rgb> = (0..width * height).map(pixel).collect();
+            let mut out = vec![0u8; width * height];
+            for y in 0..height {
+                // Serpentine scan, so errors don't all drift the same way
+                let reverse = y % 2 == 1;
+                for step in 0..width {
+                    let x = if reverse { width - 1 - step } else { step };
+                    let i = y * width + x;
+                    let index = lookup(values[i]);
+                    out[i] = index;
+                    let chosen = palette[index as usize];
+                    let error = [0, 1, 2].map(|c| values[i][c] - chosen[c]);
+                    for &(dx, dy, weight) in taps {
+                        let nx = x as i64 + if reverse { -dx } else { dx };
+                        let ny = y + dy;
+                        if nx < 0 || nx >= width as i64 || ny >= height {
+                            continue;
+                        }
+                        let target = &mut values[ny * width + nx as usize];
+                        for c in 0..3 {
+                            target[c] += error[c] * weight / divisor;
+                        }
+                    }
+                }
+            }
+            out
+        }
+    }
+}
+
+// Palette plus one index per pixel; alpha is carried through unquantized
+#[wasm_bindgen]
+pub struct QuantizedImage {
+    width: u32,
+    height: u32,
+    palette: Vec<[u8; 3]>,
+    indices: Vec<u8>,
+    alpha: Vec<u8>,
+}
+
+impl QuantizedImage {
+    pub(crate) fn compute(
+        data: &[u8],
+        width: u32,
+        height: u32,
+        colors: u32,
+        method: QuantizeMethod,
+        dither: Dither,
+        seed: u32,
+    ) -> Result<QuantizedImage, String> {
+        check_size(data, width, height)?;
+        let k = colors.clamp(1, 256) as usize;
+        let counts = color_counts(data);
+        let palette = if counts.len() <= k {
+            counts.iter().map(|(c, _)| *c).collect()
+        } else {
+            match method {
+                QuantizeMethod::MedianCut => median_cut(&counts, k),
+                QuantizeMethod::KMeans => kmeans(&counts, k, seed).0,
+                QuantizeMethod::Octree => octree(&counts, k),
+            }
+        };
+        Ok(QuantizedImage::with_palette(data, width, height, &palette, dither))
+    }
+
+    pub(crate) fn with_palette(data: &[u8], width: u32, height: u32, palette: &[Rgb], dither: Dither) -> QuantizedImage {
+        let indices = if palette.is_empty() { Vec::new() } else { remap(data, width as usize, height as usize, palette, dither) };
+        QuantizedImage {
+            width,
+            height,
+            palette: palette.iter().map(|c| c.map(|v| v.round().clamp(0.0, 255.0) as u8)).collect(),
+            indices,
+            alpha: data.chunks_exact(4).map(|p| p[3]).collect(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl QuantizedImage {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // Palette as flat RGB triples
+    #[wasm_bindgen]
+    pub fn get_palette(&self) -> Vec<u8> {
+        self.palette.iter().flatten().copied().collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_palette_size(&self) -> usize {
+        self.palette.len()
+    }
+
+    // One palette index per pixel, row by row
+    #[wasm_bindgen]
+    pub fn get_indices(&self) -> Vec<u8> {
+        self.indices.clone()
+    }
+
+    // Pixels using each palette entry
+    #[wasm_bindgen]
+    pub fn get_counts(&self) -> Vec<u32> {
+        let mut counts = vec![0u32; self.palette.len()];
+        for &i in &self.indices {
+            counts[i as usize] += 1;
+        }
+        counts
+    }
+
+    // The indexed image expanded back to RGBA
+    #[wasm_bindgen]
+    pub fn get_pixels(&self) -> Vec<u8> {
+        self.indices
+            .iter()
+            .zip(&self.alpha)
+            .flat_map(|(&i, &a)| {
+                let [r, g, b] = self.palette[i as usize];
+                [r, g, b, a]
+            })
+            .collect()
+    }
+}
+
+// Reduce to at most `colors` (1-256) colors. method is "median_cut", "kmeans" or "octree"
+// (seed only matters for kmeans); dither is "none", "floyd_steinberg", "atkinson", "bayer2",
+// "bayer4", "bayer8" or "blue_noise".
+#[wasm_bindgen]
+pub fn quantize(data: &[u8], width: u32, height: u32, colors: u32, method: &str, dither: &str, seed: u32) -> Result<QuantizedImage, JsValue> {
+    let (method, dither) = parse_options(method, dither).map_err(|e| JsValue::from_str(&e))?;
+    QuantizedImage::compute(data, width, height, colors, method, dither, seed).map_err(|e| JsValue::from_str(&e))
+}
+
+pub(crate) fn parse_palette(palette: &[u8]) -> Result<Vec<Rgb>, String> {
+    if palette.is_empty() || !palette.len().is_multiple_of(3) || palette.len() > 256 * 3 {
+        return Err("Palette must be 1-256 RGB triples".to_string());
+    }
+    Ok(palette.chunks_exact(3).map(|c| [c[0] as f32, c[1] as f32, c[2] as f32]).collect())
+}
+
+pub(crate) fn dither_rgba(data: &mut [u8], width: u32, height: u32, palette: &[Rgb], dither: Dither) {
+    let image = QuantizedImage::with_palette(data, width, height, palette, dither);
+    data.copy_from_slice(&image.get_pixels());
+}
+
+// Map an image onto a fixed palette (flat RGB triples, e.g. [0,0,0, 255,255,255] for 1-bit)
+#[wasm_bindgen]
+pub fn dither(data: &mut [u8], width: u32, height: u32, palette: &[u8], mode: &str) -> Result<(), JsValue> {
+    check_size(data, width, height).map_err(|e| JsValue::from_str(&e))?;
+    let palette = parse_palette(palette).map_err(|e| JsValue::from_str(&e))?;
+    let dither = parse_dither(mode).map_err(|e| JsValue::from_str(&e))?;
+    dither_rgba(data, width, height, &palette, dither);
+    Ok(())
+}
+
+// The k most representative colors (k-means), most common first, as flat RGB triples; handy
+// for deriving accent colors from an image
+#[wasm_bindgen]
+pub fn dominant_colors(data: &[u8], k: u32) -> Vec<u8> {
+    let counts = color_counts(data);
+    let (centres, population) = kmeans(&counts, k.clamp(1, 256) as usize, 0);
+    let mut order: Vec<usize> = (0..centres.len()).filter(|&i| population[i] > 0).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(population[i]));
+    order
+        .iter()
+        .flat_map(|&i| centres[i].map(|v| v.round().clamp(0.0, 255.0) as u8))
+        .collect()
+}
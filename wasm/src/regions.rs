@@ -0,0 +1,284 @@
+use crate::histogram::luma;
+use crate::identicon::hsl_to_rgb;
+use wasm_bindgen::prelude::*;
+
+fn check_size(data: &[u8], width: u32, height: u32) -> bool {
+    data.len() == (width * height * 4) as usize
+}
+
+// Union-find root with path halving
+fn find(parent: &mut [u32], mut i: u32) -> u32 {
+    while parent[i as usize] != i {
+        parent[i as usize] = parent[parent[i as usize] as usize];
+        i = parent[i as usize];
+    }
+    i
+}
+
+fn union(parent: &mut [u32], a: u32, b: u32) -> u32 {
+    let (ra, rb) = (find(parent, a), find(parent, b));
+    // Keep the smaller label as the root so the first pass stays close to raster order
+    let (root, child) = if ra < rb { (ra, rb) } else { (rb, ra) };
+    parent[child as usize] = root;
+    root
+}
+
+// Labels of the connected white (luma >= 128, visible) regions of an image: 0 is background,
+// components are numbered from 1 in raster order of their first pixel
+#[wasm_bindgen]
+pub struct Components {
+    width: u32,
+    height: u32,
+    labels: Vec<u32>,
+    areas: Vec<u32>,
+    // x0, y0, x1, y1 inclusive
+    bounds: Vec<[u32; 4]>,
+    sums: Vec<[f64; 2]>,
+}
+
+impl Components {
+    pub(crate) fn label(data: &[u8], width: u32, height: u32, connectivity: u32) -> Result<Components, String> {
+        if !check_size(data, width, height) {
+            return Err("Image data length does not match width * height * 4".to_string());
+        }
+        if connectivity != 4 && connectivity != 8 {
+            return Err("Connectivity must be 4 or 8".to_string());
+        }
+        let (w, h) = (width as usize, height as usize);
+        let foreground: Vec<bool> = data.chunks_exact(4).map(|p| p[3] > 0 && luma(p) >= 128).collect();
+
+        // First pass: provisional labels, recording equivalences with already-visited neighbours
+        let mut labels = vec![0u32; w * h];
+        let mut parent = vec![0u32];
+        for y in 0..h {
+            for x in 0..w {
+                if !foreground[y * w + x] {
+                    continue;
+                }
+                let mut neighbours = [0u32; 4];
+                neighbours[0] = if x > 0 { labels[y * w + x - 1] } else { 0 };
+                if y > 0 {
+                    let above = (y - 1) * w;
+                    neighbours[1] = labels[above + x];
+                    if connectivity == 8 {
+                        neighbours[2] = if x > 0 { labels[above + x - 1] } else { 0 };
+                        neighbours[3] = if x + 1 < w { labels[above + x + 1] } else { 0 };
+                    }
+                }
+                let mut label = 0;
+                for &n in neighbours.iter().filter(|&&n| n > 0) {
+                    label = if label == 0 { find(&mut parent, n) } else { union(&mut parent, label, n) };
+                }
+                if label == 0 {
+                    label = parent.len() as u32;
+                    parent.push(label);
+                }
+                labels[y * w + x] = label;
+            }
+        }
+
+        // Second pass: resolve to roots, renumber densely and gather stats
+        let mut renumber = vec![0u32; parent.len()];
+        let (mut areas, mut bounds, mut sums) = (Vec::new(), Vec::new(), Vec::new());
+        for y in 0..h {
+            for x in 0..w {
+                let provisional = labels[y * w + x];
+                if provisional == 0 {
+                    continue;
+                }
+                let root = find(&mut parent, provisional) as usize;
+                if renumber[root] == 0 {
+                    areas.push(0u32);
+                    bounds.push([x as u32, y as u32, x as u32, y as u32]);
+                    sums.push([0f64; 2]);
+                    renumber[root] = areas.len() as u32;
+                }
+                let label = renumber[root];
+                labels[y * w + x] = label;
+                let i = label as usize - 1;
+                areas[i] += 1;
+                let b = &mut bounds[i];
+                b[0] = b[0].min(x as u32);
+                b[2] = b[2].max(x as u32);
+                b[3] = y as u32;
+                sums[i][0] += x as f64;
+                sums[i][1] += y as f64;
+            }
+        }
+        Ok(Components { width, height, labels, areas, bounds, sums })
+    }
+}
+
+#[wasm_bindgen]
+impl Components {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_count(&self) -> u32 {
+        self.areas.len() as u32
+    }
+
+    // One label per pixel
+    #[wasm_bindgen]
+    pub fn get_labels(&self) -> Vec<u32> {
+        self.labels.clone()
+    }
+
+    // Pixel count of each component, in label order
+    #[wasm_bindgen]
+    pub fn get_areas(&self) -> Vec<u32> {
+        self.areas.clone()
+    }
+
+    // x, y, width, height of each component
+    #[wasm_bindgen]
+    pub fn get_bounding_boxes(&self) -> Vec<u32> {
+        self.bounds.iter().flat_map(|b| [b[0], b[1], b[2] - b[0] + 1, b[3] - b[1] + 1]).collect()
+    }
+
+    // Mean x, y of each component's pixels
+    #[wasm_bindgen]
+    pub fn get_centroids(&self) -> Vec<f32> {
+        self.sums
+            .iter()
+            .zip(&self.areas)
+            .flat_map(|(s, &a)| [(s[0] / a as f64) as f32, (s[1] / a as f64) as f32])
+            .collect()
+    }
+
+    // Selection mask (255 inside) for one component, for ImageProcessor::set_selection
+    #[wasm_bindgen]
+    pub fn mask(&self, label: u32) -> Vec<u8> {
+        self.labels.iter().map(|&l| if label > 0 && l == label { 255 } else { 0 }).collect()
+    }
+
+    // Each component in its own color on black, hues spaced by the golden angle
+    #[wasm_bindgen]
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.labels
+            .iter()
+            .flat_map(|&l| {
+                if l == 0 {
+                    [0, 0, 0, 255]
+                } else {
+                    let [r, g, b] = hsl_to_rgb(l as f32 * 137.508, 0.75, 0.55);
+                    [r, g, b, 255]
+                }
+            })
+            .collect()
+    }
+}
+
+// Two-pass union-find labeling of the white regions; connectivity is 4 or 8
+#[wasm_bindgen]
+pub fn label_components(data: &[u8], width: u32, height: u32, connectivity: u32) -> Result<Components, JsValue> {
+    Components::label(data, width, height, connectivity).map_err(|e| JsValue::from_str(&e))
+}
+
+// Pixels whose every RGBA channel is within tolerance of the seed pixel. Contiguous regions are
+// grown 4-connected a horizontal run at a time: fill the run around a seed, then queue one seed
+// per matching run in the rows above and below.
+pub(crate) fn similar_region(data: &[u8], width: u32, height: u32, x: u32, y: u32, tolerance: u8, contiguous: bool) -> Result<Vec<bool>, String> {
+    if !check_size(data, width, height) {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    if x >= width || y >= height {
+        return Err(format!("Seed ({}, {}) is outside the {}x{} image", x, y, width, height));
+    }
+    let (w, h) = (width as usize, height as usize);
+    let seed: [u8; 4] = data[(y as usize * w + x as usize) * 4..][..4].try_into().unwrap_or([0; 4]);
+    let matches = |i: usize| data[i * 4..i * 4 + 4].iter().zip(seed).all(|(&a, b)| a.abs_diff(b) <= tolerance);
+    if !contiguous {
+        return Ok((0..w * h).map(matches).collect());
+    }
+
+    let mut region = vec![false; w * h];
+    let mut stack = vec![(x as usize, y as usize)];
+    while let Some((sx, sy)) = stack.pop() {
+        let row = sy * w;
+        if region[row + sx] {
+            continue;
+        }
+        let (mut left, mut right) = (sx, sx);
+        while left > 0 && !region[row + left - 1] && matches(row + left - 1) {
+            left -= 1;
+        }
+        while right + 1 < w && !region[row + right + 1] && matches(row + right + 1) {
+            right += 1;
+        }
+        region[row + left..=row + right].fill(true);
+        for ny in [sy.wrapping_sub(1), sy + 1] {
+            if ny >= h {
+                continue;
+            }
+            let mut in_run = false;
+            for nx in left..=right {
+                let i = ny * w + nx;
+                let open = !region[i] && matches(i);
+                if open && !in_run {
+                    stack.push((nx, ny));
+                }
+                in_run = open;
+            }
+        }
+    }
+    Ok(region)
+}
+
+pub(crate) fn flood_fill_rgba(data: &mut [u8], width: u32, height: u32, x: u32, y: u32, color: &[u8], tolerance: u8) -> Result<u32, String> {
+    if color.len() != 4 {
+        return Err("Fill color must be 4 RGBA bytes".to_string());
+    }
+    let region = similar_region(data, width, height, x, y, tolerance, true)?;
+    let mut filled = 0;
+    for (p, _) in data.chunks_exact_mut(4).zip(&region).filter(|(_, &r)| r) {
+        p.copy_from_slice(color);
+        filled += 1;
+    }
+    Ok(filled)
+}
+
+// Fill the contiguous region around (x, y) that matches its color within tolerance; returns the
+// number of pixels painted
+#[wasm_bindgen]
+pub fn flood_fill(data: &mut [u8], width: u32, height: u32, x: u32, y: u32, color: &[u8], tolerance: u8) -> Result<u32, JsValue> {
+    flood_fill_rgba(data, width, height, x, y, color, tolerance).map_err(|e| JsValue::from_str(&e))
+}
+
+// Magic-wand selection: a mask with 255 for every pixel matching the seed within tolerance,
+// either the contiguous region around it or every similar pixel in the image
+#[wasm_bindgen]
+pub fn magic_wand(data: &[u8], width: u32, height: u32, x: u32, y: u32, tolerance: u8, contiguous: bool) -> Result<Vec<u8>, JsValue> {
+    let region = similar_region(data, width, height, x, y, tolerance, contiguous).map_err(|e| JsValue::from_str(&e))?;
+    Ok(region.iter().map(|&r| if r { 255 } else { 0 }).collect())
+}
+
+// Blend a filtered buffer back over the original through a selection mask (one byte per pixel,
+// 255 = fully filtered), so whole-image filters can be confined to a region
+pub(crate) fn restrict_rgba(original: &[u8], data: &mut [u8], mask: &[u8]) {
+    for ((p, o), &m) in data.chunks_exact_mut(4).zip(original.chunks_exact(4)).zip(mask) {
+        if m == 255 {
+            continue;
+        }
+        for (v, &before) in p.iter_mut().zip(o) {
+            *v = ((before as u32 * (255 - m as u32) + *v as u32 * m as u32 + 127) / 255) as u8;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn restrict_to_mask(original: &[u8], data: &mut [u8], mask: &[u8]) -> Result<(), JsValue> {
+    if original.len() != data.len() || mask.len() * 4 != data.len() {
+        return Err(JsValue::from_str("Original, filtered and mask buffers must cover the same pixels"));
+    }
+    restrict_rgba(original, data, mask);
+    Ok(())
+}
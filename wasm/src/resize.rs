@@ -0,0 +1,247 @@
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ResizeFilter {
+    Bilinear,
+    Bicubic,
+    Lanczos3,
+    Area,
+}
+
+impl ResizeFilter {
+    pub(crate) fn from_name(name: &str) -> Option<ResizeFilter> {
+        match name {
+            "bilinear" => Some(ResizeFilter::Bilinear),
+            "bicubic" => Some(ResizeFilter::Bicubic),
+            "lanczos3" => Some(ResizeFilter::Lanczos3),
+            "area" => Some(ResizeFilter::Area),
+            _ => None,
+        }
+    }
+
+    // Kernel radius in source pixels at 1:1 scale
+    fn support(self) -> f32 {
+        match self {
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+            ResizeFilter::Area => 0.5,
+        }
+    }
+
+    pub(crate) fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            // Catmull-Rom (a = -0.5), the usual "bicubic" in image editors
+            ResizeFilter::Bicubic => {
+                if x < 1.0 {
+                    (1.5 * x - 2.5) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Lanczos3 => {
+                if x < 1e-6 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = std::f32::consts::PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Area => {
+                if x <= 0.5 { 1.0 } else { 0.0 }
+            }
+        }
+    }
+}
+
+// Source taps for one destination pixel along an axis
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+// Per-output-pixel weights for one axis. Kernels are widened by the scale factor when
+// downscaling so every source pixel contributes; area weights are the exact overlap of the
+// destination pixel's footprint with each source pixel.
+fn contributions(src: usize, dst: usize, filter: ResizeFilter) -> Vec<Contribution> {
+    let scale = src as f32 / dst as f32;
+
+    (0..dst)
+        .map(|x| {
+            if filter == ResizeFilter::Area {
+                let left = x as f32 * scale;
+                let right = (left + scale).min(src as f32);
+                let start = left.floor() as usize;
+                let end = (right.ceil() as usize).clamp(start + 1, src);
+                let weights: Vec<f32> = (start..end)
+                    .map(|i| (right.min(i as f32 + 1.0) - left.max(i as f32)).max(0.0))
+                    .collect();
+                return normalized(start, weights);
+            }
+
+            let filter_scale = scale.max(1.0);
+            let support = filter.support() * filter_scale;
+            let center = (x as f32 + 0.5) * scale;
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(src).max(start + 1);
+            let weights: Vec<f32> = (start..end)
+                .map(|i| filter.weight((i as f32 + 0.5 - center) / filter_scale))
+                .collect();
+            normalized(start, weights)
+        })
+        .collect()
+}
+
+fn normalized(start: usize, mut weights: Vec<f32>) -> Contribution {
+    let total: f32 = weights.iter().sum();
+    if total.abs() > 1e-8 {
+        weights.iter_mut().for_each(|w| *w /= total);
+    }
+    Contribution { start, weights }
+}
+
+// Integer box reduction by (fx, fy), averaging colors weighted by alpha so transparent pixels
+// don't darken their neighbours. Boxes at the right/bottom edge may be partial. Used to bring
+// big downscales close to the target cheaply.
+fn reduce_box(data: &[u8], width: usize, height: usize, fx: usize, fy: usize) -> (Vec<u8>, usize, usize) {
+    let out_w = width.div_ceil(fx);
+    let out_h = height.div_ceil(fy);
+    let mut out = vec![0u8; out_w * out_h * 4];
+
+    for oy in 0..out_h {
+        let rows = oy * fy..((oy + 1) * fy).min(height);
+        for ox in 0..out_w {
+            let cols = ox * fx..((ox + 1) * fx).min(width);
+            let mut sums = [0u64; 4];
+            for y in rows.clone() {
+                for x in cols.clone() {
+                    let p = &data[(y * width + x) * 4..][..4];
+                    let a = p[3] as u64;
+                    sums[0] += p[0] as u64 * a;
+                    sums[1] += p[1] as u64 * a;
+                    sums[2] += p[2] as u64 * a;
+                    sums[3] += a;
+                }
+            }
+            let count = (rows.len() * cols.len()) as u64;
+            let dst = &mut out[(oy * out_w + ox) * 4..][..4];
+            for c in 0..3 {
+                dst[c] = (sums[c] + sums[3] / 2).checked_div(sums[3]).unwrap_or(0) as u8;
+            }
+            dst[3] = ((sums[3] + count / 2) / count) as u8;
+        }
+    }
+
+    (out, out_w, out_h)
+}
+
+// Resample RGBA8 with a separable filter in premultiplied alpha
+pub(crate) fn resize_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    new_width: u32,
+    new_height: u32,
+    filter: ResizeFilter,
+) -> Result<Vec<u8>, String> {
+    if data.len() != (width * height * 4) as usize {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    if width == 0 || height == 0 || new_width == 0 || new_height == 0 {
+        return Err("Image dimensions must be non-zero".to_string());
+    }
+
+    let (mut w, mut h) = (width as usize, height as usize);
+    let (new_w, new_h) = (new_width as usize, new_height as usize);
+    if (w, h) == (new_w, new_h) {
+        return Ok(data.to_vec());
+    }
+
+    // Fast path: shrink by whole factors with a box average first, leaving at most ~2x (or, for
+    // area averaging with exact multiples, nothing) for the real filter
+    let factor = |src: usize, dst: usize| {
+        if filter == ResizeFilter::Area && src.is_multiple_of(dst) {
+            src / dst
+        } else {
+            (src / dst / 2).max(1)
+        }
+    };
+    let (fx, fy) = (factor(w, new_w), factor(h, new_h));
+    let reduced;
+    let mut source = data;
+    if fx > 1 || fy > 1 {
+        let (buffer, rw, rh) = reduce_box(data, w, h, fx, fy);
+        reduced = buffer;
+        source = &reduced;
+        w = rw;
+        h = rh;
+        if (w, h) == (new_w, new_h) {
+            return Ok(reduced);
+        }
+    }
+
+    let premultiplied: Vec<f32> = source
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let a = p[3] as f32 / 255.0;
+            [p[0] as f32 * a, p[1] as f32 * a, p[2] as f32 * a, p[3] as f32]
+        })
+        .collect();
+
+    // Horizontal pass: w x h -> new_w x h
+    let columns = contributions(w, new_w, filter);
+    let mut horizontal = vec![0f32; new_w * h * 4];
+    for y in 0..h {
+        let row = &premultiplied[y * w * 4..(y + 1) * w * 4];
+        for (x, contribution) in columns.iter().enumerate() {
+            let mut acc = [0f32; 4];
+            for (i, &weight) in contribution.weights.iter().enumerate() {
+                let p = &row[(contribution.start + i) * 4..][..4];
+                for c in 0..4 {
+                    acc[c] += p[c] * weight;
+                }
+            }
+            horizontal[(y * new_w + x) * 4..][..4].copy_from_slice(&acc);
+        }
+    }
+
+    // Vertical pass: new_w x h -> new_w x new_h, then back to straight alpha
+    let rows = contributions(h, new_h, filter);
+    let mut out = vec![0u8; new_w * new_h * 4];
+    for (y, contribution) in rows.iter().enumerate() {
+        for x in 0..new_w {
+            let mut acc = [0f32; 4];
+            for (i, &weight) in contribution.weights.iter().enumerate() {
+                let p = &horizontal[((contribution.start + i) * new_w + x) * 4..][..4];
+                for c in 0..4 {
+                    acc[c] += p[c] * weight;
+                }
+            }
+
+            let alpha = acc[3].clamp(0.0, 255.0);
+            let dst = &mut out[(y * new_w + x) * 4..][..4];
+            if alpha > 0.0 {
+                let unpremultiply = 255.0 / alpha;
+                for c in 0..3 {
+                    dst[c] = (acc[c] * unpremultiply + 0.5).clamp(0.0, 255.0) as u8;
+                }
+            }
+            dst[3] = (alpha + 0.5) as u8;
+        }
+    }
+
+    Ok(out)
+}
+
+// Resize an RGBA buffer; filter is "bilinear", "bicubic", "lanczos3" or "area"
+#[wasm_bindgen]
+pub fn resize(data: &[u8], width: u32, height: u32, new_width: u32, new_height: u32, filter: &str) -> Result<Vec<u8>, JsValue> {
+    let filter = ResizeFilter::from_name(filter).ok_or_else(|| JsValue::from_str(&format!("Unknown resize filter: {}", filter)))?;
+    resize_rgba(data, width, height, new_width, new_height, filter).map_err(|e| JsValue::from_str(&e))
+}
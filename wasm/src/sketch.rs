@@ -0,0 +1,263 @@
+use crate::{fnv1a_hash, simple_hash};
+use wasm_bindgen::prelude::*;
+
+// Murmur3 finalizer to spread the low-quality bits of the demo hashes
+fn mix32(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85EBCA6B);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xC2B2AE35);
+    h ^ (h >> 16)
+}
+
+// Kirsch-Mitzenmacher double hashing: position i = h1 + i * h2 (mod m)
+fn double_hash_positions(item: &str, count: u32, modulus: u32) -> Vec<u32> {
+    let h1 = mix32(fnv1a_hash(item)) as u64;
+    // An odd step never collapses to a single slot when the table size is a power of two
+    let h2 = (mix32(simple_hash(item)) | 1) as u64;
+    (0..count as u64)
+        .map(|i| ((h1 + i * h2) % modulus as u64) as u32)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    size: u32,
+    hash_count: u32,
+    item_count: u32,
+}
+
+#[wasm_bindgen]
+impl BloomFilter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(size_bits: u32, hash_count: u32) -> BloomFilter {
+        let size = size_bits.max(8);
+        BloomFilter {
+            bits: vec![0u8; size.div_ceil(8) as usize],
+            size,
+            hash_count: hash_count.clamp(1, 32),
+            item_count: 0,
+        }
+    }
+
+    // Optimal m = -n ln p / (ln 2)^2 and k = m/n ln 2 for the expected load and target error rate
+    #[wasm_bindgen]
+    pub fn with_capacity(expected_items: u32, false_positive_rate: f64) -> BloomFilter {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-n * p.ln() / (ln2 * ln2)).ceil();
+        let k = (m / n * ln2).round();
+        BloomFilter::new(m as u32, k as u32)
+    }
+
+    #[wasm_bindgen]
+    pub fn insert(&mut self, item: &str) {
+        for pos in double_hash_positions(item, self.hash_count, self.size) {
+            self.bits[(pos / 8) as usize] |= 1 << (pos % 8);
+        }
+        self.item_count += 1;
+    }
+
+    #[wasm_bindgen]
+    pub fn contains(&self, item: &str) -> bool {
+        double_hash_positions(item, self.hash_count, self.size)
+            .iter()
+            .all(|&pos| self.bits[(pos / 8) as usize] & (1 << (pos % 8)) != 0)
+    }
+
+    // Bit indices touched by an item, for highlighting in the visualization
+    #[wasm_bindgen]
+    pub fn hash_positions(&self, item: &str) -> Vec<u32> {
+        double_hash_positions(item, self.hash_count, self.size)
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|b| *b = 0);
+        self.item_count = 0;
+    }
+
+    // One entry per bit (0 or 1) so the array can be drawn directly as a grid
+    #[wasm_bindgen]
+    pub fn get_bits(&self) -> Vec<u8> {
+        (0..self.size)
+            .map(|pos| (self.bits[(pos / 8) as usize] >> (pos % 8)) & 1)
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_size(&self) -> u32 {
+        self.size
+    }
+
+    #[wasm_bindgen]
+    pub fn get_hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    #[wasm_bindgen]
+    pub fn get_item_count(&self) -> u32 {
+        self.item_count
+    }
+
+    #[wasm_bindgen]
+    pub fn get_fill_ratio(&self) -> f64 {
+        let set: u32 = self.bits.iter().map(|b| b.count_ones()).sum();
+        set as f64 / self.size as f64
+    }
+
+    // (1 - e^(-kn/m))^k for the current load
+    #[wasm_bindgen]
+    pub fn theoretical_false_positive_rate(&self) -> f64 {
+        let k = self.hash_count as f64;
+        let exponent = -k * self.item_count as f64 / self.size as f64;
+        (1.0 - exponent.exp()).powf(k)
+    }
+
+    // Probe items that were never inserted: [measured_rate, theoretical_rate, tested, false_positives]
+    #[wasm_bindgen]
+    pub fn false_positive_report(&self, test_items: Vec<String>) -> Vec<f64> {
+        let false_positives = test_items.iter().filter(|item| self.contains(item)).count();
+        let tested = test_items.len();
+        let measured = if tested == 0 { 0.0 } else { false_positives as f64 / tested as f64 };
+
+        vec![
+            measured,
+            self.theoretical_false_positive_rate(),
+            tested as f64,
+            false_positives as f64,
+        ]
+    }
+}
+
+// Self-contained experiment with disjoint insert/probe sets ("item{i}" vs "probe{i}");
+// returns the same layout as BloomFilter::false_positive_report
+#[wasm_bindgen]
+pub fn bloom_false_positive_experiment(size_bits: u32, hash_count: u32, insert_count: u32, probe_count: u32) -> Vec<f64> {
+    let mut filter = BloomFilter::new(size_bits, hash_count);
+    for i in 0..insert_count {
+        filter.insert(&format!("item{}", i));
+    }
+    filter.false_positive_report((0..probe_count).map(|i| format!("probe{}", i)).collect())
+}
+
+#[wasm_bindgen]
+pub struct CountMinSketch {
+    counters: Vec<u32>,
+    width: u32,
+    depth: u32,
+    total: u64,
+}
+
+#[wasm_bindgen]
+impl CountMinSketch {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, depth: u32) -> CountMinSketch {
+        let width = width.max(1);
+        let depth = depth.clamp(1, 32);
+        CountMinSketch {
+            counters: vec![0; (width * depth) as usize],
+            width,
+            depth,
+            total: 0,
+        }
+    }
+
+    // width = e/epsilon, depth = ln(1/delta): estimates exceed the truth by at most epsilon * N
+    // with probability 1 - delta
+    #[wasm_bindgen]
+    pub fn with_error(epsilon: f64, delta: f64) -> CountMinSketch {
+        let width = (std::f64::consts::E / epsilon.clamp(1e-6, 1.0)).ceil();
+        let depth = (1.0 / delta.clamp(1e-9, 0.99)).ln().ceil();
+        CountMinSketch::new(width as u32, depth as u32)
+    }
+
+    #[wasm_bindgen]
+    pub fn insert(&mut self, item: &str, count: u32) {
+        // Row r uses the r-th double-hash position folded into its own width-sized slice
+        for (row, pos) in double_hash_positions(item, self.depth, self.width).into_iter().enumerate() {
+            let idx = row * self.width as usize + pos as usize;
+            self.counters[idx] = self.counters[idx].saturating_add(count);
+        }
+        self.total += count as u64;
+    }
+
+    #[wasm_bindgen]
+    pub fn estimate(&self, item: &str) -> u32 {
+        double_hash_positions(item, self.depth, self.width)
+            .into_iter()
+            .enumerate()
+            .map(|(row, pos)| self.counters[row * self.width as usize + pos as usize])
+            .min()
+            .unwrap_or(0)
+    }
+
+    #[wasm_bindgen]
+    pub fn hash_positions(&self, item: &str) -> Vec<u32> {
+        double_hash_positions(item, self.depth, self.width)
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c = 0);
+        self.total = 0;
+    }
+
+    // depth rows of width counters, row-major
+    #[wasm_bindgen]
+    pub fn get_counters(&self) -> Vec<u32> {
+        self.counters.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_depth(&self) -> u32 {
+        self.depth
+    }
+
+    #[wasm_bindgen]
+    pub fn get_total(&self) -> f64 {
+        self.total as f64
+    }
+
+    // Additive error bound epsilon * N, where epsilon = e / width
+    #[wasm_bindgen]
+    pub fn error_bound(&self) -> f64 {
+        std::f64::consts::E / self.width as f64 * self.total as f64
+    }
+
+    // Probability that an estimate exceeds the bound, e^-depth
+    #[wasm_bindgen]
+    pub fn failure_probability(&self) -> f64 {
+        (-(self.depth as f64)).exp()
+    }
+
+    // Compare estimates against known counts:
+    // [mean_overestimate, max_overestimate, error_bound, fraction_within_bound, failure_probability]
+    #[wasm_bindgen]
+    pub fn overestimate_report(&self, items: Vec<String>, true_counts: Vec<u32>) -> Vec<f64> {
+        let bound = self.error_bound();
+        let mut sum = 0.0;
+        let mut max = 0.0f64;
+        let mut within = 0usize;
+        let tested = items.len().min(true_counts.len());
+
+        for (item, &truth) in items.iter().zip(&true_counts) {
+            let over = self.estimate(item).saturating_sub(truth) as f64;
+            sum += over;
+            max = max.max(over);
+            if over <= bound {
+                within += 1;
+            }
+        }
+
+        let tested_f = tested.max(1) as f64;
+        vec![sum / tested_f, max, bound, within as f64 / tested_f, self.failure_probability()]
+    }
+}
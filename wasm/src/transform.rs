@@ -0,0 +1,501 @@
+use crate::resize::ResizeFilter;
+use crate::{alloc_rgba, MAX_IMAGE_PIXELS};
+use wasm_bindgen::prelude::*;
+
+// How coordinates outside the image are resolved when sampling or filtering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BorderMode {
+    // Repeat the edge pixel
+    Clamp,
+    // Tile the image
+    Wrap,
+    // Reflect with the edge pixel repeated (... c b a | a b c ...)
+    Mirror,
+    // Transparent black
+    Zero,
+}
+
+impl BorderMode {
+    pub(crate) fn from_name(name: &str) -> Option<BorderMode> {
+        match name {
+            "clamp" => Some(BorderMode::Clamp),
+            "wrap" => Some(BorderMode::Wrap),
+            "mirror" => Some(BorderMode::Mirror),
+            "zero" => Some(BorderMode::Zero),
+            _ => None,
+        }
+    }
+
+    // Map a possibly out-of-range index into 0..size, or None for Zero borders
+    pub(crate) fn resolve(self, index: i64, size: usize) -> Option<usize> {
+        let size = size as i64;
+        if (0..size).contains(&index) {
+            return Some(index as usize);
+        }
+        match self {
+            BorderMode::Clamp => Some(index.clamp(0, size - 1) as usize),
+            BorderMode::Wrap => Some(index.rem_euclid(size) as usize),
+            BorderMode::Mirror => {
+                let m = index.rem_euclid(2 * size);
+                Some(if m >= size { 2 * size - 1 - m } else { m } as usize)
+            }
+            BorderMode::Zero => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Sampling {
+    Nearest,
+    Bilinear,
+    Bicubic,
+}
+
+impl Sampling {
+    pub(crate) fn from_name(name: &str) -> Option<Sampling> {
+        match name {
+            "nearest" => Some(Sampling::Nearest),
+            "bilinear" => Some(Sampling::Bilinear),
+            "bicubic" => Some(Sampling::Bicubic),
+            _ => None,
+        }
+    }
+}
+
+fn parse_options(sampling: &str, border: &str) -> Result<(Sampling, BorderMode), String> {
+    let sampling = Sampling::from_name(sampling).ok_or_else(|| format!("Unknown sampling mode: {}", sampling))?;
+    let border = BorderMode::from_name(border).ok_or_else(|| format!("Unknown border mode: {}", border))?;
+    Ok((sampling, border))
+}
+
+fn check_size(data: &[u8], width: u32, height: u32) -> Result<(), String> {
+    if data.len() as u64 != width as u64 * height as u64 * 4 {
+        return Err("Image data length does not match width * height * 4".to_string());
+    }
+    Ok(())
+}
+
+// Result of a transform whose output size is computed rather than given
+#[wasm_bindgen]
+pub struct TransformedImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl TransformedImage {
+    #[wasm_bindgen]
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen]
+    pub fn get_pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+impl TransformedImage {
+    pub(crate) fn into_parts(self) -> (u32, u32, Vec<u8>) {
+        (self.width, self.height, self.pixels)
+    }
+}
+
+pub(crate) fn crop_rgba(data: &[u8], width: u32, height: u32, x: u32, y: u32, crop_width: u32, crop_height: u32) -> Result<Vec<u8>, String> {
+    check_size(data, width, height)?;
+    // Checked so huge offsets from JS can't wrap past the bounds test
+    let outside = |start: u32, size: u32, limit: u32| start.checked_add(size).is_none_or(|end| end > limit);
+    if crop_width == 0 || crop_height == 0 || outside(x, crop_width, width) || outside(y, crop_height, height) {
+        return Err("Crop rectangle must be non-empty and inside the image".to_string());
+    }
+
+    let (w, x, cw) = (width as usize, x as usize, crop_width as usize);
+    let mut out = Vec::with_capacity(cw * crop_height as usize * 4);
+    for row in y as usize..y as usize + crop_height as usize {
+        out.extend_from_slice(&data[(row * w + x) * 4..(row * w + x + cw) * 4]);
+    }
+    Ok(out)
+}
+
+// Copy every pixel to the position given by dest(x, y) in an out_w-wide buffer
+fn remap(data: &[u8], width: u32, height: u32, out_w: usize, dest: impl Fn(usize, usize) -> (usize, usize)) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let mut out = vec![0u8; w * h * 4];
+    if data.len() != out.len() {
+        return out;
+    }
+    for y in 0..h {
+        for x in 0..w {
+            let (dx, dy) = dest(x, y);
+            out[(dy * out_w + dx) * 4..][..4].copy_from_slice(&data[(y * w + x) * 4..][..4]);
+        }
+    }
+    out
+}
+
+// Clockwise quarter turn; the result is height x width
+#[wasm_bindgen]
+pub fn rotate_90(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let h = height as usize;
+    remap(data, width, height, h, |x, y| (h - 1 - y, x))
+}
+
+#[wasm_bindgen]
+pub fn rotate_180(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    remap(data, width, height, w, |x, y| (w - 1 - x, h - 1 - y))
+}
+
+// Counter-clockwise quarter turn; the result is height x width
+#[wasm_bindgen]
+pub fn rotate_270(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    remap(data, width, height, h, |x, y| (y, w - 1 - x))
+}
+
+// Mirror left-right in place
+#[wasm_bindgen]
+pub fn flip_horizontal(data: &mut [u8], width: u32, height: u32) {
+    if data.len() != (width * height * 4) as usize {
+        return;
+    }
+    let w = width as usize;
+    for row in data.chunks_exact_mut(w * 4) {
+        for x in 0..w / 2 {
+            for c in 0..4 {
+                row.swap(x * 4 + c, (w - 1 - x) * 4 + c);
+            }
+        }
+    }
+}
+
+// Mirror top-bottom in place
+#[wasm_bindgen]
+pub fn flip_vertical(data: &mut [u8], width: u32, height: u32) {
+    if data.len() != (width * height * 4) as usize {
+        return;
+    }
+    let stride = width as usize * 4;
+    let h = height as usize;
+    for y in 0..h / 2 {
+        let (top, bottom) = data.split_at_mut((h - 1 - y) * stride);
+        top[y * stride..(y + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+    }
+}
+
+// Sample at a continuous position (pixel centers at integer coordinates). Interpolation runs on
+// premultiplied values so transparent pixels and Zero borders don't bleed dark fringes.
+fn sample(data: &[u8], width: usize, height: usize, x: f64, y: f64, sampling: Sampling, border: BorderMode) -> [u8; 4] {
+    let mut xs = [(0i64, 0f32); 4];
+    let mut ys = [(0i64, 0f32); 4];
+    let taps = |pos: f64, out: &mut [(i64, f32); 4]| -> usize {
+        let base = pos.floor();
+        let t = (pos - base) as f32;
+        let base = base as i64;
+        match sampling {
+            Sampling::Nearest => {
+                out[0] = (pos.round() as i64, 1.0);
+                1
+            }
+            Sampling::Bilinear => {
+                out[0] = (base, 1.0 - t);
+                out[1] = (base + 1, t);
+                2
+            }
+            Sampling::Bicubic => {
+                for (i, tap) in out.iter_mut().enumerate() {
+                    let offset = i as i64 - 1;
+                    *tap = (base + offset, ResizeFilter::Bicubic.weight(t - offset as f32));
+                }
+                4
+            }
+        }
+    };
+    let nx = taps(x, &mut xs);
+    let ny = taps(y, &mut ys);
+
+    let mut acc = [0f32; 4];
+    for &(yi, wy) in &ys[..ny] {
+        let Some(sy) = border.resolve(yi, height) else { continue };
+        for &(xi, wx) in &xs[..nx] {
+            let Some(sx) = border.resolve(xi, width) else { continue };
+            let p = &data[(sy * width + sx) * 4..][..4];
+            let weight = wx * wy;
+            let alpha = p[3] as f32 * weight;
+            acc[0] += p[0] as f32 * alpha;
+            acc[1] += p[1] as f32 * alpha;
+            acc[2] += p[2] as f32 * alpha;
+            acc[3] += alpha;
+        }
+    }
+
+    let alpha = acc[3].clamp(0.0, 255.0);
+    if alpha <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+    let inv = 1.0 / acc[3];
+    [
+        (acc[0] * inv + 0.5).clamp(0.0, 255.0) as u8,
+        (acc[1] * inv + 0.5).clamp(0.0, 255.0) as u8,
+        (acc[2] * inv + 0.5).clamp(0.0, 255.0) as u8,
+        (alpha + 0.5) as u8,
+    ]
+}
+
+fn invert_3x3(m: &[f64; 9]) -> Option<[f64; 9]> {
+    let det = m[0] * (m[4] * m[8] - m[5] * m[7]) - m[1] * (m[3] * m[8] - m[5] * m[6]) + m[2] * (m[3] * m[7] - m[4] * m[6]);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    Some([
+        (m[4] * m[8] - m[5] * m[7]) * inv,
+        (m[2] * m[7] - m[1] * m[8]) * inv,
+        (m[1] * m[5] - m[2] * m[4]) * inv,
+        (m[5] * m[6] - m[3] * m[8]) * inv,
+        (m[0] * m[8] - m[2] * m[6]) * inv,
+        (m[2] * m[3] - m[0] * m[5]) * inv,
+        (m[3] * m[7] - m[4] * m[6]) * inv,
+        (m[1] * m[6] - m[0] * m[7]) * inv,
+        (m[0] * m[4] - m[1] * m[3]) * inv,
+    ])
+}
+
+// Inverse-map every output pixel through a forward homography (source -> destination)
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn warp_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f64; 9],
+    out_width: u32,
+    out_height: u32,
+    sampling: Sampling,
+    border: BorderMode,
+) -> Result<Vec<u8>, String> {
+    check_size(data, width, height)?;
+    if width == 0 || height == 0 || out_width == 0 || out_height == 0 {
+        return Err("Image dimensions must be non-zero".to_string());
+    }
+    let inv = invert_3x3(matrix).ok_or("Transform matrix is not invertible")?;
+
+    let (w, h) = (width as usize, height as usize);
+    let mut out = alloc_rgba(out_width, out_height)?;
+    for oy in 0..out_height as usize {
+        for ox in 0..out_width as usize {
+            let (x, y) = (ox as f64, oy as f64);
+            let z = inv[6] * x + inv[7] * y + inv[8];
+            if z.abs() < 1e-12 {
+                continue;
+            }
+            let sx = (inv[0] * x + inv[1] * y + inv[2]) / z;
+            let sy = (inv[3] * x + inv[4] * y + inv[5]) / z;
+            let pixel = sample(data, w, h, sx, sy, sampling, border);
+            out[(oy * out_width as usize + ox) * 4..][..4].copy_from_slice(&pixel);
+        }
+    }
+    Ok(out)
+}
+
+// Clockwise rotation by any angle about the image center; with expand the canvas grows to fit
+// the rotated corners, otherwise the size is kept and the corners are cut off
+pub(crate) fn rotate_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    degrees: f64,
+    expand: bool,
+    sampling: Sampling,
+    border: BorderMode,
+) -> Result<TransformedImage, String> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (out_w, out_h) = if expand {
+        // Shave off float noise so exact quarter turns don't gain a pixel
+        let fit = |a: f64, b: f64| ((a + b) - 1e-6).ceil().max(1.0) as u32;
+        (
+            fit(width as f64 * cos.abs(), height as f64 * sin.abs()),
+            fit(width as f64 * sin.abs(), height as f64 * cos.abs()),
+        )
+    } else {
+        (width, height)
+    };
+
+    let (cx, cy) = ((width as f64 - 1.0) / 2.0, (height as f64 - 1.0) / 2.0);
+    let (ox, oy) = ((out_w as f64 - 1.0) / 2.0, (out_h as f64 - 1.0) / 2.0);
+    // dest = R (src - c) + o, with y pointing down so positive angles turn clockwise on screen
+    let matrix = [
+        cos,
+        -sin,
+        ox - cos * cx + sin * cy,
+        sin,
+        cos,
+        oy - sin * cx - cos * cy,
+        0.0,
+        0.0,
+        1.0,
+    ];
+    let pixels = warp_rgba(data, width, height, &matrix, out_w, out_h, sampling, border)?;
+    Ok(TransformedImage {
+        width: out_w,
+        height: out_h,
+        pixels,
+    })
+}
+
+// Homography taking the four src points onto the four dst points (x0, y0, ..., x3, y3),
+// from the standard 8x8 direct linear transform with h33 = 1
+pub(crate) fn homography_from_points(src: &[f64], dst: &[f64]) -> Result<[f64; 9], String> {
+    if src.len() != 8 || dst.len() != 8 {
+        return Err("Expected four points as 8 coordinates".to_string());
+    }
+
+    let mut a = [[0f64; 9]; 8];
+    for i in 0..4 {
+        let (x, y) = (src[i * 2], src[i * 2 + 1]);
+        let (u, v) = (dst[i * 2], dst[i * 2 + 1]);
+        a[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        a[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
+
+    // Gaussian elimination with partial pivoting on the augmented matrix
+    for col in 0..8 {
+        let pivot = (col..8)
+            .max_by(|&r, &s| a[r][col].abs().total_cmp(&a[s][col].abs()))
+            .unwrap_or(col);
+        if a[pivot][col].abs() < 1e-10 {
+            return Err("Points are degenerate (three or more are collinear)".to_string());
+        }
+        a.swap(col, pivot);
+        let pivot_row = a[col];
+        for (r, row) in a.iter_mut().enumerate() {
+            if r != col {
+                let factor = row[col] / pivot_row[col];
+                for (value, &p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * p;
+                }
+            }
+        }
+    }
+
+    let mut h = [1f64; 9];
+    for i in 0..8 {
+        h[i] = a[i][8] / a[i][i];
+    }
+    Ok(h)
+}
+
+// Map the quadrilateral `corners` (top-left, top-right, bottom-right, bottom-left) onto an
+// upright out_width x out_height rectangle. A zero size is estimated from the longer of each
+// pair of opposite edges.
+pub(crate) fn perspective_correct_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    corners: &[f64],
+    out_width: u32,
+    out_height: u32,
+    sampling: Sampling,
+) -> Result<TransformedImage, String> {
+    if corners.len() != 8 {
+        return Err("Expected four corners as 8 coordinates".to_string());
+    }
+    let dist = |i: usize, j: usize| (corners[i * 2] - corners[j * 2]).hypot(corners[i * 2 + 1] - corners[j * 2 + 1]);
+    // Corners sit on pixel centers, so an edge of length n spans n + 1 pixels; checked because
+    // corners from JS can be arbitrarily far apart
+    let span = |edge: f64| {
+        (edge.is_finite() && edge < MAX_IMAGE_PIXELS as f64)
+            .then(|| edge.round() as u32 + 1)
+            .ok_or_else(|| "Perspective corners are too far apart".to_string())
+    };
+    let out_w = if out_width == 0 { span(dist(0, 1).max(dist(3, 2)))? } else { out_width };
+    let out_h = if out_height == 0 { span(dist(0, 3).max(dist(1, 2)))? } else { out_height };
+
+    let (right, bottom) = (out_w as f64 - 1.0, out_h as f64 - 1.0);
+    let target = [0.0, 0.0, right, 0.0, right, bottom, 0.0, bottom];
+    let matrix = homography_from_points(corners, &target)?;
+    let pixels = warp_rgba(data, width, height, &matrix, out_w, out_h, sampling, BorderMode::Clamp)?;
+    Ok(TransformedImage {
+        width: out_w,
+        height: out_h,
+        pixels,
+    })
+}
+
+fn to_js<T>(result: Result<T, String>) -> Result<T, JsValue> {
+    result.map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn crop(data: &[u8], width: u32, height: u32, x: u32, y: u32, crop_width: u32, crop_height: u32) -> Result<Vec<u8>, JsValue> {
+    to_js(crop_rgba(data, width, height, x, y, crop_width, crop_height))
+}
+
+// sampling: "nearest", "bilinear" or "bicubic"; border: "clamp", "wrap", "mirror" or "zero"
+#[wasm_bindgen]
+pub fn rotate(data: &[u8], width: u32, height: u32, degrees: f64, expand: bool, sampling: &str, border: &str) -> Result<TransformedImage, JsValue> {
+    to_js(parse_options(sampling, border).and_then(|(s, b)| rotate_rgba(data, width, height, degrees, expand, s, b)))
+}
+
+// Forward affine matrix [a, b, tx, c, d, ty]: x' = a x + b y + tx, y' = c x + d y + ty
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn warp_affine(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f64],
+    out_width: u32,
+    out_height: u32,
+    sampling: &str,
+    border: &str,
+) -> Result<Vec<u8>, JsValue> {
+    if matrix.len() != 6 {
+        return Err(JsValue::from_str("Affine matrix needs 6 values"));
+    }
+    let full = [matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5], 0.0, 0.0, 1.0];
+    to_js(parse_options(sampling, border).and_then(|(s, b)| warp_rgba(data, width, height, &full, out_width, out_height, s, b)))
+}
+
+// Forward 3x3 homography, row-major
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn warp_perspective(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f64],
+    out_width: u32,
+    out_height: u32,
+    sampling: &str,
+    border: &str,
+) -> Result<Vec<u8>, JsValue> {
+    let full: [f64; 9] = matrix.try_into().map_err(|_| JsValue::from_str("Homography needs 9 values"))?;
+    to_js(parse_options(sampling, border).and_then(|(s, b)| warp_rgba(data, width, height, &full, out_width, out_height, s, b)))
+}
+
+// Row-major 3x3 homography mapping four source points onto four destination points
+#[wasm_bindgen]
+pub fn perspective_matrix(src_points: &[f64], dst_points: &[f64]) -> Result<Vec<f64>, JsValue> {
+    to_js(homography_from_points(src_points, dst_points)).map(|h| h.to_vec())
+}
+
+// Straighten a photographed document/screen given its corners (TL, TR, BR, BL)
+#[wasm_bindgen]
+pub fn perspective_correct(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    corners: &[f64],
+    out_width: u32,
+    out_height: u32,
+    sampling: &str,
+) -> Result<TransformedImage, JsValue> {
+    let sampling = Sampling::from_name(sampling).ok_or_else(|| JsValue::from_str(&format!("Unknown sampling mode: {}", sampling)))?;
+    to_js(perspective_correct_rgba(data, width, height, corners, out_width, out_height, sampling))
+}
@@ -0,0 +1,170 @@
+use crate::pipeline::Stage;
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+// Where filter arithmetic happens. sRGB bytes are perceptually spaced, so averaging them (blur,
+// resampling) darkens edges and scaling them doesn't scale light; linear light fixes both at
+// the cost of a decode/encode around the filter.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) enum WorkingSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl WorkingSpace {
+    pub(crate) fn from_name(name: &str) -> Option<WorkingSpace> {
+        match name {
+            "srgb" => Some(WorkingSpace::Srgb),
+            "linear" => Some(WorkingSpace::Linear),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            WorkingSpace::Srgb => "srgb",
+            WorkingSpace::Linear => "linear",
+        }
+    }
+}
+
+pub(crate) fn parse_working_space(name: &str) -> Result<WorkingSpace, String> {
+    WorkingSpace::from_name(name).ok_or_else(|| format!("Unknown working space: {} (expected srgb or linear)", name))
+}
+
+// Sample type the classic filters are written against: sRGB bytes, quantized exactly as the
+// filters always have, or linear-light floats on the same 0-255 scale that are only clamped,
+// so chained filters don't lose precision in the shadows
+pub(crate) trait Channel: Copy + Default {
+    fn value(self) -> f32;
+    // Store a result the way the byte filters truncate (`as u8`)
+    fn truncate(v: f32) -> Self;
+    // Store a result the way the byte filters round
+    fn round(v: f32) -> Self;
+}
+
+impl Channel for u8 {
+    fn value(self) -> f32 {
+        self as f32
+    }
+
+    fn truncate(v: f32) -> u8 {
+        v as u8
+    }
+
+    fn round(v: f32) -> u8 {
+        v.round() as u8
+    }
+}
+
+impl Channel for f32 {
+    fn value(self) -> f32 {
+        self
+    }
+
+    fn truncate(v: f32) -> f32 {
+        v.clamp(0.0, 255.0)
+    }
+
+    fn round(v: f32) -> f32 {
+        v.clamp(0.0, 255.0)
+    }
+}
+
+// sRGB transfer curve on 0-1 values
+pub(crate) fn decode_srgb(c: f64) -> f64 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub(crate) fn encode_srgb(l: f64) -> f64 {
+    if l <= 0.0031308 { l * 12.92 } else { 1.055 * l.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Linear-light value (0-255 scale) of every sRGB byte
+fn decode_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0f32; 256];
+        for (i, v) in table.iter_mut().enumerate() {
+            *v = (decode_srgb(i as f64 / 255.0) * 255.0) as f32;
+        }
+        table
+    })
+}
+
+// sRGB byte for 16-bit linear values; fine enough that every byte round-trips exactly
+const ENCODE_STEPS: usize = 65536;
+
+fn encode_table() -> &'static [u8] {
+    static TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        (0..ENCODE_STEPS)
+            .map(|i| {
+                let c = encode_srgb(i as f64 / (ENCODE_STEPS - 1) as f64);
+                (c * 255.0).round().clamp(0.0, 255.0) as u8
+            })
+            .collect()
+    })
+}
+
+// Decode RGBA bytes to linear floats; alpha is not gamma-encoded and is copied as is
+pub(crate) fn decode_linear(data: &[u8]) -> Vec<f32> {
+    let table = decode_table();
+    data.iter()
+        .enumerate()
+        .map(|(i, &v)| if i % 4 == 3 { v as f32 } else { table[v as usize] })
+        .collect()
+}
+
+pub(crate) fn encode_linear(values: &[f32], data: &mut [u8]) {
+    let table = encode_table();
+    let scale = (ENCODE_STEPS - 1) as f32 / 255.0;
+    for (i, (d, &v)) in data.iter_mut().zip(values).enumerate() {
+        *d = if i % 4 == 3 {
+            v.round().clamp(0.0, 255.0) as u8
+        } else {
+            table[(v * scale).round().clamp(0.0, (ENCODE_STEPS - 1) as f32) as usize]
+        };
+    }
+}
+
+// Run one of the classic filters in the given working space
+pub(crate) fn run_stage(stage: &Stage, data: &mut [u8], width: u32, height: u32, space: WorkingSpace) {
+    match space {
+        WorkingSpace::Srgb => stage.run(data, width, height),
+        WorkingSpace::Linear => {
+            let mut values = decode_linear(data);
+            stage.run(&mut values, width, height);
+            encode_linear(&values, data);
+        }
+    }
+}
+
+// Decode sRGB RGBA bytes to linear light on a 0-1 scale (alpha scaled to 0-1 too)
+#[wasm_bindgen]
+pub fn srgb_to_linear(data: &[u8]) -> Vec<f32> {
+    decode_linear(data).iter().map(|v| v / 255.0).collect()
+}
+
+// Encode 0-1 linear-light RGBA floats back to sRGB bytes
+#[wasm_bindgen]
+pub fn linear_to_srgb(values: &[f32]) -> Vec<u8> {
+    let scaled: Vec<f32> = values.iter().map(|v| v * 255.0).collect();
+    let mut out = vec![0u8; values.len()];
+    encode_linear(&scaled, &mut out);
+    out
+}
+
+// Apply one of the classic filters by name (as ImageProcessor::apply) in "srgb" or "linear"
+// working space
+#[wasm_bindgen]
+pub fn apply_filter(data: &mut [u8], width: u32, height: u32, op: &str, value: f32, working_space: &str) -> Result<(), JsValue> {
+    let space = parse_working_space(working_space).map_err(|e| JsValue::from_str(&e))?;
+    let stage = Stage::from_op(op, value).ok_or_else(|| JsValue::from_str(&format!("Unknown image operation: {}", op)))?;
+    if data.len() != (width * height * 4) as usize {
+        return Err(JsValue::from_str("Image data length does not match width * height * 4"));
+    }
+    run_stage(&stage, data, width, height, space);
+    Ok(())
+}